@@ -0,0 +1,156 @@
+use std::fs;
+
+use bestagons::i18n;
+use bestagons::puzzle::difficulty::TierScheme;
+use bestagons::puzzle::library::{Filter, Library, LibraryError};
+
+use super::{difficulty_message, Args};
+
+// Manages the local puzzle library, kept in the platform's data
+// directory unless another `--dir` is given:
+//
+//     library               describes the library
+//     library import PACK   adds the puzzles of a binary pack that the
+//                           library does not hold yet
+//     library play ID       marks the puzzle as played
+//     library next          prints the first unplayed puzzle of the
+//                           `--radius` and `--tier`, or of difficulty
+//                           between `--min-difficulty` and
+//                           `--max-difficulty`, when given
+pub fn run(args: &Args) -> Result<(), String> {
+    let arguments: Vec<&str> = args.arguments().iter().map(String::as_str).collect();
+    match arguments[..] {
+        [] => {
+            args.expect(&["dir"], &[])?;
+            summary(&open(args)?);
+        }
+        ["import", path, ..] => {
+            args.expect_arguments(2, &["dir"], &[])?;
+            import(&mut open(args)?, path)?;
+        }
+        ["play", id, ..] => {
+            args.expect_arguments(2, &["dir"], &[])?;
+            let id = id
+                .parse()
+                .map_err(|_| i18n::message("library.unknown-puzzle", &[("id", &id)]))?;
+            let mut library = open(args)?;
+            library.mark_played(id).map_err(describe)?;
+            save(&library)?;
+        }
+        ["next", ..] => {
+            args.expect_arguments(
+                1,
+                &["dir", "radius", "tier", "min-difficulty", "max-difficulty"],
+                &[],
+            )?;
+            next(&open(args)?, &filter(args)?)?;
+        }
+        [command @ ("import" | "play")] => {
+            return Err(i18n::message(
+                "library.missing-argument",
+                &[("command", &command)],
+            ));
+        }
+        [argument, ..] => {
+            return Err(i18n::message(
+                "error.unexpected-argument",
+                &[("argument", &argument)],
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn open(args: &Args) -> Result<Library, String> {
+    match args.option("dir") {
+        Some(dir) => Library::open(dir),
+        None => Library::open_default(),
+    }
+    .map_err(|error| i18n::message("library.open-failed", &[("error", &describe(error))]))
+}
+
+fn summary(library: &Library) {
+    let played = library
+        .entries()
+        .iter()
+        .filter(|entry| entry.is_played())
+        .count();
+    println!(
+        "{}",
+        i18n::message(
+            "library.summary",
+            &[
+                ("count", &library.entries().len()),
+                ("path", &library.root().display()),
+                ("played", &played),
+            ],
+        )
+    );
+}
+
+fn import(library: &mut Library, path: &str) -> Result<(), String> {
+    let bytes = fs::read(path)
+        .map_err(|error| i18n::message("error.read", &[("path", &path), ("error", &error)]))?;
+    let count = library.import_pack(&bytes).map_err(|error| {
+        i18n::message(
+            "library.import-failed",
+            &[("path", &path), ("error", &describe(error))],
+        )
+    })?;
+    save(library)?;
+    println!(
+        "{}",
+        i18n::message("library.imported", &[("count", &count)])
+    );
+    Ok(())
+}
+
+// The filter of the `--radius`, the `--tier` of the default scheme, and
+// the difficulties between `--min-difficulty` and `--max-difficulty`.
+fn filter(args: &Args) -> Result<Filter, String> {
+    let mut filter = Filter::default();
+    if args.option("radius").is_some() {
+        filter = filter.with_radius(args.parsed("radius", 0)?);
+    }
+    if let Some(name) = args.option("tier") {
+        let tier = TierScheme::default()
+            .tier_by_name(name)
+            .cloned()
+            .ok_or_else(|| i18n::message("error.unknown-tier", &[("tier", &name)]))?;
+        filter = filter.with_tier(tier);
+    }
+    if args.option("min-difficulty").is_some() || args.option("max-difficulty").is_some() {
+        filter = filter.with_difficulty(
+            args.parsed("min-difficulty", f64::NEG_INFINITY)?,
+            args.parsed("max-difficulty", f64::INFINITY)?,
+        );
+    }
+
+    Ok(filter)
+}
+
+fn next(library: &Library, filter: &Filter) -> Result<(), String> {
+    let (id, entry) = library
+        .next(filter)
+        .ok_or_else(|| i18n::message("library.no-match", &[]))?;
+    println!("{}\n", i18n::message("library.puzzle", &[("id", &id)]));
+    println!("{}", entry.puzzle());
+    if let Some(difficulty) = entry.difficulty() {
+        println!("{}", difficulty_message(difficulty, entry.tier()));
+    }
+    Ok(())
+}
+
+fn save(library: &Library) -> Result<(), String> {
+    library
+        .save()
+        .map_err(|error| i18n::message("library.save-failed", &[("error", &describe(error))]))
+}
+
+fn describe(error: LibraryError) -> String {
+    match error {
+        LibraryError::UnknownPuzzle(id) => i18n::message("library.unknown-puzzle", &[("id", &id)]),
+        error => error.to_string(),
+    }
+}
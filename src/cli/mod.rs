@@ -0,0 +1,663 @@
+#[cfg(feature = "library")]
+pub mod library;
+#[cfg(feature = "generate")]
+pub mod new;
+#[cfg(feature = "server")]
+pub mod serve;
+#[cfg(feature = "generate")]
+pub mod stats;
+
+use std::collections::{HashMap, HashSet};
+#[cfg(any(feature = "generate", feature = "library", test))]
+use std::str::FromStr;
+#[cfg(feature = "generate")]
+use std::sync::Arc;
+#[cfg(feature = "generate")]
+use std::time::Duration;
+
+#[cfg(feature = "generate")]
+use rand::Rng;
+
+use bestagons::i18n::{self, Catalog, CatalogError};
+#[cfg(any(feature = "generate", feature = "library"))]
+use bestagons::puzzle::difficulty::{Difficulty, Tier};
+#[cfg(feature = "generate")]
+use bestagons::{
+    grid::{Axis, Distance},
+    puzzle::board::Board,
+    puzzle::difficulty::TierScheme,
+    puzzle::optimizer::{Annealer, Objective},
+    puzzle::puzzle::{Generator, GeneratorFn, Smoothed},
+    puzzle::refiner::{Budget, RefineError, Refiner},
+    puzzle::shapes::{Shape, ShapeGenerator},
+    puzzle::strategy::{Lookahead, LowestClue, MostConstrained, RandomCell, RefineStrategy},
+    puzzle::validator::{
+        DifficultyRange, MaximumSolvedClues, MaximumSolvedPositions, RequireClueSolving,
+        RequireHintSolving, Validator, ValidatorStrategy,
+    },
+    puzzle::{ClueKind, ClueMix, Palette},
+};
+
+// Command line arguments, made of an optional command and arguments of
+// its own, followed by options, given either as `--name value` or
+// `--name=value`, and switches, given as `--name`.
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    command: Option<String>,
+    arguments: Vec<String>,
+    options: HashMap<String, String>,
+    switches: HashSet<String>,
+}
+
+impl Args {
+    pub fn parse(arguments: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut args = Args::default();
+        let mut arguments = arguments.peekable();
+
+        while let Some(argument) = arguments.next() {
+            if let Some(name) = argument.strip_prefix("--") {
+                if let Some((name, value)) = name.split_once('=') {
+                    args.options.insert(name.to_string(), value.to_string());
+                } else if arguments.peek().is_some_and(|next| !next.starts_with("--")) {
+                    args.options
+                        .insert(name.to_string(), arguments.next().unwrap());
+                } else {
+                    args.switches.insert(name.to_string());
+                }
+            } else if args.options.is_empty() {
+                match args.command {
+                    None => args.command = Some(argument),
+                    Some(_) => args.arguments.push(argument),
+                }
+            } else {
+                return Err(i18n::message(
+                    "error.unexpected-argument",
+                    &[("argument", &argument)],
+                ));
+            }
+        }
+
+        Ok(args)
+    }
+
+    pub fn command(&self) -> Option<&str> {
+        self.command.as_deref()
+    }
+
+    // The arguments given after the command, such as its subcommand.
+    #[cfg(feature = "library")]
+    pub fn arguments(&self) -> &[String] {
+        &self.arguments
+    }
+
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    // Parses the value of an option, falling back to the given default
+    // when the option is not present.
+    #[cfg(any(feature = "generate", feature = "library", test))]
+    pub fn parsed<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.option(name) {
+            Some(value) => value.parse().map_err(|_| {
+                i18n::message("error.invalid-value", &[("value", &value), ("name", &name)])
+            }),
+            None => Ok(default),
+        }
+    }
+
+    #[cfg(any(feature = "generate", test))]
+    pub fn switch(&self, name: &str) -> bool {
+        self.switches.contains(name)
+    }
+
+    // Fails on arguments after the command, on options and switches the
+    // command does not accept, and on accepted options given without a
+    // value, which are parsed as switches. The `--lang` option is
+    // accepted by every command.
+    #[cfg(any(feature = "generate", feature = "library", feature = "server", test))]
+    pub fn expect(&self, options: &[&str], switches: &[&str]) -> Result<(), String> {
+        self.expect_arguments(0, options, switches)
+    }
+
+    // Like `expect`, but accepts up to the given number of arguments
+    // after the command.
+    #[cfg(any(feature = "generate", feature = "library", feature = "server", test))]
+    pub fn expect_arguments(
+        &self,
+        count: usize,
+        options: &[&str],
+        switches: &[&str],
+    ) -> Result<(), String> {
+        let unexpected = self
+            .arguments
+            .get(count)
+            .cloned()
+            .or_else(|| {
+                self.options
+                    .keys()
+                    .find(|name| *name != "lang" && !options.contains(&name.as_str()))
+                    .map(|name| format!("--{name}"))
+            })
+            .or_else(|| {
+                self.switches
+                    .iter()
+                    .find(|name| !switches.contains(&name.as_str()))
+                    .map(|name| format!("--{name}"))
+            });
+
+        match unexpected {
+            Some(argument) => Err(i18n::message(
+                "error.unexpected-argument",
+                &[("argument", &argument)],
+            )),
+            None => Ok(()),
+        }
+    }
+
+    // Fails when more than one of the given options is present, as they
+    // pick between alternatives that cannot be combined.
+    #[cfg(feature = "generate")]
+    pub fn exclusive(&self, names: &[&str]) -> Result<(), String> {
+        let mut present = names.iter().filter(|name| self.option(name).is_some());
+        match (present.next(), present.next()) {
+            (Some(option), Some(other)) => Err(i18n::message(
+                "error.conflicting-options",
+                &[("option", option), ("other", other)],
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "generate")]
+pub const DEFAULT_RADIUS: i32 = 5;
+
+// The options and switches of the generator, validator, refiner and
+// budget, accepted by every command that generates puzzles.
+#[cfg(feature = "generate")]
+pub const GENERATOR_OPTIONS: &[&str] = &[
+    "radius",
+    "shape",
+    "mirror",
+    "clustered",
+    "colors",
+    "smooth",
+    "clues",
+    "tier",
+    "objective",
+    "beam",
+    "strategy",
+    "polish",
+    "timeout",
+    "attempts",
+];
+#[cfg(feature = "generate")]
+pub const GENERATOR_SWITCHES: &[&str] = &["strengthen", "minimize"];
+
+// The catalog of messages in the `--lang` language, or else in the
+// language of the environment's locale.
+pub fn catalog(args: &Args) -> Result<Catalog, String> {
+    let locale = match args.option("lang") {
+        Some(language) => language.to_string(),
+        None => ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|locale| !locale.is_empty())
+            .unwrap_or_default(),
+    };
+    let language = i18n::language(&locale);
+
+    match i18n::locales() {
+        Some(directory) if !["", "en", "C", "POSIX"].contains(&language) => {
+            Catalog::load(&directory, language).map_err(|CatalogError::InvalidLine(line)| {
+                Catalog::english().message(
+                    "error.invalid-catalog",
+                    &[("line", &line), ("language", &language)],
+                )
+            })
+        }
+        _ => Ok(Catalog::english()),
+    }
+}
+
+// The name of the tier in the catalog's language, for the built-in tiers.
+#[cfg(any(feature = "generate", feature = "library"))]
+pub fn tier_name(tier: &Tier) -> String {
+    i18n::label(&format!("tier.{}", tier.name().to_lowercase()), tier.name())
+}
+
+// The rating of a puzzle, followed by the name and stars of its tier,
+// when the rating falls in one.
+#[cfg(any(feature = "generate", feature = "library"))]
+pub fn difficulty_message(difficulty: Difficulty, tier: Option<&Tier>) -> String {
+    let value = format!("{:.1}", difficulty.value());
+    match tier {
+        Some(tier) => i18n::message(
+            "difficulty.tier",
+            &[
+                ("value", &value),
+                ("tier", &tier_name(tier)),
+                ("stars", &"*".repeat(tier.stars() as usize)),
+            ],
+        ),
+        None => i18n::message("difficulty", &[("value", &value)]),
+    }
+}
+
+// The generator for solutions of puzzles generated from the command
+// line, drawing the built-in `--shape`, mirrored across the `--mirror`
+// axis, or clustered following noise of the `--clustered` scale, when
+// one of them is given, or else painted with the given number of
+// `--colors`, and smoothed for the given number of `--smooth`
+// iterations. Its lines reveal kinds of clue picked from the `--clues`
+// mix, which defaults to full clues.
+#[cfg(feature = "generate")]
+pub fn generator<T: Rng + 'static>(
+    args: &Args,
+    radius: Distance,
+) -> Result<GeneratorFn<T>, String> {
+    if radius < 0 {
+        return Err(i18n::message(
+            "error.invalid-radius",
+            &[("radius", &radius)],
+        ));
+    }
+
+    // Shapes, mirrored and clustered boards are painted with colors of
+    // their own, so none of them can be combined with `--colors` either.
+    args.exclusive(&["shape", "mirror", "clustered", "colors"])?;
+
+    let generator: GeneratorFn<T> = if let Some(shape) = args.option("shape") {
+        let shape: Shape = shape.parse()?;
+        let generator = ShapeGenerator::new(shape, radius).map_err(|error| error.to_string())?;
+        Box::new(move |rng: &mut T| generator.generate(rng))
+    } else {
+        match args.option("mirror") {
+            Some(axis) => {
+                let axis = match axis.to_ascii_lowercase().as_str() {
+                    "x" => Axis::X,
+                    "y" => Axis::Y,
+                    "z" => Axis::Z,
+                    _ => return Err(i18n::message("error.invalid-axis", &[("axis", &axis)])),
+                };
+                Board::mirrored_generator(radius, axis).map_err(|error| error.to_string())?
+            }
+            None if args.option("clustered").is_some() => {
+                let scale = args.parsed("clustered", 3.0)?;
+                Board::clustered_generator(radius, scale).map_err(|_| {
+                    i18n::message(
+                        "error.invalid-value",
+                        &[("value", &scale), ("name", &"clustered")],
+                    )
+                })?
+            }
+            None => Board::palette_generator(radius, palette(args)?)
+                .map_err(|error| error.to_string())?,
+        }
+    };
+
+    let generator: GeneratorFn<T> = match args.option("smooth") {
+        Some(_) => {
+            let smoothed = Smoothed::new(generator, args.parsed("smooth", 1)?);
+            Box::new(move |rng: &mut T| smoothed.generate(rng))
+        }
+        None => generator,
+    };
+
+    Ok(match args.option("clues") {
+        Some(mix) => {
+            let mix: ClueMix = mix.parse()?;
+            Box::new(move |rng: &mut T| {
+                let puzzle = generator.generate(rng);
+                // Generated boards hold their whole solution, which is
+                // all the kinds of clue need.
+                puzzle.with_clue_mix(rng, &mix).unwrap()
+            })
+        }
+        None => generator,
+    })
+}
+
+// The palette of the given number of `--colors`, all of them unless
+// given.
+#[cfg(feature = "generate")]
+fn palette(args: &Args) -> Result<Palette, String> {
+    let colors = args.parsed("colors", Palette::default().colors())?;
+    Palette::new(colors).map_err(|_| {
+        i18n::message(
+            "error.invalid-value",
+            &[("value", &colors), ("name", &"colors")],
+        )
+    })
+}
+
+// How much the validator of puzzles generated from the command line
+// asks of them, from the least to the most.
+#[cfg(feature = "generate")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Profile {
+    // Any puzzle will do.
+    Unlimited,
+    // Puzzles have at most 12 givens.
+    Givens,
+    // Puzzles have at most 12 givens, and are solved with both hint and
+    // clue passes, without any clue solving its line on its own.
+    Passes,
+}
+
+#[cfg(feature = "generate")]
+impl Profile {
+    fn strategies(self) -> Vec<Box<dyn ValidatorStrategy>> {
+        match self {
+            Profile::Unlimited => vec![],
+            Profile::Givens => vec![Box::new(MaximumSolvedPositions(12))],
+            Profile::Passes => vec![
+                Box::new(RequireClueSolving(true)),
+                Box::new(RequireHintSolving(true)),
+                Box::new(MaximumSolvedClues(0)),
+                Box::new(MaximumSolvedPositions(12)),
+            ],
+        }
+    }
+}
+
+// The options that puzzles are validated by.
+#[cfg(feature = "generate")]
+struct ProfileOptions {
+    radius: Distance,
+    shape: bool,
+    palette: Palette,
+    mix: ClueMix,
+}
+
+// Whether options call for a profile.
+#[cfg(feature = "generate")]
+type Applies = fn(&ProfileOptions) -> bool;
+
+// The profiles that options limit puzzles to, of which puzzles get the
+// lowest that applies, or else `Passes`.
+#[cfg(feature = "generate")]
+const PROFILES: &[(Applies, Profile)] = &[
+    // The clues of a single cell give it away.
+    (|options| options.radius == 0, Profile::Unlimited),
+    // Totals need many more givens.
+    (
+        |options| options.mix.kinds().any(|kind| kind == ClueKind::Totals),
+        Profile::Unlimited,
+    ),
+    // Lines of a few cells are mostly solved by their clues alone.
+    (|options| options.radius == 1, Profile::Givens),
+    // Pictures are made of lines of a single color, solved by their clues
+    // alone.
+    (|options| options.shape, Profile::Givens),
+    // Fewer colors solve many lines by their clues alone, while more of
+    // them are rarely solved with both passes.
+    (
+        |options| options.palette != Palette::default(),
+        Profile::Givens,
+    ),
+    // Other kinds of clues than full clues are rarely solved with both
+    // passes.
+    (
+        |options| options.mix.kinds().any(|kind| kind != ClueKind::Full),
+        Profile::Givens,
+    ),
+];
+
+// The validator used for puzzles generated from the command line, with
+// the profile that the `--radius`, `--shape`, `--colors` and `--clues`
+// call for. Any of them may be limited to the difficulties of the
+// `--tier` of that name.
+#[cfg(feature = "generate")]
+pub fn validator(args: &Args) -> Result<Validator, String> {
+    let options = ProfileOptions {
+        radius: args
+            .parsed("radius", DEFAULT_RADIUS)
+            .unwrap_or(DEFAULT_RADIUS),
+        shape: args.option("shape").is_some(),
+        palette: palette(args)?,
+        mix: match args.option("clues") {
+            Some(mix) => mix.parse()?,
+            None => ClueMix::default(),
+        },
+    };
+
+    let profile = PROFILES
+        .iter()
+        .filter(|(applies, _profile)| applies(&options))
+        .map(|(_applies, profile)| *profile)
+        .min()
+        .unwrap_or(Profile::Passes);
+    let validator = Validator::new(profile.strategies());
+
+    Ok(match args.option("tier") {
+        Some(name) => {
+            let scheme = TierScheme::default();
+            let tier = scheme
+                .tier_by_name(name)
+                .ok_or_else(|| i18n::message("error.unknown-tier", &[("tier", &name)]))?;
+            let (minimum, maximum) = scheme.bounds(tier);
+            validator.with_strategy(Box::new(DifficultyRange(minimum, maximum)))
+        }
+        None => validator,
+    })
+}
+
+// The refiner used for puzzles generated from the command line, using
+// beam search when a `--beam` width is given, and polishing puzzles
+// for a number of `--polish` iterations when given. Both rank puzzles
+// by the `--objective` profile or weights. Otherwise, the cells to
+// reveal are picked by the `--strategy` of that name, and clues are
+// strengthened before revealing any with `--strengthen`. Redundant
+// givens are removed with `--minimize`.
+#[cfg(feature = "generate")]
+pub fn refiner(args: &Args) -> Result<Refiner, String> {
+    let objective: Objective = args.parsed("objective", Objective::default())?;
+    let mut refiner = Refiner::new(validator(args)?).with_objective(objective);
+
+    if args.option("beam").is_some() {
+        refiner = refiner.with_beam_search(args.parsed("beam", 1)?);
+    }
+
+    if let Some(name) = args.option("strategy") {
+        let strategy: Arc<dyn RefineStrategy> = match name {
+            "lowest-clue" => Arc::new(LowestClue),
+            "random-cell" => Arc::new(RandomCell::from_entropy()),
+            "most-constrained" => Arc::new(MostConstrained),
+            "lookahead" => Arc::new(Lookahead(8)),
+            _ => {
+                return Err(i18n::message(
+                    "error.invalid-value",
+                    &[("value", &name), ("name", &"strategy")],
+                ))
+            }
+        };
+        refiner = refiner.with_strategy(strategy);
+    }
+
+    if args.switch("strengthen") {
+        refiner = refiner.with_clue_strengthening();
+    }
+
+    if args.switch("minimize") {
+        refiner = refiner.with_minimization();
+    }
+
+    if args.option("polish").is_some() {
+        let annealer =
+            Annealer::new(validator(args)?, objective).with_iterations(args.parsed("polish", 0)?);
+        refiner = refiner.with_annealer(annealer);
+    }
+
+    Ok(refiner)
+}
+
+// The budget for refining each puzzle generated from the command line,
+// which gives up after `--timeout` seconds, a minute by default, or
+// after `--attempts` solutions when given.
+#[cfg(feature = "generate")]
+pub fn budget(args: &Args) -> Result<Budget, String> {
+    let timeout = args.parsed("timeout", 60)?;
+    let budget = Budget::unlimited().with_duration(Duration::from_secs(timeout));
+
+    Ok(match args.option("attempts") {
+        Some(_) => budget.with_attempts(args.parsed("attempts", 0)?),
+        None => budget,
+    })
+}
+
+// The message for a refiner that gave up on finding a valid puzzle.
+#[cfg(feature = "generate")]
+pub fn refine_error(error: RefineError) -> String {
+    match error {
+        RefineError::Exhausted(attempts) => {
+            i18n::message("error.exhausted", &[("attempts", &attempts)])
+        }
+        RefineError::Cancelled(_) => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Result<Args, String> {
+        Args::parse(arguments.iter().map(|argument| argument.to_string()))
+    }
+
+    #[test]
+    fn parse_arguments() {
+        let args = parse(&["stats", "--radius", "3", "--json", "--count=10"]).unwrap();
+
+        assert_eq!(Some("stats"), args.command());
+        assert_eq!(Some("3"), args.option("radius"));
+        assert_eq!(10, args.parsed("count", 100).unwrap());
+        assert_eq!(5, args.parsed("missing", 5).unwrap());
+        assert!(args.switch("json"));
+        assert!(!args.switch("radius"));
+
+        assert!(parse(&[]).unwrap().command().is_none());
+        assert!(parse(&["stats", "--radius", "3", "extra"]).is_err());
+        assert!(parse(&["--radius", "x"])
+            .unwrap()
+            .parsed("radius", 5)
+            .is_err());
+    }
+
+    #[test]
+    fn expected_arguments() {
+        let args = parse(&["stats", "--radius", "3", "--json", "--lang=fr"]).unwrap();
+        assert!(args.expect(&["radius"], &["json"]).is_ok());
+
+        // Unknown options and switches, such as misspelled ones.
+        assert!(args.expect(&["raduis"], &["json"]).is_err());
+        assert!(args.expect(&["radius"], &[]).is_err());
+        let args = parse(&["--help"]).unwrap();
+        assert!(args.expect(&["radius"], &["json"]).is_err());
+
+        // Options missing their value.
+        let args = parse(&["generate", "--radius"]).unwrap();
+        assert_eq!(
+            Err("unexpected argument `--radius`".to_string()),
+            args.expect(&["radius"], &[])
+        );
+        let args = parse(&["generate", "--radius", "--minimize"]).unwrap();
+        assert!(args.expect(&["radius"], &["minimize"]).is_err());
+
+        // Arguments after the command, for commands that take none or
+        // fewer of them.
+        let args = parse(&["stats", "extra"]).unwrap();
+        assert_eq!(
+            Err("unexpected argument `extra`".to_string()),
+            args.expect(&[], &[])
+        );
+        let args = parse(&["library", "import", "pack.bg", "--dir", "x"]).unwrap();
+        assert!(args.expect_arguments(2, &["dir"], &[]).is_ok());
+        assert!(args.expect_arguments(1, &["dir"], &[]).is_err());
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn exclusive_options() {
+        let args = parse(&["--mirror", "x", "--clustered", "2"]).unwrap();
+        assert!(args.exclusive(&["shape", "mirror", "clustered"]).is_err());
+        assert!(args.exclusive(&["shape", "mirror"]).is_ok());
+        assert!(generator::<rand::rngs::StdRng>(&args, 2).is_err());
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn invalid_colors() {
+        for colors in ["x", "1"] {
+            let args = parse(&["generate", "--colors", colors]).unwrap();
+            assert!(validator(&args).is_err());
+            assert!(generator::<rand::rngs::StdRng>(&args, 2).is_err());
+        }
+
+        let args = parse(&["generate", "--colors", "2", "--shape", "heart"]).unwrap();
+        assert!(generator::<rand::rngs::StdRng>(&args, 2).is_err());
+        let args = parse(&["generate", "--colors", "2", "--mirror", "x"]).unwrap();
+        assert!(generator::<rand::rngs::StdRng>(&args, 2).is_err());
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn invalid_clustered() {
+        for scale in ["0", "-2", "nan", "inf"] {
+            let args = parse(&["generate", "--clustered", scale]).unwrap();
+            assert!(generator::<rand::rngs::StdRng>(&args, 2).is_err());
+        }
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn invalid_clues() {
+        let args = parse(&["generate", "--clues", "full=x"]).unwrap();
+        assert!(validator(&args).is_err());
+        assert!(refiner(&args).is_err());
+    }
+
+    // Every radius the wizard offers has a validator that boards of that
+    // radius can satisfy.
+    #[cfg(feature = "generate")]
+    #[test]
+    fn small_radii() {
+        use bestagons::puzzle::refiner::Budget;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let budget = Budget::unlimited().with_attempts(100);
+        for radius in ["0", "1"] {
+            let args = parse(&["generate", "--radius", radius]).unwrap();
+            let generator: GeneratorFn<StdRng> = generator(&args, radius.parse().unwrap()).unwrap();
+
+            let refined = refiner(&args)
+                .unwrap()
+                .refined_within(&mut rng, &generator, &budget);
+            assert!(refined.is_ok());
+        }
+    }
+
+    // Refined puzzles keep their givens, so the validator of generated
+    // puzzles must allow some of them for generation to ever finish.
+    #[cfg(feature = "generate")]
+    #[test]
+    fn validator_allows_givens() {
+        use bestagons::puzzle::refiner::Budget;
+        use bestagons::puzzle::solver::Solver;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let args = parse(&["generate", "--radius", "3"]).unwrap();
+        let generator: GeneratorFn<StdRng> = generator(&args, 3).unwrap();
+        let budget = Budget::unlimited().with_attempts(1000);
+
+        let puzzle = refiner(&args)
+            .unwrap()
+            .refined_within(&mut rng, &generator, &budget)
+            .unwrap();
+        assert!(puzzle.board().cells().len() <= 12);
+        assert!(Solver::new(puzzle).solve());
+    }
+}
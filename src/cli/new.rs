@@ -0,0 +1,221 @@
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+
+use bestagons::grid::Distance;
+use bestagons::i18n;
+use bestagons::puzzle::difficulty::TierScheme;
+use bestagons::puzzle::ClueMix;
+
+use super::{Args, DEFAULT_RADIUS};
+
+// Asks for the settings of a new puzzle on the terminal, offering the
+// defaults of the `generate` command, and returns the arguments of the
+// `generate` command that makes and saves such a puzzle. The command
+// line is printed, so that the choice can be scripted later.
+pub fn run(args: &Args) -> Result<Args, String> {
+    args.expect(&[], &[])?;
+    let arguments = ask(&mut io::stdin().lock(), &mut io::stdout())?;
+
+    println!(
+        "{}\n",
+        i18n::message("new.command", &[("command", &command(&arguments))])
+    );
+
+    Args::parse(arguments.into_iter())
+}
+
+// Asks each question in turn, asking again after invalid answers, and
+// taking the default for empty answers or once the input ends.
+fn ask(input: &mut impl BufRead, output: &mut impl Write) -> Result<Vec<String>, String> {
+    let mut arguments = vec!["generate".to_string()];
+
+    let radius = question(
+        input,
+        output,
+        "new.radius",
+        &[],
+        DEFAULT_RADIUS.to_string(),
+        |answer| match answer.parse::<Distance>() {
+            Ok(radius) if radius >= 0 => Ok(()),
+            _ => Err(i18n::message(
+                "error.invalid-radius",
+                &[("radius", &answer)],
+            )),
+        },
+    )?;
+    arguments.extend(["--radius".to_string(), radius]);
+
+    let scheme = TierScheme::default();
+    let tiers: Vec<String> = scheme
+        .tiers()
+        .iter()
+        .map(|tier| tier.name().to_lowercase())
+        .collect();
+    let tier = question(
+        input,
+        output,
+        "new.tier",
+        &[("tiers", &tiers.join(", "))],
+        "any".to_string(),
+        |answer| match answer == "any" || scheme.tier_by_name(answer).is_some() {
+            true => Ok(()),
+            false => Err(i18n::message("error.unknown-tier", &[("tier", &answer)])),
+        },
+    )?;
+    if tier != "any" {
+        arguments.extend(["--tier".to_string(), tier.to_lowercase()]);
+    }
+
+    let symmetry = question(
+        input,
+        output,
+        "new.symmetry",
+        &[],
+        "none".to_string(),
+        |answer| match ["none", "x", "y", "z"].contains(&answer.to_ascii_lowercase().as_str()) {
+            true => Ok(()),
+            false => Err(i18n::message("error.invalid-axis", &[("axis", &answer)])),
+        },
+    )?;
+    if !symmetry.eq_ignore_ascii_case("none") {
+        arguments.extend(["--mirror".to_string(), symmetry.to_lowercase()]);
+    }
+
+    let clues = question(
+        input,
+        output,
+        "new.clues",
+        &[],
+        "full".to_string(),
+        |answer| answer.parse::<ClueMix>().map(|_mix| ()),
+    )?;
+    if clues != "full" {
+        arguments.extend(["--clues".to_string(), clues]);
+    }
+
+    let format = question(
+        input,
+        output,
+        "new.format",
+        &[],
+        "binary".to_string(),
+        |answer| match ["text", "binary"].contains(&answer) {
+            true => Ok(()),
+            false => Err(i18n::message(
+                "error.invalid-format",
+                &[("format", &answer), ("expected", &"`binary` or `text`")],
+            )),
+        },
+    )?;
+    let path = match format.as_str() {
+        "binary" => "puzzle.bg",
+        _ => "puzzle.txt",
+    };
+    let path = question(
+        input,
+        output,
+        "new.output",
+        &[],
+        path.to_string(),
+        |_answer| Ok(()),
+    )?;
+    arguments.extend(["--format".to_string(), format, "--output".to_string(), path]);
+
+    Ok(arguments)
+}
+
+fn question(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    key: &str,
+    arguments: &[(&str, &dyn Display)],
+    default: String,
+    validate: impl Fn(&str) -> Result<(), String>,
+) -> Result<String, String> {
+    let arguments: Vec<(&str, &dyn Display)> = arguments
+        .iter()
+        .copied()
+        .chain([("default", &default as &dyn Display)])
+        .collect();
+
+    loop {
+        write!(output, "{} ", i18n::message(key, &arguments))
+            .and_then(|_| output.flush())
+            .map_err(|error| error.to_string())?;
+
+        let mut answer = String::new();
+        if input
+            .read_line(&mut answer)
+            .map_err(|error| error.to_string())?
+            == 0
+        {
+            writeln!(output).map_err(|error| error.to_string())?;
+            return Ok(default);
+        }
+
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Ok(default);
+        }
+
+        match validate(answer) {
+            Ok(()) => return Ok(answer.to_string()),
+            Err(error) => writeln!(output, "{}", i18n::message("error", &[("error", &error)]))
+                .map_err(|error| error.to_string())?,
+        }
+    }
+}
+
+// The command line of the arguments, quoting those with spaces.
+fn command(arguments: &[String]) -> String {
+    std::iter::once("bestagons".to_string())
+        .chain(arguments.iter().map(|argument| {
+            if argument.contains(char::is_whitespace) {
+                format!("'{argument}'")
+            } else {
+                argument.clone()
+            }
+        }))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers(input: &str) -> (Vec<String>, String) {
+        let mut output = vec![];
+        let arguments = ask(&mut input.as_bytes(), &mut output).unwrap();
+        (arguments, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn wizard() {
+        let (arguments, _output) = answers("");
+        assert_eq!(
+            "bestagons generate --radius 5 --format binary --output puzzle.bg",
+            command(&arguments)
+        );
+
+        let (arguments, output) = answers("-1\n3\nhard\nX\nfull=2, ordered\n\nmy puzzles.bg\n");
+        assert_eq!(
+            "bestagons generate --radius 3 --tier hard --mirror x --clues 'full=2, ordered' \
+             --format binary --output 'my puzzles.bg'",
+            command(&arguments)
+        );
+        assert!(output.contains("invalid radius -1"));
+
+        let args = Args::parse(arguments.into_iter()).unwrap();
+        assert_eq!(Some("generate"), args.command());
+        assert_eq!(Some("my puzzles.bg"), args.option("output"));
+
+        let (arguments, output) = answers("\nimpossible\n\nsideways\n\n\ntext\n");
+        assert_eq!(
+            "bestagons generate --radius 5 --format text --output puzzle.txt",
+            command(&arguments)
+        );
+        assert!(output.contains("unknown difficulty tier `impossible`"));
+        assert!(output.contains("invalid axis `sideways`"));
+    }
+}
@@ -0,0 +1,22 @@
+use std::net::TcpListener;
+
+use bestagons::i18n;
+use bestagons::server;
+
+use super::Args;
+
+// Streams the steps of the solver over WebSockets to the clients
+// connecting to the `--address`, local port 7878 unless given.
+pub fn run(args: &Args) -> Result<(), String> {
+    args.expect(&["address"], &[])?;
+    let address = args.option("address").unwrap_or("127.0.0.1:7878");
+    let failed = |error| i18n::message("serve.failed", &[("address", &address), ("error", &error)]);
+
+    let listener = TcpListener::bind(address).map_err(failed)?;
+    println!(
+        "{}",
+        i18n::message("serve.listening", &[("address", &address)])
+    );
+    server::serve(&listener);
+    Ok(())
+}
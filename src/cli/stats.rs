@@ -0,0 +1,321 @@
+use std::fmt::Write;
+use std::fs;
+
+use rand::thread_rng;
+
+use bestagons::grid::Direction;
+use bestagons::i18n;
+use bestagons::puzzle::binary;
+use bestagons::puzzle::difficulty::{Difficulty, Tier, TierScheme};
+use bestagons::puzzle::puzzle::{GeneratorFn, Puzzle};
+use bestagons::puzzle::solver::Solver;
+
+use super::{
+    budget, generator, refine_error, refiner, tier_name, Args, DEFAULT_RADIUS, GENERATOR_OPTIONS,
+    GENERATOR_SWITCHES,
+};
+
+// The distribution of a metric over the generated puzzles.
+struct Summary {
+    name: &'static str,
+    min: f64,
+    median: f64,
+    mean: f64,
+    max: f64,
+}
+
+impl Summary {
+    fn new(name: &'static str, mut values: Vec<f64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        values.sort_by(f64::total_cmp);
+        let middle = values.len() / 2;
+        let median = if values.len().is_multiple_of(2) {
+            (values[middle - 1] + values[middle]) / 2.0
+        } else {
+            values[middle]
+        };
+
+        Some(Summary {
+            name,
+            min: values[0],
+            median,
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            max: values[values.len() - 1],
+        })
+    }
+}
+
+// Generates many puzzles with the command line generator and refiner,
+// or reads them from the binary pack of the `--input` file, and prints
+// the distributions of their difficulty, given cells, refinement
+// attempts, the share of the most common color across their solutions
+// and the longest run of a single color along the lines of their
+// solutions, along with the number of puzzles in each difficulty tier.
+// Puzzles read from a file have no attempts to tell. A `--format` of
+// `json` prints them as a single JSON object instead of a table.
+pub fn run(args: &Args) -> Result<(), String> {
+    args.expect(
+        &[GENERATOR_OPTIONS, &["input", "count", "format"]].concat(),
+        GENERATOR_SWITCHES,
+    )?;
+    let format = args.option("format").unwrap_or("text");
+    if !["text", "json"].contains(&format) {
+        return Err(i18n::message(
+            "error.invalid-format",
+            &[("format", &format), ("expected", &"`text` or `json`")],
+        ));
+    }
+    let mut metrics = Metrics::default();
+
+    let heading = if let Some(path) = args.option("input") {
+        let bytes = fs::read(path)
+            .map_err(|error| i18n::message("error.read", &[("path", &path), ("error", &error)]))?;
+        let puzzles = binary::decode_pack(&bytes).map_err(|error| {
+            i18n::message("error.decode", &[("path", &path), ("error", &error)])
+        })?;
+
+        for puzzle in &puzzles {
+            metrics.add(puzzle);
+        }
+        Heading::Input(path, puzzles.len())
+    } else {
+        let radius = args.parsed("radius", DEFAULT_RADIUS)?;
+        let count: usize = args.parsed("count", 100)?;
+
+        let mut rng = thread_rng();
+        let generator: GeneratorFn<_> = generator(args, radius)?;
+        let refiner = refiner(args)?;
+        let budget = budget(args)?;
+
+        for _ in 0..count {
+            let (refined, stats) = refiner.refined_with_stats(&mut rng, &generator, &budget);
+            metrics.add(&refined.map_err(refine_error)?);
+            metrics.attempts.push(stats.attempts() as f64);
+        }
+        Heading::Generated(radius, count)
+    };
+
+    let unrated = metrics.unrated;
+    let tiers = tiers(&metrics.difficulties);
+    let summaries: Vec<Summary> = [
+        Summary::new("difficulty", metrics.difficulties),
+        Summary::new("givens", metrics.givens),
+        Summary::new("attempts", metrics.attempts),
+        Summary::new("color balance", metrics.balances),
+        Summary::new("longest run", metrics.runs),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if format == "json" {
+        println!("{}", json(&heading, unrated, &tiers, &summaries));
+    } else {
+        print!("{}", table(&heading, unrated, &tiers, &summaries));
+    }
+
+    Ok(())
+}
+
+// Where the puzzles come from: the radius and number of generated ones,
+// or the file they were read from and their number.
+enum Heading<'a> {
+    Generated(i32, usize),
+    Input(&'a str, usize),
+}
+
+// The values of each metric over the puzzles.
+#[derive(Default)]
+struct Metrics {
+    difficulties: Vec<f64>,
+    givens: Vec<f64>,
+    attempts: Vec<f64>,
+    balances: Vec<f64>,
+    runs: Vec<f64>,
+    unrated: usize,
+}
+
+impl Metrics {
+    // Adds the metrics of the puzzle, and of its solution, when the
+    // solver finds one.
+    fn add(&mut self, puzzle: &Puzzle) {
+        match Difficulty::rate(puzzle) {
+            Some(difficulty) => self.difficulties.push(difficulty.value()),
+            None => self.unrated += 1,
+        }
+        self.givens.push(puzzle.board().cells().len() as f64);
+
+        let mut solver = Solver::new(puzzle.clone());
+        if !solver.solve() {
+            return;
+        }
+
+        let stats = solver.solution().stats();
+        self.balances.extend(stats.dominance());
+        self.runs.push(
+            Direction::normalized()
+                .into_iter()
+                .map(|direction| stats.longest_run(direction))
+                .max()
+                .unwrap_or(0) as f64,
+        );
+    }
+}
+
+// The number of difficulties in each tier of the default scheme.
+fn tiers(difficulties: &[f64]) -> Vec<(Tier, usize)> {
+    let scheme = TierScheme::default();
+    scheme
+        .tiers()
+        .iter()
+        .map(|tier| {
+            let count = difficulties
+                .iter()
+                .filter(|value| scheme.tier(Difficulty::new(**value)) == Some(tier))
+                .count();
+            (tier.clone(), count)
+        })
+        .collect()
+}
+
+fn table(
+    heading: &Heading,
+    unrated: usize,
+    tiers: &[(Tier, usize)],
+    summaries: &[Summary],
+) -> String {
+    let mut output = String::new();
+
+    let heading = match heading {
+        Heading::Generated(radius, count) => {
+            i18n::message("stats.count", &[("count", count), ("radius", radius)])
+        }
+        Heading::Input(path, count) => {
+            i18n::message("stats.input", &[("count", count), ("path", path)])
+        }
+    };
+    writeln!(output, "{heading}").unwrap();
+    if unrated > 0 {
+        writeln!(
+            output,
+            "{}",
+            i18n::message("stats.unrated", &[("unrated", &unrated)])
+        )
+        .unwrap();
+    }
+    let tiers: Vec<String> = tiers
+        .iter()
+        .map(|(tier, count)| format!("{} {count}", tier_name(tier)))
+        .collect();
+    writeln!(
+        output,
+        "{}",
+        i18n::message("stats.tiers", &[("tiers", &tiers.join(", "))])
+    )
+    .unwrap();
+    writeln!(output).unwrap();
+    writeln!(
+        output,
+        "{:<14} {:>8} {:>8} {:>8} {:>8}",
+        i18n::message("stats.metric", &[]),
+        i18n::message("stats.min", &[]),
+        i18n::message("stats.median", &[]),
+        i18n::message("stats.mean", &[]),
+        i18n::message("stats.max", &[])
+    )
+    .unwrap();
+
+    for summary in summaries {
+        let name = format!("stats.metric.{}", summary.name.replace(' ', "-"));
+        writeln!(
+            output,
+            "{:<14} {:>8.2} {:>8.2} {:>8.2} {:>8.2}",
+            i18n::label(&name, summary.name),
+            summary.min,
+            summary.median,
+            summary.mean,
+            summary.max
+        )
+        .unwrap();
+    }
+
+    output
+}
+
+fn json(
+    heading: &Heading,
+    unrated: usize,
+    tiers: &[(Tier, usize)],
+    summaries: &[Summary],
+) -> String {
+    let mut fields = match heading {
+        Heading::Generated(radius, count) => {
+            vec![format!("\"radius\":{radius}"), format!("\"count\":{count}")]
+        }
+        Heading::Input(_path, count) => vec![format!("\"count\":{count}")],
+    };
+    fields.push(format!("\"unrated\":{unrated}"));
+    let tiers: Vec<String> = tiers
+        .iter()
+        .map(|(tier, count)| format!("\"{}\":{count}", tier.name().to_lowercase()))
+        .collect();
+    fields.push(format!("\"tiers\":{{{}}}", tiers.join(",")));
+
+    let metrics = summaries.iter().map(|summary| {
+        format!(
+            "\"{}\":{{\"min\":{},\"median\":{},\"mean\":{},\"max\":{}}}",
+            summary.name.replace(' ', "_"),
+            summary.min,
+            summary.median,
+            summary.mean,
+            summary.max
+        )
+    });
+    fields.extend(metrics);
+
+    format!("{{{}}}", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            "{\"radius\":2,\"count\":0,\"unrated\":0,\"tiers\":{}}",
+            super::json(&Heading::Generated(2, 0), 0, &[], &[])
+        );
+
+        let tiers = tiers(&[1.0, 9.0, 12.0]);
+        assert_eq!(
+            vec![1, 2, 0, 0, 0],
+            tiers
+                .iter()
+                .map(|(_tier, count)| *count)
+                .collect::<Vec<_>>()
+        );
+
+        let summaries = [Summary::new("longest run", vec![1.0, 3.0]).unwrap()];
+        assert_eq!(
+            "{\"count\":2,\"unrated\":1,\"tiers\":{\"easy\":1,\"medium\":2,\"hard\":0,\"expert\":0,\"fiendish\":0},\"longest_run\":{\"min\":1,\"median\":2,\"mean\":2,\"max\":3}}",
+            super::json(&Heading::Input("pack.bin", 2), 1, &tiers, &summaries)
+        );
+    }
+    #[test]
+    fn format() {
+        let args = Args::parse(
+            ["stats", "--format", "yaml"]
+                .iter()
+                .map(|argument| argument.to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            Err("invalid format `yaml`, expected `text` or `json`".to_string()),
+            run(&args)
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use super::hexagon::{Hexagon, HexagonError, HexagonIterator};
+use super::{Distance, Position};
+
+// Partitions the plane into hexagonal chunks of a given radius, which
+// tile the plane without gaps or overlaps. The chunks are centered on
+// the points of a lattice spanned by two vectors, one sixty degrees
+// clockwise from the other, such that the chunk centered on the origin
+// of the chunking is one of them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Chunking {
+    origin: Position,
+    radius: Distance,
+}
+
+impl Chunking {
+    // Chunks of radius zero are single positions, one for each of them.
+    pub fn new(radius: Distance) -> Result<Self, HexagonError> {
+        if radius >= 0 {
+            Ok(Chunking {
+                origin: Position::zero(),
+                radius,
+            })
+        } else {
+            Err(HexagonError::InsufficientRadius(radius))
+        }
+    }
+
+    // Centers a chunk on the given position rather than on the origin of
+    // the grid, such as the center of a hexagon to tile with chunks.
+    pub fn with_origin(mut self, origin: Position) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn origin(&self) -> Position {
+        self.origin
+    }
+
+    pub fn radius(&self) -> Distance {
+        self.radius
+    }
+
+    // The two vectors spanning the lattice of chunk centers. Each of
+    // them is at a distance of `2 * radius + 1` from the origin, which
+    // is the distance between the centers of two adjacent chunks.
+    fn basis(&self) -> (Position, Position) {
+        let radius = self.radius;
+
+        (
+            Position(2 * radius + 1, -radius),
+            Position(radius, radius + 1),
+        )
+    }
+
+    fn lattice_position(&self, a: i32, b: i32) -> Position {
+        let (first, second) = self.basis();
+        self.origin + first * a + second * b
+    }
+
+    // The fractional lattice coordinates of the given position.
+    fn lattice_coordinates(&self, position: Position) -> (f64, f64) {
+        let (first, second) = self.basis();
+        let determinant = (first.x() * second.y() - first.y() * second.x()) as f64;
+        let position = position - self.origin;
+        let (x, y) = (position.x() as f64, position.y() as f64);
+
+        (
+            (x * second.y() as f64 - y * second.x() as f64) / determinant,
+            (y * first.x() as f64 - x * first.y() as f64) / determinant,
+        )
+    }
+
+    // The center of the chunk that contains the given position.
+    pub fn center(&self, position: Position) -> Position {
+        let (a, b) = self.lattice_coordinates(position);
+        let (a, b) = (a.floor() as i32, b.floor() as i32);
+
+        // The nearest lattice point by Euclidean distance is not always
+        // the center of the chunk that contains the position, but it is
+        // always one of the lattice points around it.
+        (a - 1..=a + 2)
+            .flat_map(|a| (b - 1..=b + 2).map(move |b| (a, b)))
+            .map(|(a, b)| self.lattice_position(a, b))
+            .find(|center| center.distance_to(position) <= self.radius)
+            .unwrap()
+    }
+
+    // The chunk that contains the given position.
+    pub fn chunk(&self, position: Position) -> Hexagon {
+        Hexagon::new(self.center(position), self.radius).unwrap()
+    }
+
+    // The chunks that contain at least one position of the given
+    // hexagon, each of them restricted to the positions within it. The
+    // whole chunks, such as the tiles of a puzzle made of smaller ones,
+    // are their hexagons.
+    pub fn chunks(&self, hexagon: Hexagon) -> impl Iterator<Item = Chunk> {
+        let chunking = *self;
+        let reach = hexagon.radius() + self.radius;
+        let (a, b) = self.lattice_coordinates(hexagon.origin());
+        let (a, b) = (a.round() as i32, b.round() as i32);
+        let span = reach / self.radius.max(1) + 2;
+
+        (a - span..=a + span)
+            .flat_map(move |a| (b - span..=b + span).map(move |b| (a, b)))
+            .map(move |(a, b)| chunking.lattice_position(a, b))
+            .filter(move |center| hexagon.origin().distance_to(*center) <= reach)
+            .map(move |center| Chunk {
+                hexagon: Hexagon::new(center, chunking.radius).unwrap(),
+                bounds: hexagon,
+            })
+            .filter(|chunk| chunk.into_iter().next().is_some())
+    }
+}
+
+// A chunk of a partitioned hexagon. Chunks at the border of the
+// partitioned hexagon may only contain some of the positions of their
+// full chunk hexagon.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    hexagon: Hexagon,
+    bounds: Hexagon,
+}
+
+impl Chunk {
+    pub fn hexagon(&self) -> Hexagon {
+        self.hexagon
+    }
+
+    pub fn center(&self) -> Position {
+        self.hexagon.origin()
+    }
+
+    pub fn bounds(&self) -> Hexagon {
+        self.bounds
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.hexagon.contains(position) && self.bounds.contains(position)
+    }
+
+    // Whether the chunk contains all of the positions of its hexagon.
+    pub fn is_complete(&self) -> bool {
+        self.bounds.origin().distance_to(self.center()) + self.hexagon.radius()
+            <= self.bounds.radius()
+    }
+}
+
+impl IntoIterator for Chunk {
+    type Item = Position;
+
+    type IntoIter = ChunkIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ChunkIterator::new(self)
+    }
+}
+
+pub struct ChunkIterator {
+    hexagon_iterator: HexagonIterator,
+    bounds: Hexagon,
+}
+
+impl ChunkIterator {
+    pub fn new(chunk: Chunk) -> Self {
+        Self {
+            hexagon_iterator: chunk.hexagon.into_iter(),
+            bounds: chunk.bounds,
+        }
+    }
+}
+
+impl Iterator for ChunkIterator {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bounds = self.bounds;
+        self.hexagon_iterator
+            .find(|position| bounds.contains(*position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::grid::Direction;
+
+    #[test]
+    fn center() {
+        for radius in 1..=4 {
+            let chunking = Chunking::new(radius).unwrap();
+            let hexagon = Hexagon::zero(radius * 5).unwrap();
+
+            for position in hexagon {
+                let center = chunking.center(position);
+
+                assert!((position - center).distance() <= radius);
+                assert_eq!(center, chunking.center(center));
+            }
+
+            for direction in Direction::all() {
+                let center = chunking.center(direction.position() * (2 * radius + 1));
+                assert_eq!(2 * radius + 1, center.distance());
+            }
+        }
+    }
+
+    #[test]
+    fn origin() {
+        let origin = Position::new((3, -4, 1)).unwrap();
+        let hexagon = Hexagon::new(origin, 6).unwrap();
+
+        for radius in 0..=3 {
+            let chunking = Chunking::new(radius).unwrap().with_origin(origin);
+            assert_eq!(
+                Hexagon::new(origin, radius).unwrap(),
+                chunking.chunk(origin)
+            );
+
+            for position in hexagon {
+                let chunk = chunking.chunk(position);
+                assert_eq!(radius, chunk.radius());
+                assert!(chunk.contains(position));
+                assert!(chunk
+                    .into_iter()
+                    .all(|other| chunking.chunk(other) == chunk));
+            }
+
+            // The chunks of a hexagon centered on the origin of the
+            // chunking partition it, including the one on its center.
+            let chunks: Vec<Chunk> = chunking.chunks(hexagon).collect();
+            assert!(chunks.iter().any(|chunk| chunk.center() == origin));
+            assert_eq!(
+                hexagon.into_iter().count(),
+                chunks
+                    .iter()
+                    .map(|chunk| chunk.into_iter().count())
+                    .sum::<usize>()
+            );
+        }
+
+        assert!(Chunking::new(-1).is_err());
+    }
+
+    #[test]
+    fn chunks() {
+        let chunking = Chunking::new(2).unwrap();
+        let hexagon = Hexagon::new(Position::new((3, -1, -2)).unwrap(), 7).unwrap();
+        let mut seen = HashSet::new();
+
+        for chunk in chunking.chunks(hexagon) {
+            for position in chunk {
+                assert!(hexagon.contains(position));
+                assert_eq!(chunk.center(), chunking.center(position));
+                assert!(seen.insert(position));
+            }
+
+            assert_eq!(
+                chunk.is_complete(),
+                chunk.into_iter().count() == chunk.hexagon().into_iter().count()
+            );
+        }
+
+        assert_eq!(hexagon.into_iter().count(), seen.len());
+    }
+}
@@ -0,0 +1,117 @@
+use super::vertex::Vertex;
+use super::{Direction, Position};
+
+// The border between two adjacent cells. Each edge is owned by one of
+// the two cells, the one from which the other is reached in one of the
+// normalized directions, so that equal edges compare equal regardless
+// of which cell they were made from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edge {
+    position: Position,
+    direction: Direction,
+}
+
+impl Edge {
+    // The edge between two positions, if they are adjacent.
+    pub fn new(a: Position, b: Position) -> Option<Self> {
+        Direction::between(a, b).map(|direction| Edge::towards(a, direction))
+    }
+
+    // The edge of the given position on the side of the given direction.
+    pub fn towards(position: Position, direction: Direction) -> Self {
+        if direction.normalize() == direction {
+            Edge {
+                position,
+                direction,
+            }
+        } else {
+            Edge {
+                position: position + direction.position(),
+                direction: direction.opposite(),
+            }
+        }
+    }
+
+    // The two positions on either side of the edge.
+    pub fn positions(&self) -> [Position; 2] {
+        [self.position, self.position + self.direction.position()]
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.positions().contains(&position)
+    }
+
+    // The two vertices at the ends of the edge, the corners of the cells
+    // on either side of it that are shared with the cells on either end.
+    pub fn vertices(&self) -> [Vertex; 2] {
+        [
+            Vertex::corner(self.position, self.direction),
+            Vertex::corner(self.position, self.direction.rotate_back()),
+        ]
+    }
+
+    // The four edges that share a vertex with this one.
+    pub fn neighbors(&self) -> Vec<Edge> {
+        self.vertices()
+            .into_iter()
+            .flat_map(|vertex| vertex.edges())
+            .filter(|edge| edge != self)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn new() {
+        let a = Position::new((2, -3, 1)).unwrap();
+
+        for direction in Direction::all() {
+            let b = a + direction.position();
+            let edge = Edge::new(a, b).unwrap();
+
+            assert_eq!(Some(edge), Edge::new(b, a));
+            assert_eq!(edge, Edge::towards(a, direction));
+            assert_eq!(edge, Edge::towards(b, direction.opposite()));
+            assert!(edge.contains(a) && edge.contains(b));
+            assert_eq!(
+                HashSet::from([a, b]),
+                edge.positions().into_iter().collect()
+            );
+        }
+
+        assert!(Edge::new(a, a).is_none());
+        assert!(Edge::new(a, a + Direction::XY.position() * 2).is_none());
+        assert_eq!(6, a.edges().into_iter().collect::<HashSet<_>>().len());
+    }
+
+    #[test]
+    fn vertices() {
+        let a = Position::new((2, -3, 1)).unwrap();
+        let edge = Edge::towards(a, Direction::YZ);
+
+        // Both ends of the edge are corners of both cells it separates.
+        for vertex in edge.vertices() {
+            for position in edge.positions() {
+                assert!(vertex.contains(position));
+            }
+            assert!(vertex.edges().contains(&edge));
+        }
+        assert_ne!(edge.vertices()[0], edge.vertices()[1]);
+
+        let neighbors: HashSet<Edge> = edge.neighbors().into_iter().collect();
+        assert_eq!(4, neighbors.len());
+        assert!(neighbors
+            .iter()
+            .all(|neighbor| neighbor.positions().iter().any(|p| edge.contains(*p))));
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::{HashSet, VecDeque};
+
+use super::region::Region;
+use super::Position;
+
+// The positions of the region that can be reached from the start by
+// steps between adjacent positions that all match the predicate. The
+// result is empty when the start itself is outside of the region or
+// does not match.
+pub fn flood_fill(
+    start: Position,
+    region: &impl Region,
+    predicate: impl Fn(Position) -> bool,
+) -> HashSet<Position> {
+    let mut filled = HashSet::new();
+    if !region.contains(start) || !predicate(start) {
+        return filled;
+    }
+
+    let mut queue = VecDeque::from([start]);
+    filled.insert(start);
+
+    while let Some(position) = queue.pop_front() {
+        for neighbor in position.neighbors() {
+            if region.contains(neighbor) && !filled.contains(&neighbor) && predicate(neighbor) {
+                filled.insert(neighbor);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    filled
+}
+
+// Splits the given positions into groups of positions connected by
+// steps between adjacent positions of the same group, largest first.
+pub fn components(positions: impl IntoIterator<Item = Position>) -> Vec<HashSet<Position>> {
+    let mut remaining: HashSet<Position> = positions.into_iter().collect();
+    let mut components = vec![];
+
+    while let Some(start) = remaining.iter().next().copied() {
+        let mut component = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        remaining.remove(&start);
+
+        while let Some(position) = queue.pop_front() {
+            for neighbor in position.neighbors() {
+                if remaining.remove(&neighbor) {
+                    component.insert(neighbor);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        components.push(component);
+    }
+
+    components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::hexagon::Hexagon;
+    use crate::grid::ring::Ring;
+    use crate::grid::Direction;
+
+    #[test]
+    fn flood_fill() {
+        let hexagon = Hexagon::zero(2).unwrap();
+        let ring: HashSet<Position> = Ring::zero(1).unwrap().into_iter().collect();
+
+        // The ring walls the center off from the outer ring.
+        let inside = super::flood_fill(Position::zero(), &hexagon, |position| {
+            !ring.contains(&position)
+        });
+        assert_eq!(HashSet::from([Position::zero()]), inside);
+
+        let outside = super::flood_fill(Direction::XY.position() * 2, &hexagon, |position| {
+            !ring.contains(&position)
+        });
+        assert_eq!(12, outside.len());
+
+        assert!(super::flood_fill(Direction::XY.position() * 3, &hexagon, |_| true).is_empty());
+        assert_eq!(
+            19,
+            super::flood_fill(Position::zero(), &hexagon, |_| true).len()
+        );
+    }
+
+    #[test]
+    fn components() {
+        let mut positions: Vec<Position> = Ring::zero(2).unwrap().into_iter().collect();
+        positions.push(Position::zero());
+        positions.push(Direction::YZ.position() * 4);
+        positions.push(Direction::YZ.position() * 5);
+
+        let components = super::components(positions);
+        assert_eq!(
+            vec![12, 2, 1],
+            components.iter().map(HashSet::len).collect::<Vec<_>>()
+        );
+        assert_eq!(HashSet::from([Position::zero()]), components[2]);
+        assert!(super::components([]).is_empty());
+    }
+}
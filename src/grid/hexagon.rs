@@ -1,22 +1,61 @@
+use std::fmt::Display;
+
+#[cfg(feature = "generate")]
+use rand::Rng;
+
 use super::line::Line;
 use super::ring::{Ring, RingIterator};
 use super::segment::Segment;
 use super::{Direction, Distance, Position};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "HexagonFields"))]
 pub struct Hexagon {
     origin: Position,
     radius: Distance,
 }
 
+// Hexagons are read through their constructor, so that those of
+// negative radius are rejected.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct HexagonFields {
+    origin: Position,
+    radius: Distance,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<HexagonFields> for Hexagon {
+    type Error = HexagonError;
+
+    fn try_from(fields: HexagonFields) -> Result<Self, Self::Error> {
+        Hexagon::new(fields.origin, fields.radius)
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum HexagonError {
     InsufficientRadius(Distance),
 }
 
+impl Display for HexagonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HexagonError::InsufficientRadius(radius) => {
+                write!(f, "radius {radius} of a hexagon is negative")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HexagonError {}
+
 impl Hexagon {
+    // A hexagon of radius zero is the single position at its origin,
+    // without any rings, whose segments are of a single position.
     pub fn new(origin: Position, radius: Distance) -> Result<Self, HexagonError> {
-        if radius > 0 {
+        if radius >= 0 {
             Ok(Hexagon { origin, radius })
         } else {
             Err(HexagonError::InsufficientRadius(radius))
@@ -44,7 +83,117 @@ impl Hexagon {
     }
 
     pub fn contains(&self, position: Position) -> bool {
-        (position - self.origin).distance() <= self.radius
+        self.origin.distance_to(position) <= self.radius
+    }
+
+    pub fn translate(&self, offset: Position) -> Self {
+        Hexagon {
+            origin: self.origin + offset,
+            radius: self.radius,
+        }
+    }
+
+    // The hexagon with the same origin and a radius larger by the given
+    // number of rings, or smaller when it is negative.
+    pub fn grow(&self, delta: Distance) -> Result<Self, HexagonError> {
+        Self::new(self.origin, self.radius + delta)
+    }
+
+    pub fn intersects(&self, other: &Hexagon) -> bool {
+        self.origin.distance_to(other.origin) <= self.radius + other.radius
+    }
+
+    // The positions of either hexagon, each of them once: those of this
+    // hexagon first, then those of the other one that are not part of
+    // this one.
+    pub fn union_iter(&self, other: &Hexagon) -> impl Iterator<Item = Position> {
+        let hexagon = *self;
+        self.into_iter().chain(
+            other
+                .into_iter()
+                .filter(move |position| !hexagon.contains(*position)),
+        )
+    }
+
+    // The positions of this hexagon that are also part of the other one.
+    pub fn intersection_iter(&self, other: &Hexagon) -> impl Iterator<Item = Position> {
+        let other = *other;
+        self.into_iter()
+            .filter(move |position| other.contains(*position))
+    }
+
+    // The number of positions of the hexagon.
+    pub fn area(&self) -> usize {
+        (3 * self.radius * (self.radius + 1) + 1) as usize
+    }
+
+    // The positions of the outermost ring, or the origin alone for a
+    // hexagon of radius zero.
+    pub fn border(&self) -> impl Iterator<Item = Position> {
+        self.into_iter().skip(self.interior_area())
+    }
+
+    // The positions that are not part of the border, which are those of
+    // the hexagon one ring smaller.
+    pub fn interior(&self) -> impl Iterator<Item = Position> {
+        self.into_iter().take(self.interior_area())
+    }
+
+    // The hexagon is walked ring by ring, so its interior comes first.
+    fn interior_area(&self) -> usize {
+        self.grow(-1).map(|interior| interior.area()).unwrap_or(0)
+    }
+
+    // The position at the given index in the order the hexagon is
+    // walked, ring by ring from the origin, found without walking the
+    // positions before it.
+    pub fn position(&self, index: usize) -> Option<Position> {
+        if index >= self.area() {
+            return None;
+        }
+        if index == 0 {
+            return Some(self.origin);
+        }
+
+        // Each ring holds six positions for every step of its radius.
+        let mut index = index as Distance - 1;
+        let mut radius = 1;
+        while index >= radius * 6 {
+            index -= radius * 6;
+            radius += 1;
+        }
+
+        Ring::new(self.origin, radius).unwrap().position(index)
+    }
+
+    // The index of the given position in the order the hexagon is
+    // walked, the inverse of `position`.
+    pub fn index(&self, position: Position) -> Option<usize> {
+        let radius = self.origin.distance_to(position);
+        if radius > self.radius {
+            return None;
+        }
+        if radius == 0 {
+            return Some(0);
+        }
+
+        let before = 3 * radius * (radius - 1) + 1;
+        let index = Ring::new(self.origin, radius).unwrap().index(position)?;
+        Some((before + index) as usize)
+    }
+
+    // A position of the hexagon picked uniformly at random.
+    #[cfg(feature = "generate")]
+    pub fn random_position(&self, rng: &mut impl Rng) -> Position {
+        self.position(rng.gen_range(0..self.area())).unwrap()
+    }
+
+    // A hexagon of the given radius that lies within this one, centered
+    // on a random position, if the radius is not larger than this one's.
+    #[cfg(feature = "generate")]
+    pub fn random_subhexagon(&self, rng: &mut impl Rng, radius: Distance) -> Option<Hexagon> {
+        let centers = Hexagon::new(self.origin, self.radius - radius).ok()?;
+        Hexagon::new(centers.random_position(rng), radius).ok()
     }
 
     pub fn segment(&self, distance: Distance, direction: Direction) -> Option<Segment> {
@@ -68,6 +217,43 @@ impl Hexagon {
         (-self.radius..=self.radius)
             .map(move |distance| (distance, hexagon.segment(distance, direction).unwrap()))
     }
+
+    // The keys of the three lines of the hexagon through the position,
+    // one for each of the normalized directions, in their order. Each
+    // line keeps one coordinate of its positions fixed, which tells its
+    // distance without walking any segment.
+    pub fn segment_keys(&self, position: Position) -> Option<[(Direction, Distance); 3]> {
+        if !self.contains(position) {
+            return None;
+        }
+
+        let (x, y, z) = (position - self.origin).coordinates();
+        Some(Direction::normalized().map(|direction| {
+            let (dx, dy, _dz) = direction.position().coordinates();
+            let (ox, oy, oz) = direction.rotate().position().coordinates();
+            let distance = match (dx, dy) {
+                (0, _) => x / ox,
+                (_, 0) => y / oy,
+                _ => z / oz,
+            };
+            (direction, distance)
+        }))
+    }
+
+    // The triangular sixth of the hexagon between the corners in the
+    // given direction and in the next clockwise direction, walked ring
+    // by ring from the origin. It holds the first corner of every ring
+    // but not the second, so the six wedges of a hexagon share no
+    // positions and cover all of it but its origin.
+    pub fn wedge(&self, direction: Direction) -> impl Iterator<Item = Position> {
+        let origin = self.origin;
+        (1..=self.radius).flat_map(move |radius| {
+            Ring::new(origin, radius)
+                .unwrap()
+                .segment(direction)
+                .into_iter()
+        })
+    }
 }
 
 impl IntoIterator for Hexagon {
@@ -90,7 +276,7 @@ impl HexagonIterator {
     pub fn new(hexagon: Hexagon) -> Self {
         Self {
             hexagon,
-            ring_iterator: hexagon.ring(1).unwrap().into_iter(),
+            ring_iterator: Ring::new(hexagon.origin, 1).unwrap().into_iter(),
             step: 0,
         }
     }
@@ -126,6 +312,11 @@ impl Iterator for HexagonIterator {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
+    #[cfg(feature = "generate")]
+    use rand::{rngs::StdRng, SeedableRng};
+
     use super::*;
 
     #[test]
@@ -149,6 +340,114 @@ mod tests {
             .all(|position| !hexagon.contains(position)));
     }
 
+    #[test]
+    fn border() {
+        let hexagon = Hexagon::new(Position::new((3, -4, 1)).unwrap(), 3).unwrap();
+        let border: HashSet<Position> = hexagon.border().collect();
+        let interior: HashSet<Position> = hexagon.interior().collect();
+
+        assert_eq!(
+            hexagon.ring(3).unwrap().into_iter().collect::<HashSet<_>>(),
+            border
+        );
+        assert_eq!(
+            hexagon
+                .grow(-1)
+                .unwrap()
+                .into_iter()
+                .collect::<HashSet<_>>(),
+            interior
+        );
+        assert!(border.is_disjoint(&interior));
+        assert_eq!(hexagon.area(), border.len() + interior.len());
+
+        let single = Hexagon::zero(0).unwrap();
+        assert_eq!(vec![Position::zero()], single.border().collect::<Vec<_>>());
+        assert_eq!(0, single.interior().count());
+    }
+
+    #[test]
+    fn wedge() {
+        let hexagon = Hexagon::new(Position::new((3, -4, 1)).unwrap(), 3).unwrap();
+        let mut covered = HashSet::from([hexagon.origin()]);
+
+        for direction in Direction::all() {
+            let wedge: Vec<Position> = hexagon.wedge(direction).collect();
+            assert_eq!(6, wedge.len());
+            assert_eq!(
+                Some(&(hexagon.origin() + direction.position())),
+                wedge.first()
+            );
+            assert!(wedge.iter().all(|position| covered.insert(*position)));
+
+            // Rotating a wedge around the origin lands on the next one.
+            let rotated: HashSet<Position> = wedge
+                .iter()
+                .map(|position| position.rotate_cw(hexagon.origin()))
+                .collect();
+            assert_eq!(
+                hexagon.wedge(direction.rotate()).collect::<HashSet<_>>(),
+                rotated
+            );
+        }
+
+        assert_eq!(hexagon.into_iter().collect::<HashSet<_>>(), covered);
+        assert_eq!(0, Hexagon::zero(0).unwrap().wedge(Direction::XY).count());
+    }
+
+    #[test]
+    fn operations() {
+        let hexagon = Hexagon::new(Position::new((3, -4, 1)).unwrap(), 2).unwrap();
+        let offset = Position::new((1, 1, -2)).unwrap();
+
+        let translated = hexagon.translate(offset);
+        assert_eq!(hexagon.origin() + offset, translated.origin());
+        assert_eq!(hexagon.radius(), translated.radius());
+
+        assert_eq!(4, hexagon.grow(2).unwrap().radius());
+        assert_eq!(0, hexagon.grow(-2).unwrap().radius());
+        assert!(hexagon.grow(-3).is_err());
+
+        // Hexagons whose origins are 3 apart share the positions of
+        // their rings that face each other.
+        let other = hexagon.translate(Direction::XY.position() * 3);
+        assert!(hexagon.intersects(&other));
+        assert!(!hexagon.intersects(&hexagon.translate(Direction::XY.position() * 5)));
+
+        let intersection: HashSet<Position> = hexagon.intersection_iter(&other).collect();
+        let union: Vec<Position> = hexagon.union_iter(&other).collect();
+        assert_eq!(4, intersection.len());
+        assert!(intersection
+            .iter()
+            .all(|position| hexagon.contains(*position) && other.contains(*position)));
+        assert_eq!(2 * hexagon.area() - 4, union.len());
+        assert_eq!(union.len(), union.iter().collect::<HashSet<_>>().len());
+        assert_eq!(
+            0,
+            hexagon
+                .intersection_iter(&hexagon.translate(Direction::XY.position() * 5))
+                .count()
+        );
+    }
+
+    #[test]
+    fn segment_keys() {
+        let hexagon = Hexagon::new(Position::new((3, -4, 1)).unwrap(), 3).unwrap();
+
+        for position in hexagon {
+            let keys = hexagon.segment_keys(position).unwrap();
+            assert_eq!(
+                Direction::normalized(),
+                keys.map(|(direction, _)| direction)
+            );
+            for (direction, distance) in keys {
+                let segment = hexagon.segment(distance, direction).unwrap();
+                assert!(segment.into_iter().any(|other| other == position));
+            }
+        }
+        assert_eq!(None, hexagon.segment_keys(Position::zero()));
+    }
+
     #[test]
     fn segment() {
         let hexagon = Hexagon::new(Position::new((3, -4, 1)).unwrap(), 3).unwrap();
@@ -273,4 +572,59 @@ mod tests {
             .into_iter()
             .all(|position| hexagon.contains(position)));
     }
+
+    #[test]
+    fn single() {
+        let origin = Position::new((3, -4, 1)).unwrap();
+        let hexagon = Hexagon::new(origin, 0).unwrap();
+
+        assert!(Hexagon::new(origin, -1).is_err());
+        assert_eq!(vec![origin], hexagon.into_iter().collect::<Vec<_>>());
+        assert!(hexagon.ring(0).is_none());
+        assert!(hexagon.ring(1).is_none());
+
+        for direction in Direction::all() {
+            let segments: Vec<_> = hexagon.segments(direction).collect();
+            assert_eq!(1, segments.len());
+
+            let (distance, segment) = segments[0];
+            assert_eq!(0, distance);
+            assert_eq!(vec![origin], segment.into_iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn random() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let hexagon = Hexagon::new(Position::new((3, -4, 1)).unwrap(), 2).unwrap();
+        assert_eq!(19, hexagon.area());
+        assert_eq!(hexagon.area(), hexagon.into_iter().count());
+
+        let positions: HashSet<Position> = (0..500)
+            .map(|_| hexagon.random_position(&mut rng))
+            .collect();
+        assert_eq!(hexagon.into_iter().collect::<HashSet<_>>(), positions);
+
+        for radius in 0..=2 {
+            let subhexagon = hexagon.random_subhexagon(&mut rng, radius).unwrap();
+            assert_eq!(radius, subhexagon.radius());
+            assert!(subhexagon
+                .into_iter()
+                .all(|position| hexagon.contains(position)));
+        }
+        assert!(hexagon.random_subhexagon(&mut rng, 3).is_none());
+        assert!(hexagon.random_subhexagon(&mut rng, -1).is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let hexagon = Hexagon::new(Position::new((3, -4, 1)).unwrap(), 2).unwrap();
+        let json = serde_json::to_string(&hexagon).unwrap();
+        assert_eq!(hexagon, serde_json::from_str(&json).unwrap());
+
+        let json = json.replace("\"radius\":2", "\"radius\":-2");
+        assert!(serde_json::from_str::<Hexagon>(&json).is_err());
+    }
 }
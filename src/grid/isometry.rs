@@ -0,0 +1,219 @@
+use super::{Axis, Direction, Position};
+
+// A distance-preserving transformation of the grid, made of an
+// optional reflection across the `X` axis, followed by a number of
+// clockwise rotation steps around the origin, followed by a
+// translation.
+//
+// Any composition of translations, rotations around arbitrary centers
+// and reflections across arbitrary axes can be expressed this way.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Isometry {
+    reflected: bool,
+    rotation: i32,
+    translation: Position,
+}
+
+const IDENTITY: Isometry = Isometry {
+    reflected: false,
+    rotation: 0,
+    translation: Position(0, 0),
+};
+
+impl Isometry {
+    pub fn identity() -> Self {
+        IDENTITY
+    }
+
+    pub fn translation(offset: Position) -> Self {
+        Isometry {
+            translation: offset,
+            ..IDENTITY
+        }
+    }
+
+    // Rotates the grid around the given center by the given number of
+    // clockwise steps of sixty degrees. Negative steps rotate the grid
+    // counter-clockwise.
+    pub fn rotation(center: Position, steps: i32) -> Self {
+        Self::translation(-center)
+            .then(Isometry {
+                rotation: steps.rem_euclid(6),
+                ..IDENTITY
+            })
+            .then(Self::translation(center))
+    }
+
+    // Reflects the grid across the line that goes through the given
+    // center and keeps the given axis' coordinate constant.
+    pub fn reflection(center: Position, axis: Axis) -> Self {
+        use Axis::*;
+
+        let rotation = match axis {
+            X => 0,
+            Z => 2,
+            Y => 4,
+        };
+
+        Self::translation(-center)
+            .then(Isometry {
+                reflected: true,
+                rotation,
+                ..IDENTITY
+            })
+            .then(Self::translation(center))
+    }
+
+    pub fn is_reflected(&self) -> bool {
+        self.reflected
+    }
+
+    pub fn rotation_steps(&self) -> i32 {
+        self.rotation
+    }
+
+    pub fn translation_offset(&self) -> Position {
+        self.translation
+    }
+
+    // Returns the isometry that applies this isometry first and the
+    // given one afterwards.
+    pub fn then(&self, other: Isometry) -> Self {
+        // Reflecting after rotating is the same as rotating in the
+        // opposite direction after reflecting.
+        let rotation = if other.reflected {
+            other.rotation - self.rotation
+        } else {
+            other.rotation + self.rotation
+        };
+
+        Isometry {
+            reflected: self.reflected != other.reflected,
+            rotation: rotation.rem_euclid(6),
+            translation: other.apply(self.translation),
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        // A reflected linear part is its own inverse, while a rotation
+        // is undone by rotating the same number of steps back.
+        let linear = Isometry {
+            reflected: self.reflected,
+            rotation: if self.reflected {
+                self.rotation
+            } else {
+                (-self.rotation).rem_euclid(6)
+            },
+            translation: Position::zero(),
+        };
+
+        Isometry {
+            translation: -linear.apply(self.translation),
+            ..linear
+        }
+    }
+
+    pub fn apply(&self, position: Position) -> Position {
+        self.apply_linear(position) + self.translation
+    }
+
+    // Directions are only affected by the rotation and reflection of
+    // an isometry, not by its translation.
+    pub fn apply_direction(&self, direction: Direction) -> Direction {
+        let position = self.apply_linear(direction.position());
+
+        Direction::all()
+            .into_iter()
+            .find(|direction| direction.position() == position)
+            .unwrap()
+    }
+
+    fn apply_linear(&self, position: Position) -> Position {
+        let mut position = if self.reflected {
+            position.reflect(Axis::X)
+        } else {
+            position
+        };
+
+        for _ in 0..self.rotation {
+            position = position.rotate_cw(Position::zero());
+        }
+
+        position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation() {
+        let center = Position::new((1, -2, 1)).unwrap();
+        let rotation = Isometry::rotation(center, 1);
+
+        assert_eq!(center, rotation.apply(center));
+
+        for direction in Direction::all() {
+            assert_eq!(
+                center + direction.rotate().position(),
+                rotation.apply(center + direction.position())
+            );
+            assert_eq!(direction.rotate(), rotation.apply_direction(direction));
+        }
+
+        let position = Position::new((4, -1, -3)).unwrap();
+        let mut rotated = position;
+        for _ in 0..6 {
+            rotated = rotation.apply(rotated);
+        }
+
+        assert_eq!(position, rotated);
+        assert_eq!(
+            Isometry::rotation(center, -1).apply(position),
+            Isometry::rotation(center, 5).apply(position)
+        );
+    }
+
+    #[test]
+    fn reflection() {
+        let center = Position::new((1, -2, 1)).unwrap();
+
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let reflection = Isometry::reflection(center, axis);
+            let position = Position::new((4, -1, -3)).unwrap();
+            let reflected = reflection.apply(position);
+
+            assert_eq!(position.axis(axis), reflected.axis(axis));
+            assert_eq!(
+                (position - center).distance(),
+                (reflected - center).distance()
+            );
+            assert_eq!(position, reflection.apply(reflected));
+            assert_eq!(Isometry::identity(), reflection.then(reflection));
+        }
+
+        let reflection = Isometry::reflection(Position::zero(), Axis::Z);
+        assert_eq!(
+            (-1, 4, -3),
+            reflection.apply(Position::new((4, -1, -3)).unwrap()).into()
+        );
+    }
+
+    #[test]
+    fn composition() {
+        let rotation = Isometry::rotation(Position::new((1, -2, 1)).unwrap(), 2);
+        let reflection = Isometry::reflection(Position::new((0, 3, -3)).unwrap(), Axis::Y);
+        let translation = Isometry::translation(Position::new((2, 2, -4)).unwrap());
+        let composed = rotation.then(reflection).then(translation);
+
+        let position = Position::new((4, -1, -3)).unwrap();
+
+        assert_eq!(
+            translation.apply(reflection.apply(rotation.apply(position))),
+            composed.apply(position)
+        );
+        assert_eq!(position, composed.inverse().apply(composed.apply(position)));
+        assert_eq!(Isometry::identity(), composed.then(composed.inverse()));
+    }
+}
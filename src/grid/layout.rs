@@ -0,0 +1,213 @@
+use super::Position;
+
+// A point in pixel space. The `y` axis grows downwards, as is usual
+// for screen and image coordinates.
+pub type Point = (f64, f64);
+
+const SQRT_3: f64 = 1.732_050_807_568_877_2;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Orientation {
+    PointyTop,
+    FlatTop,
+}
+
+// Maps positions on the grid to points in pixel space.
+//
+// Positions are converted to axial coordinates `(q, r)`, as given by
+// `Position::to_axial`, such that, in the pointy-top orientation,
+// segments in the `XY` direction are laid out as rows from left to
+// right, and rows of increasing `z` are laid out from bottom to top.
+// This matches the text rendering of a puzzle.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Layout {
+    orientation: Orientation,
+    size: f64,
+    origin: Point,
+}
+
+impl Layout {
+    // The size of a layout is the distance from the center of a cell
+    // to any of its corners.
+    pub fn new(orientation: Orientation, size: f64, origin: Point) -> Self {
+        Layout {
+            orientation,
+            size,
+            origin,
+        }
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn size(&self) -> f64 {
+        self.size
+    }
+
+    pub fn origin(&self) -> Point {
+        self.origin
+    }
+
+    pub fn position_to_pixel(&self, position: Position) -> Point {
+        let (q, r) = position.to_axial();
+        let (q, r) = (q as f64, r as f64);
+
+        let (x, y) = match self.orientation {
+            Orientation::PointyTop => (SQRT_3 * q + SQRT_3 / 2.0 * r, 1.5 * r),
+            Orientation::FlatTop => (1.5 * q, SQRT_3 / 2.0 * q + SQRT_3 * r),
+        };
+
+        (self.origin.0 + x * self.size, self.origin.1 + y * self.size)
+    }
+
+    // The position of the cell containing the given point, the inverse
+    // of `position_to_pixel` for the centers of cells.
+    pub fn pixel_to_position(&self, point: Point) -> Position {
+        let x = (point.0 - self.origin.0) / self.size;
+        let y = (point.1 - self.origin.1) / self.size;
+
+        let (q, r) = match self.orientation {
+            Orientation::PointyTop => (SQRT_3 / 3.0 * x - y / 3.0, 2.0 / 3.0 * y),
+            Orientation::FlatTop => (2.0 / 3.0 * x, -x / 3.0 + SQRT_3 / 3.0 * y),
+        };
+
+        Position::round(q + r, -q, -r)
+    }
+
+    // The corners of the cell at the given position, in clockwise
+    // order. Adjacent cells share exactly two corners.
+    pub fn cell_corners(&self, position: Position) -> [Point; 6] {
+        let (x, y) = self.position_to_pixel(position);
+        let start = match self.orientation {
+            Orientation::PointyTop => -30.0,
+            Orientation::FlatTop => 0.0,
+        };
+
+        let mut corners = [(0.0, 0.0); 6];
+        for (index, corner) in corners.iter_mut().enumerate() {
+            let angle = (start + 60.0 * index as f64).to_radians();
+            *corner = (x + self.size * angle.cos(), y + self.size * angle.sin());
+        }
+
+        corners
+    }
+
+    // The midpoints of the edges of the cell at the given position.
+    // The edge midpoint at each index lies between the corners at
+    // that index and the next one, as returned by `cell_corners`.
+    pub fn edge_midpoints(&self, position: Position) -> [Point; 6] {
+        let corners = self.cell_corners(position);
+
+        let mut midpoints = [(0.0, 0.0); 6];
+        for (index, midpoint) in midpoints.iter_mut().enumerate() {
+            let (ax, ay) = corners[index];
+            let (bx, by) = corners[(index + 1) % 6];
+            *midpoint = ((ax + bx) / 2.0, (ay + by) / 2.0);
+        }
+
+        midpoints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Direction;
+
+    fn assert_close(expected: Point, actual: Point) {
+        assert!(
+            (expected.0 - actual.0).abs() < 1e-9 && (expected.1 - actual.1).abs() < 1e-9,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    fn distance(a: Point, b: Point) -> f64 {
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn position_to_pixel() {
+        let layout = Layout::new(Orientation::PointyTop, 2.0, (10.0, 20.0));
+
+        assert_close((10.0, 20.0), layout.position_to_pixel(Position::zero()));
+        assert_close(
+            (10.0 + 2.0 * SQRT_3, 20.0),
+            layout.position_to_pixel(Direction::XY.position()),
+        );
+        assert_close(
+            (10.0 - SQRT_3, 23.0),
+            layout.position_to_pixel(Direction::YZ.position()),
+        );
+
+        let layout = Layout::new(Orientation::FlatTop, 2.0, (0.0, 0.0));
+
+        assert_close(
+            (3.0, SQRT_3),
+            layout.position_to_pixel(Direction::XY.position()),
+        );
+    }
+
+    #[test]
+    fn pixel_to_position() {
+        for orientation in [Orientation::PointyTop, Orientation::FlatTop] {
+            let layout = Layout::new(orientation, 3.0, (1.0, -1.0));
+            let position = Position::new((2, -3, 1)).unwrap();
+
+            let (x, y) = layout.position_to_pixel(position);
+            assert_eq!(position, layout.pixel_to_position((x, y)));
+            assert_eq!(position, layout.pixel_to_position((x + 1.2, y - 1.2)));
+            for corner in layout.cell_corners(position) {
+                let inside = ((corner.0 * 4.0 + x) / 5.0, (corner.1 * 4.0 + y) / 5.0);
+                assert_eq!(position, layout.pixel_to_position(inside));
+            }
+        }
+    }
+
+    #[test]
+    fn cell_corners() {
+        for orientation in [Orientation::PointyTop, Orientation::FlatTop] {
+            let layout = Layout::new(orientation, 3.0, (1.0, -1.0));
+            let position = Position::new((2, -3, 1)).unwrap();
+            let center = layout.position_to_pixel(position);
+            let corners = layout.cell_corners(position);
+
+            for corner in corners {
+                assert!((distance(center, corner) - 3.0).abs() < 1e-9);
+            }
+
+            for direction in Direction::all() {
+                let neighbor = layout.cell_corners(position + direction.position());
+                let shared = corners
+                    .iter()
+                    .filter(|corner| {
+                        neighbor
+                            .iter()
+                            .any(|other| distance(**corner, *other) < 1e-9)
+                    })
+                    .count();
+
+                assert_eq!(2, shared);
+            }
+        }
+    }
+
+    #[test]
+    fn edge_midpoints() {
+        for orientation in [Orientation::PointyTop, Orientation::FlatTop] {
+            let layout = Layout::new(orientation, 3.0, (1.0, -1.0));
+            let position = Position::new((2, -3, 1)).unwrap();
+            let center = layout.position_to_pixel(position);
+            let midpoints = layout.edge_midpoints(position);
+
+            for direction in Direction::all() {
+                let (nx, ny) = layout.position_to_pixel(position + direction.position());
+                let between = ((center.0 + nx) / 2.0, (center.1 + ny) / 2.0);
+
+                assert!(midpoints
+                    .iter()
+                    .any(|midpoint| distance(*midpoint, between) < 1e-9));
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use super::{Direction, Distance, Position};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Line {
     origin: Position,
     direction: Direction,
@@ -20,6 +21,22 @@ impl Line {
         self.origin + (direction * distance)
     }
 
+    // The signed distance of the given position from the origin of the
+    // line, the inverse of `position`, if the position is on the line.
+    pub fn distance_of(&self, position: Position) -> Option<Distance> {
+        let relative = position - self.origin;
+
+        if relative.axis(self.direction.neutral_axis()) == 0 {
+            Some(relative.axis(self.direction.positive_axis()))
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.distance_of(position).is_some()
+    }
+
     pub fn origin(&self) -> Position {
         self.origin
     }
@@ -105,6 +122,24 @@ mod tests {
         assert_eq!((-5, 6, -1), line.position(-5).into());
     }
 
+    #[test]
+    fn distance_of() {
+        let origin = Position::new((0, 1, -1)).unwrap();
+
+        for direction in Direction::all() {
+            let line = Line::new(origin, direction);
+
+            for distance in -5..=5 {
+                assert_eq!(Some(distance), line.distance_of(line.position(distance)));
+            }
+            assert!(!line.contains(origin + direction.rotate().position()));
+            assert_eq!(
+                None,
+                line.distance_of(origin + direction.rotate_back().position() * 3)
+            );
+        }
+    }
+
     #[test]
     fn iterator() {
         let line = Line::new(Position::new((0, 1, -1)).unwrap(), Direction::XY);
@@ -0,0 +1,103 @@
+use super::Position;
+
+// Line of sight between positions of the grid.
+
+// Nudges points off the edges between cells, so that points that fall
+// exactly between two cells are always rounded the same way. The nudge
+// keeps the coordinates adding up to zero.
+const NUDGE: (f64, f64, f64) = (1e-6, 1e-6, -2e-6);
+
+// The positions on the straight line between two positions, from the
+// first one to the second one, both included. Each of them is adjacent
+// to the one before.
+pub fn line_between(a: Position, b: Position) -> Vec<Position> {
+    let steps = a.distance_to(b);
+    if steps == 0 {
+        return vec![a];
+    }
+
+    let (ax, ay, az) = a.coordinates();
+    let (bx, by, bz) = b.coordinates();
+    let lerp = |a: i32, b: i32, nudge: f64, t: f64| a as f64 + nudge + (b - a) as f64 * t;
+
+    (0..=steps)
+        .map(|step| {
+            let t = step as f64 / steps as f64;
+            Position::round(
+                lerp(ax, bx, NUDGE.0, t),
+                lerp(ay, by, NUDGE.1, t),
+                lerp(az, bz, NUDGE.2, t),
+            )
+        })
+        .collect()
+}
+
+// Whether the second position can be seen from the first, that is, no
+// position strictly between them on the line between them blocks the
+// view. The positions themselves never block it.
+pub fn is_visible(a: Position, b: Position, blocks: impl Fn(Position) -> bool) -> bool {
+    let line = line_between(a, b);
+
+    line.iter()
+        .skip(1)
+        .take(line.len().saturating_sub(2))
+        .all(|position| !blocks(*position))
+}
+
+// The positions among the given ones that can be seen from the given
+// position.
+pub fn visible(
+    from: Position,
+    positions: impl IntoIterator<Item = Position>,
+    blocks: impl Fn(Position) -> bool,
+) -> Vec<Position> {
+    positions
+        .into_iter()
+        .filter(|position| is_visible(from, *position, &blocks))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::hexagon::Hexagon;
+    use crate::grid::Direction;
+
+    #[test]
+    fn line_between() {
+        let a = Position::new((2, -3, 1)).unwrap();
+
+        assert_eq!(vec![a], super::line_between(a, a));
+        assert_eq!(
+            (0..=3)
+                .map(|step| a + Direction::XY.position() * step)
+                .collect::<Vec<_>>(),
+            super::line_between(a, a + Direction::XY.position() * 3)
+        );
+
+        let b = Position::new((-2, 4, -2)).unwrap();
+        let line = super::line_between(a, b);
+        assert_eq!(a.distance_to(b) as usize + 1, line.len());
+        assert_eq!(Some(&a), line.first());
+        assert_eq!(Some(&b), line.last());
+        assert!(line
+            .windows(2)
+            .all(|pair| pair[0].distance_to(pair[1]) == 1));
+    }
+
+    #[test]
+    fn visibility() {
+        let origin = Position::zero();
+        let wall = Direction::XY.position() * 2;
+        let blocks = |position: Position| position == wall;
+
+        assert!(is_visible(origin, wall, blocks));
+        assert!(!is_visible(origin, wall * 2, blocks));
+        assert!(is_visible(origin, Direction::YZ.position() * 3, blocks));
+
+        let hexagon = Hexagon::zero(3).unwrap();
+        let seen = visible(origin, hexagon, blocks);
+        assert_eq!(hexagon.area() - 1, seen.len());
+        assert!(!seen.contains(&(wall + Direction::XY.position())));
+    }
+}
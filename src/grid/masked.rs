@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use super::hexagon::{Hexagon, HexagonIterator};
+use super::segment::Segment;
+use super::{Direction, Distance, Position};
+
+// A hexagon with holes, positions that are not part of the playable
+// area. Lines of the hexagon that cross holes are broken into several
+// segments, one for each of the pieces between the holes. The holes are
+// shared between copies of the region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaskedHexagon {
+    hexagon: Hexagon,
+    holes: Arc<HashSet<Position>>,
+}
+
+impl MaskedHexagon {
+    // Holes outside of the hexagon are ignored.
+    pub fn new(hexagon: Hexagon, holes: impl IntoIterator<Item = Position>) -> Self {
+        let holes = holes
+            .into_iter()
+            .filter(|position| hexagon.contains(*position))
+            .collect();
+
+        MaskedHexagon {
+            hexagon,
+            holes: Arc::new(holes),
+        }
+    }
+
+    pub fn hexagon(&self) -> Hexagon {
+        self.hexagon
+    }
+
+    pub fn holes(&self) -> &HashSet<Position> {
+        &self.holes
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.hexagon.contains(position) && !self.holes.contains(&position)
+    }
+
+    // The pieces of the hexagon's lines in the given direction, keyed by
+    // the distance of their line, in the order of the lines and of the
+    // pieces along them. Lines made only of holes have no pieces.
+    pub fn segments(&self, direction: Direction) -> impl Iterator<Item = (Distance, Segment)> {
+        let masked = self.clone();
+
+        self.hexagon
+            .segments(direction)
+            .flat_map(move |(distance, segment)| {
+                masked
+                    .pieces(segment)
+                    .into_iter()
+                    .map(move |piece| (distance, piece))
+            })
+    }
+
+    // The pieces of the line at the given distance.
+    pub fn segments_at(&self, distance: Distance, direction: Direction) -> Vec<Segment> {
+        self.hexagon
+            .segment(distance, direction)
+            .map(|segment| self.pieces(segment))
+            .unwrap_or_default()
+    }
+
+    fn pieces(&self, segment: Segment) -> Vec<Segment> {
+        let mut pieces = vec![];
+        let mut start: Option<Distance> = None;
+
+        for (distance, position) in (0..).zip(segment) {
+            if self.holes.contains(&position) {
+                pieces.extend(
+                    start
+                        .take()
+                        .and_then(|start| segment.slice(start..distance)),
+                );
+            } else {
+                start.get_or_insert(distance);
+            }
+        }
+        pieces.extend(start.and_then(|start| segment.slice(start..segment.length())));
+
+        pieces
+    }
+}
+
+impl IntoIterator for MaskedHexagon {
+    type Item = Position;
+
+    type IntoIter = MaskedHexagonIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MaskedHexagonIterator::new(self)
+    }
+}
+
+// Iterates over the positions of the hexagon, skipping its holes.
+pub struct MaskedHexagonIterator {
+    holes: Arc<HashSet<Position>>,
+    hexagon_iterator: HexagonIterator,
+}
+
+impl MaskedHexagonIterator {
+    pub fn new(masked: MaskedHexagon) -> Self {
+        MaskedHexagonIterator {
+            holes: masked.holes,
+            hexagon_iterator: masked.hexagon.into_iter(),
+        }
+    }
+}
+
+impl Iterator for MaskedHexagonIterator {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let holes = &self.holes;
+        self.hexagon_iterator
+            .find(|position| !holes.contains(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::region::Region;
+
+    #[test]
+    fn segments() {
+        let hexagon = Hexagon::zero(2).unwrap();
+        let holes = [Position::zero(), Direction::XY.position() * 2];
+        let masked = MaskedHexagon::new(
+            hexagon,
+            holes
+                .into_iter()
+                .chain([Position::new((5, -5, 0)).unwrap()]),
+        );
+
+        assert_eq!(2, masked.holes().len());
+        assert_eq!(17, masked.clone().into_iter().count());
+        assert!(!masked.contains(Position::zero()));
+        assert!(masked.contains(Direction::XY.position()));
+
+        // The middle row loses its center and its last cell.
+        let pieces = masked.segments_at(0, Direction::XY);
+        assert_eq!(2, pieces.len());
+        assert_eq!(
+            vec![2, 1],
+            pieces
+                .iter()
+                .map(|piece| piece.length())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Direction::XY.position(), pieces[1].start());
+
+        for direction in Direction::normalized() {
+            let positions: Vec<Position> = masked
+                .segments(direction)
+                .flat_map(|(_distance, segment)| segment)
+                .collect();
+
+            assert_eq!(17, positions.len());
+            assert!(positions.iter().all(|position| masked.contains(*position)));
+        }
+        assert_eq!(6, masked.segments(Direction::XY).count());
+
+        // Broken lines have no single segment, while shortened ones do.
+        assert_eq!(Some((Direction::XY, 0)), masked.broken_line());
+        assert_eq!(None, Region::segment(&masked, 0, Direction::XY));
+        let corner = MaskedHexagon::new(hexagon, [Direction::XY.position() * 2]);
+        assert_eq!(None, corner.broken_line());
+        assert_eq!(
+            Some(4),
+            Region::segment(&corner, 0, Direction::XY).map(|segment| segment.length())
+        );
+    }
+}
@@ -1,16 +1,45 @@
+pub mod chunk;
+pub mod edge;
+pub mod fill;
 pub mod hexagon;
+pub mod isometry;
+pub mod layout;
 pub mod line;
+pub mod los;
+pub mod masked;
+pub mod offset;
+pub mod parallelogram;
+pub mod path;
+pub mod rect;
+pub mod region;
 pub mod ring;
 pub mod segment;
+pub mod torus;
+pub mod triangle;
+pub mod vertex;
 
+use std::fmt::Display;
 use std::ops::{Add, Mul, Neg, Sub};
 
+pub use edge::Edge;
+pub use fill::{components, flood_fill};
+pub use vertex::Vertex;
+
+use hexagon::HexagonError;
+use parallelogram::ParallelogramError;
+use rect::RectError;
+use ring::RingError;
+use segment::SegmentError;
+use torus::TorusError;
+use triangle::TriangleError;
+
 pub type Coordinate = i32;
 pub type Coordinates = (i32, i32, i32);
 
 pub type Distance = i32;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position(Coordinate, Coordinate);
 
 const ZERO: Position = Position(0, 0);
@@ -18,6 +47,117 @@ const ZERO: Position = Position(0, 0);
 #[derive(Debug, Copy, Clone)]
 pub enum PositionError {
     InvalidCoordinates(Coordinates),
+    InvalidDoubled(Coordinate, Coordinate),
+}
+
+impl Display for PositionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PositionError::InvalidCoordinates((x, y, z)) => {
+                write!(f, "coordinates ({x}, {y}, {z}) do not add up to zero")
+            }
+            PositionError::InvalidDoubled(column, row) => {
+                write!(
+                    f,
+                    "doubled coordinates ({column}, {row}) do not add up to an even number"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+// Any of the errors of the grid, for callers that build several kinds
+// of shapes and want to propagate their errors alike.
+#[derive(Debug, Copy, Clone)]
+pub enum GridError {
+    Position(PositionError),
+    Hexagon(HexagonError),
+    Ring(RingError),
+    Segment(SegmentError),
+    Rect(RectError),
+    Parallelogram(ParallelogramError),
+    Triangle(TriangleError),
+    Torus(TorusError),
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::Position(error) => error.fmt(f),
+            GridError::Hexagon(error) => error.fmt(f),
+            GridError::Ring(error) => error.fmt(f),
+            GridError::Segment(error) => error.fmt(f),
+            GridError::Rect(error) => error.fmt(f),
+            GridError::Parallelogram(error) => error.fmt(f),
+            GridError::Triangle(error) => error.fmt(f),
+            GridError::Torus(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for GridError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(match self {
+            GridError::Position(error) => error,
+            GridError::Hexagon(error) => error,
+            GridError::Ring(error) => error,
+            GridError::Segment(error) => error,
+            GridError::Rect(error) => error,
+            GridError::Parallelogram(error) => error,
+            GridError::Triangle(error) => error,
+            GridError::Torus(error) => error,
+        })
+    }
+}
+
+impl From<PositionError> for GridError {
+    fn from(error: PositionError) -> Self {
+        GridError::Position(error)
+    }
+}
+
+impl From<HexagonError> for GridError {
+    fn from(error: HexagonError) -> Self {
+        GridError::Hexagon(error)
+    }
+}
+
+impl From<RingError> for GridError {
+    fn from(error: RingError) -> Self {
+        GridError::Ring(error)
+    }
+}
+
+impl From<SegmentError> for GridError {
+    fn from(error: SegmentError) -> Self {
+        GridError::Segment(error)
+    }
+}
+
+impl From<RectError> for GridError {
+    fn from(error: RectError) -> Self {
+        GridError::Rect(error)
+    }
+}
+
+impl From<ParallelogramError> for GridError {
+    fn from(error: ParallelogramError) -> Self {
+        GridError::Parallelogram(error)
+    }
+}
+
+impl From<TriangleError> for GridError {
+    fn from(error: TriangleError) -> Self {
+        GridError::Triangle(error)
+    }
+}
+
+impl From<TorusError> for GridError {
+    fn from(error: TorusError) -> Self {
+        GridError::Torus(error)
+    }
 }
 
 impl Position {
@@ -57,13 +197,120 @@ impl Position {
         }
     }
 
+    // The distance from the origin, in steps between adjacent cells.
     pub fn distance(&self) -> Distance {
         self.x().abs().max(self.y().abs()).max(self.z().abs())
     }
 
+    pub fn distance_to(&self, other: Position) -> Distance {
+        (other - *self).distance()
+    }
+
     pub fn coordinates(&self) -> Coordinates {
         (*self).into()
     }
+
+    // Rotates the position by sixty degrees clockwise around the given
+    // center, in the same sense as `Direction::rotate`.
+    pub fn rotate_cw(&self, center: Position) -> Self {
+        let (_x, y, z) = (*self - center).coordinates();
+        center + Position(-y, -z)
+    }
+
+    pub fn rotate_ccw(&self, center: Position) -> Self {
+        let (x, _y, z) = (*self - center).coordinates();
+        center + Position(-z, -x)
+    }
+
+    // Reflects the position across the line through the origin that
+    // keeps the given axis' coordinate constant, swapping the other two.
+    // Reflections across other lines are made with `Isometry`.
+    pub fn reflect(&self, axis: Axis) -> Self {
+        use Axis::*;
+
+        let (x, y, z) = self.coordinates();
+        match axis {
+            X => Position(x, z),
+            Y => Position(z, y),
+            Z => Position(y, x),
+        }
+    }
+
+    // The index of the position among those of the hexagon of the given
+    // radius around the origin, from zero for the origin to one less
+    // than the hexagon's area, in the order the hexagon is walked.
+    pub fn to_index(&self, radius: Distance) -> Option<usize> {
+        hexagon::Hexagon::zero(radius).ok()?.index(*self)
+    }
+
+    pub fn from_index(index: usize, radius: Distance) -> Option<Self> {
+        hexagon::Hexagon::zero(radius).ok()?.position(index)
+    }
+
+    // Packs the coordinates of any position into a single number, the
+    // `x` coordinate in the high half and the `y` coordinate in the low
+    // half.
+    pub fn to_packed(&self) -> u64 {
+        ((self.x() as u32 as u64) << 32) | self.y() as u32 as u64
+    }
+
+    pub fn from_packed(packed: u64) -> Self {
+        Position(
+            (packed >> 32) as u32 as Coordinate,
+            packed as u32 as Coordinate,
+        )
+    }
+
+    // The six positions adjacent to this one, in the order of the
+    // directions leading to them, as given by `Direction::all`.
+    pub fn neighbors(&self) -> [Position; 6] {
+        Direction::all().map(|direction| *self + direction.position())
+    }
+
+    // The adjacent positions, each with the direction leading to it.
+    pub fn directed_neighbors(&self) -> impl Iterator<Item = (Direction, Position)> {
+        let position = *self;
+        Direction::all()
+            .into_iter()
+            .map(move |direction| (direction, position + direction.position()))
+    }
+
+    // The borders with the adjacent positions, in the order of the
+    // directions leading to them.
+    pub fn edges(&self) -> [Edge; 6] {
+        Direction::all().map(|direction| Edge::towards(*self, direction))
+    }
+
+    // The corners of the position, each of them between the neighbors in
+    // a direction and in the next clockwise one, in the order of the
+    // directions given by `Direction::all`.
+    pub fn vertices(&self) -> [Vertex; 6] {
+        Direction::all().map(|direction| Vertex::corner(*self, direction))
+    }
+
+    // Rounds fractional cube coordinates to the position of the cell
+    // that contains them. The coordinates are expected to add up to
+    // zero, as with integer coordinates.
+    pub fn round(x: f64, y: f64, z: f64) -> Self {
+        let (rx, ry, rz) = (x.round(), y.round(), z.round());
+        let (dx, dy, dz) = ((rx - x).abs(), (ry - y).abs(), (rz - z).abs());
+
+        // Rounding each coordinate separately may break the constraint
+        // that they add up to zero, so the coordinate that was rounded
+        // the furthest is derived from the other two instead.
+        if dx > dy && dx > dz {
+            Position(-(ry as Coordinate) - rz as Coordinate, ry as Coordinate)
+        } else if dy > dz {
+            Position(rx as Coordinate, -(rx as Coordinate) - rz as Coordinate)
+        } else {
+            Position(rx as Coordinate, ry as Coordinate)
+        }
+    }
+}
+
+// The distance between two positions, in steps between adjacent cells.
+pub fn distance(a: Position, b: Position) -> Distance {
+    a.distance_to(b)
 }
 
 impl Add<Position> for Position {
@@ -113,6 +360,7 @@ impl TryFrom<Coordinates> for Position {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Axis {
     X,
     Y,
@@ -120,6 +368,7 @@ pub enum Axis {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     XY,
     XZ,
@@ -154,6 +403,27 @@ impl Direction {
         NORMALIZED_DIRECTIONS
     }
 
+    // The direction leading from one position to the other, if they are
+    // adjacent.
+    pub fn between(a: Position, b: Position) -> Option<Direction> {
+        match Direction::towards(a, b) {
+            Some((direction, 1)) => Some(direction),
+            _ => None,
+        }
+    }
+
+    // The direction of the line going from one position through the
+    // other, and the number of steps between them, if they are distinct
+    // positions on a common line.
+    pub fn towards(a: Position, b: Position) -> Option<(Direction, Distance)> {
+        Direction::all().into_iter().find_map(|direction| {
+            match line::Line::new(a, direction).distance_of(b) {
+                Some(distance) if distance > 0 => Some((direction, distance)),
+                _ => None,
+            }
+        })
+    }
+
     // Returns a tuple of positive, neutral and negative axes.
     pub fn axes(&self) -> (Axis, Axis, Axis) {
         use Axis::*;
@@ -223,6 +493,17 @@ impl Direction {
     pub fn rotate_back(&self) -> Self {
         self.opposite().rotate().rotate()
     }
+
+    // The direction mirrored across the given axis, as positions are by
+    // `Position::reflect`.
+    pub fn reflect(&self, axis: Axis) -> Self {
+        let position = self.position().reflect(axis);
+
+        Direction::all()
+            .into_iter()
+            .find(|direction| direction.position() == position)
+            .unwrap()
+    }
 }
 
 const XY_UNIT: Position = Position(1, -1);
@@ -246,3 +527,161 @@ impl From<Direction> for Position {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round() {
+        assert_eq!((0, 0, 0), Position::round(0.2, -0.1, -0.1).into());
+        assert_eq!((1, -1, 0), Position::round(0.6, -0.7, 0.1).into());
+        assert_eq!((1, -1, 0), Position::round(1.6, -1.3, -0.3).into());
+        assert_eq!((-3, 1, 2), Position::round(-2.5, 0.9, 1.6).into());
+    }
+
+    #[test]
+    fn distance() {
+        let a = Position::new((2, -3, 1)).unwrap();
+        let b = Position::new((-1, 1, 0)).unwrap();
+
+        assert_eq!(4, a.distance_to(b));
+        assert_eq!(4, b.distance_to(a));
+        assert_eq!(0, a.distance_to(a));
+        assert_eq!(a.distance(), super::distance(Position::zero(), a));
+    }
+
+    #[test]
+    fn transforms() {
+        let center = Position::new((1, -2, 1)).unwrap();
+        let position = Position::new((4, -1, -3)).unwrap();
+
+        for direction in Direction::all() {
+            assert_eq!(
+                center + direction.rotate().position(),
+                (center + direction.position()).rotate_cw(center)
+            );
+            assert_eq!(
+                center + direction.rotate_back().position(),
+                (center + direction.position()).rotate_ccw(center)
+            );
+        }
+        assert_eq!(position, position.rotate_cw(center).rotate_ccw(center));
+        assert_eq!(center, center.rotate_cw(center));
+
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let reflected = position.reflect(axis);
+
+            assert_eq!(position.axis(axis), reflected.axis(axis));
+            assert_eq!(position, reflected.reflect(axis));
+            assert_eq!(
+                isometry::Isometry::reflection(Position::zero(), axis).apply(position),
+                reflected
+            );
+            for direction in Direction::all() {
+                assert_eq!(
+                    direction.position().reflect(axis),
+                    direction.reflect(axis).position()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn between() {
+        let position = Position::new((2, -3, 1)).unwrap();
+
+        for (direction, neighbor) in position.directed_neighbors() {
+            assert_eq!(Some(direction), Direction::between(position, neighbor));
+            assert_eq!(
+                Some((direction, 4)),
+                Direction::towards(position, position + direction.position() * 4)
+            );
+            assert_eq!(
+                None,
+                Direction::between(position, position + direction.position() * 2)
+            );
+        }
+
+        assert_eq!(None, Direction::between(position, position));
+        assert_eq!(None, Direction::towards(position, position));
+        assert_eq!(
+            None,
+            Direction::towards(position, position + Position::new((2, -1, -1)).unwrap())
+        );
+    }
+
+    #[test]
+    fn index() {
+        for (index, position) in hexagon::Hexagon::zero(3).unwrap().into_iter().enumerate() {
+            assert_eq!(Some(index), position.to_index(3));
+            assert_eq!(Some(position), Position::from_index(index, 3));
+        }
+
+        let outside = Direction::XY.position() * 4;
+        assert_eq!(None, outside.to_index(3));
+        assert_eq!(Some(37), outside.to_index(4));
+        assert_eq!(None, Position::from_index(37, 3));
+        assert_eq!(None, Position::zero().to_index(-1));
+
+        for position in [
+            Position::zero(),
+            Position::new((5, -7, 2)).unwrap(),
+            Position::new((-100_000, 3, 99_997)).unwrap(),
+            Position::new((i32::MIN + 1, 0, i32::MAX)).unwrap(),
+        ] {
+            assert_eq!(position, Position::from_packed(position.to_packed()));
+        }
+    }
+
+    #[test]
+    fn neighbors() {
+        let position = Position::new((2, -3, 1)).unwrap();
+        let neighbors = position.neighbors();
+
+        for neighbor in neighbors {
+            assert_eq!(1, position.distance_to(neighbor));
+            assert_eq!(
+                1,
+                neighbors.iter().filter(|other| **other == neighbor).count()
+            );
+        }
+        for (direction, neighbor) in position.directed_neighbors() {
+            assert_eq!(position, neighbor + direction.opposite().position());
+        }
+        assert_eq!(
+            neighbors.to_vec(),
+            position
+                .directed_neighbors()
+                .map(|(_direction, neighbor)| neighbor)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        use std::error::Error;
+
+        fn shapes(radius: Distance) -> Result<(ring::Ring, hexagon::Hexagon), GridError> {
+            Ok((
+                ring::Ring::zero(radius + 2)?,
+                hexagon::Hexagon::zero(radius)?,
+            ))
+        }
+
+        assert!(shapes(0).is_ok());
+        let error = shapes(-1).unwrap_err();
+        assert!(matches!(
+            error,
+            GridError::Hexagon(HexagonError::InsufficientRadius(-1))
+        ));
+        assert_eq!("radius -1 of a hexagon is negative", error.to_string());
+        assert!(error.source().is_some());
+
+        let error = Position::new((1, 1, 1)).unwrap_err();
+        assert_eq!(
+            "coordinates (1, 1, 1) do not add up to zero",
+            error.to_string()
+        );
+    }
+}
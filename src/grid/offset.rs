@@ -0,0 +1,141 @@
+use super::{Coordinate, Position, PositionError};
+
+// Conversions between cube positions and the two-coordinate systems
+// used by level editors and arrays of cells.
+//
+// Axial coordinates `(q, r)` are those of the pixel layout, where `r`
+// counts rows along the `XY` direction and `q` counts cells within a
+// row, so that `q = -y` and `r = -z`. Offset and doubled coordinates
+// `(column, row)` are derived from them.
+
+// Offset coordinates shift every other row, in the pointy-top layout,
+// or every other column, in the flat-top layout, by half a cell. Odd
+// layouts push the odd rows or columns forward, and even layouts push
+// the even ones.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OffsetLayout {
+    OddR,
+    EvenR,
+    OddQ,
+    EvenQ,
+}
+
+// Doubled coordinates count half cells along rows, in the pointy-top
+// layout, or along columns, in the flat-top layout, so that no row or
+// column is shifted. Only coordinates that add up to an even number
+// address a cell.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DoubledLayout {
+    Width,
+    Height,
+}
+
+impl Position {
+    pub fn to_axial(&self) -> (Coordinate, Coordinate) {
+        (-self.y(), -self.z())
+    }
+
+    pub fn from_axial(q: Coordinate, r: Coordinate) -> Self {
+        Position(q + r, -q)
+    }
+
+    pub fn to_offset(&self, layout: OffsetLayout) -> (Coordinate, Coordinate) {
+        use OffsetLayout::*;
+
+        let (q, r) = self.to_axial();
+        match layout {
+            OddR => (q + (r - (r & 1)) / 2, r),
+            EvenR => (q + (r + (r & 1)) / 2, r),
+            OddQ => (q, r + (q - (q & 1)) / 2),
+            EvenQ => (q, r + (q + (q & 1)) / 2),
+        }
+    }
+
+    pub fn from_offset(layout: OffsetLayout, column: Coordinate, row: Coordinate) -> Self {
+        use OffsetLayout::*;
+
+        let (q, r) = match layout {
+            OddR => (column - (row - (row & 1)) / 2, row),
+            EvenR => (column - (row + (row & 1)) / 2, row),
+            OddQ => (column, row - (column - (column & 1)) / 2),
+            EvenQ => (column, row - (column + (column & 1)) / 2),
+        };
+        Self::from_axial(q, r)
+    }
+
+    pub fn to_doubled(&self, layout: DoubledLayout) -> (Coordinate, Coordinate) {
+        let (q, r) = self.to_axial();
+        match layout {
+            DoubledLayout::Width => (2 * q + r, r),
+            DoubledLayout::Height => (q, 2 * r + q),
+        }
+    }
+
+    pub fn from_doubled(
+        layout: DoubledLayout,
+        column: Coordinate,
+        row: Coordinate,
+    ) -> Result<Self, PositionError> {
+        if (column + row) & 1 != 0 {
+            return Err(PositionError::InvalidDoubled(column, row));
+        }
+
+        let (q, r) = match layout {
+            DoubledLayout::Width => ((column - row) / 2, row),
+            DoubledLayout::Height => (column, (row - column) / 2),
+        };
+        Ok(Self::from_axial(q, r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::hexagon::Hexagon;
+    use crate::grid::layout::{Layout, Orientation};
+    use crate::grid::Direction;
+
+    #[test]
+    fn offset() {
+        let hexagon = Hexagon::zero(3).unwrap();
+        let layouts = [
+            OffsetLayout::OddR,
+            OffsetLayout::EvenR,
+            OffsetLayout::OddQ,
+            OffsetLayout::EvenQ,
+        ];
+
+        for position in hexagon {
+            let (q, r) = position.to_axial();
+            assert_eq!(position, Position::from_axial(q, r));
+
+            for layout in layouts {
+                let (column, row) = position.to_offset(layout);
+                assert_eq!(position, Position::from_offset(layout, column, row));
+            }
+            for layout in [DoubledLayout::Width, DoubledLayout::Height] {
+                let (column, row) = position.to_doubled(layout);
+                assert_eq!(
+                    position,
+                    Position::from_doubled(layout, column, row).unwrap()
+                );
+            }
+        }
+
+        // The cell half a cell to the right of the origin, on the row
+        // below it, is in the same column with odd-r coordinates and in
+        // the next one with even-r coordinates.
+        let below = Direction::XZ.position();
+        let layout = Layout::new(Orientation::PointyTop, 1.0, (0.0, 0.0));
+        assert_eq!(1.5, layout.position_to_pixel(below).1);
+        assert!(layout.position_to_pixel(below).0 > 0.0);
+        assert_eq!((0, 1), below.to_offset(OffsetLayout::OddR));
+        assert_eq!((1, 1), below.to_offset(OffsetLayout::EvenR));
+        assert_eq!(
+            (1, 0),
+            Direction::XY.position().to_offset(OffsetLayout::OddR)
+        );
+
+        assert!(Position::from_doubled(DoubledLayout::Width, 1, 0).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt::Display;
+
+use super::region;
+use super::segment::Segment;
+use super::{Direction, Distance, Position};
+
+// A field of hexagons shaped as a parallelogram, made of rows of
+// `width` cells laid out in the `XY` direction, each row starting one
+// step further in the `XZ` direction than the one before, for `height`
+// rows. Positions of the field are addressed by axial coordinates
+// `(q, r)` from its origin, the first cell of the first row.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Parallelogram {
+    origin: Position,
+    width: Distance,
+    height: Distance,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum ParallelogramError {
+    InsufficientSize(Distance, Distance),
+}
+
+impl Display for ParallelogramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParallelogramError::InsufficientSize(width, height) => {
+                write!(
+                    f,
+                    "size {width}×{height} of a parallelogram is not positive"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParallelogramError {}
+
+impl Parallelogram {
+    pub fn new(
+        origin: Position,
+        width: Distance,
+        height: Distance,
+    ) -> Result<Self, ParallelogramError> {
+        if width > 0 && height > 0 {
+            Ok(Parallelogram {
+                origin,
+                width,
+                height,
+            })
+        } else {
+            Err(ParallelogramError::InsufficientSize(width, height))
+        }
+    }
+
+    pub fn zero(width: Distance, height: Distance) -> Result<Self, ParallelogramError> {
+        Self::new(Position::zero(), width, height)
+    }
+
+    pub fn origin(&self) -> Position {
+        self.origin
+    }
+
+    pub fn width(&self) -> Distance {
+        self.width
+    }
+
+    pub fn height(&self) -> Distance {
+        self.height
+    }
+
+    // The position at the given axial coordinates, if it is part of the
+    // field.
+    pub fn position(&self, q: Distance, r: Distance) -> Option<Position> {
+        if (0..self.width).contains(&q) && (0..self.height).contains(&r) {
+            Some(self.origin + Position::from_axial(q, r))
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        let (q, r) = (position - self.origin).to_axial();
+        self.position(q, r).is_some()
+    }
+
+    // The segments of the field in the given direction, keyed by their
+    // distance from the line through the origin. Rows are the segments
+    // in the `XY` direction, and columns those in the `ZX` direction.
+    pub fn segment(&self, distance: Distance, direction: Direction) -> Option<Segment> {
+        self.segments(direction)
+            .find(|(other, _segment)| *other == distance)
+            .map(|(_distance, segment)| segment)
+    }
+
+    pub fn segments(&self, direction: Direction) -> impl Iterator<Item = (Distance, Segment)> {
+        region::unbroken_segments(self.origin, *self, direction)
+    }
+}
+
+impl IntoIterator for Parallelogram {
+    type Item = Position;
+
+    type IntoIter = ParallelogramIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ParallelogramIterator::new(self)
+    }
+}
+
+// Iterates over the positions of a field row by row.
+pub struct ParallelogramIterator {
+    parallelogram: Parallelogram,
+    index: Distance,
+}
+
+impl ParallelogramIterator {
+    pub fn new(parallelogram: Parallelogram) -> Self {
+        ParallelogramIterator {
+            parallelogram,
+            index: 0,
+        }
+    }
+}
+
+impl Iterator for ParallelogramIterator {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let width = self.parallelogram.width;
+        let position = self
+            .parallelogram
+            .position(self.index % width, self.index / width)?;
+        self.index += 1;
+        Some(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn segments() {
+        let parallelogram = Parallelogram::new(Position::new((3, -4, 1)).unwrap(), 4, 3).unwrap();
+
+        assert_eq!(12, parallelogram.into_iter().count());
+        assert!(parallelogram.contains(parallelogram.origin()));
+        assert!(!parallelogram.contains(parallelogram.origin() - Direction::XY.position()));
+        assert!(Parallelogram::zero(4, 0).is_err());
+
+        for direction in Direction::normalized() {
+            let mut covered = HashSet::new();
+
+            for (distance, segment) in parallelogram.segments(direction) {
+                assert_eq!(Some(segment), parallelogram.segment(distance, direction));
+                for position in segment {
+                    assert!(parallelogram.contains(position));
+                    assert!(covered.insert(position));
+                }
+            }
+
+            assert_eq!(12, covered.len());
+        }
+
+        let rows: Vec<_> = parallelogram.segments(Direction::XY).collect();
+        assert_eq!(3, rows.len());
+        assert!(rows
+            .iter()
+            .all(|(_distance, segment)| segment.length() == 4));
+        assert_eq!(
+            parallelogram.origin(),
+            parallelogram.segment(0, Direction::XY).unwrap().start()
+        );
+        assert_eq!(6, parallelogram.segments(Direction::YZ).count());
+    }
+}
@@ -0,0 +1,145 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use super::region::Region;
+use super::{Coordinate, Distance, Position};
+
+// Paths between positions of a region, stepping between adjacent
+// positions that are not blocked.
+
+// The positions of a shortest path from the start to the goal, both
+// included, found with A* search. There is no path when either of them
+// is outside of the region or blocked.
+pub fn shortest_path(
+    start: Position,
+    goal: Position,
+    region: &impl Region,
+    blocked: impl Fn(Position) -> bool,
+) -> Option<Vec<Position>> {
+    let open = |position: Position| region.contains(position) && !blocked(position);
+    if !open(start) || !open(goal) {
+        return None;
+    }
+
+    // Positions are not ordered, so the queue holds their coordinates,
+    // behind the estimated length of the path through them.
+    let mut queue: BinaryHeap<Reverse<(Distance, Coordinate, Coordinate)>> = BinaryHeap::new();
+    let mut steps: HashMap<Position, Distance> = HashMap::from([(start, 0)]);
+    let mut previous: HashMap<Position, Position> = HashMap::new();
+    queue.push(Reverse((start.distance_to(goal), start.x(), start.y())));
+
+    while let Some(Reverse((_estimate, x, y))) = queue.pop() {
+        let position = Position(x, y);
+        if position == goal {
+            let mut path = vec![goal];
+            while let Some(before) = previous.get(path.last().unwrap()) {
+                path.push(*before);
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let next = steps[&position] + 1;
+        for neighbor in position.neighbors() {
+            if open(neighbor) && steps.get(&neighbor).is_none_or(|known| next < *known) {
+                steps.insert(neighbor, next);
+                previous.insert(neighbor, position);
+                queue.push(Reverse((
+                    next + neighbor.distance_to(goal),
+                    neighbor.x(),
+                    neighbor.y(),
+                )));
+            }
+        }
+    }
+
+    None
+}
+
+// The number of steps from the start to every position of the region
+// that can be reached from it, found with breadth-first search.
+pub fn distances(
+    start: Position,
+    region: &impl Region,
+    blocked: impl Fn(Position) -> bool,
+) -> HashMap<Position, Distance> {
+    let mut distances = HashMap::new();
+    if !region.contains(start) || blocked(start) {
+        return distances;
+    }
+
+    let mut queue = VecDeque::from([start]);
+    distances.insert(start, 0);
+
+    while let Some(position) = queue.pop_front() {
+        let next = distances[&position] + 1;
+        for neighbor in position.neighbors() {
+            if region.contains(neighbor) && !blocked(neighbor) && !distances.contains_key(&neighbor)
+            {
+                distances.insert(neighbor, next);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::grid::hexagon::Hexagon;
+    use crate::grid::ring::Ring;
+    use crate::grid::Direction;
+
+    #[test]
+    fn shortest_path() {
+        let hexagon = Hexagon::zero(3).unwrap();
+        let start = Direction::YX.position() * 3;
+        let goal = Direction::XY.position() * 3;
+
+        let path = super::shortest_path(start, goal, &hexagon, |_| false).unwrap();
+        assert_eq!(7, path.len());
+        assert_eq!(Some(&start), path.first());
+        assert_eq!(Some(&goal), path.last());
+        assert!(path
+            .windows(2)
+            .all(|pair| pair[0].distance_to(pair[1]) == 1));
+
+        // Walling off the center makes the path go around it.
+        let wall: HashSet<Position> = Hexagon::zero(1).unwrap().into_iter().collect();
+        let blocked = |position: Position| wall.contains(&position);
+        let path = super::shortest_path(start, goal, &hexagon, blocked).unwrap();
+        assert_eq!(9, path.len());
+        assert!(path.iter().all(|position| !wall.contains(position)));
+
+        assert_eq!(
+            Some(vec![start]),
+            super::shortest_path(start, start, &hexagon, blocked)
+        );
+        assert!(super::shortest_path(start, Position::zero(), &hexagon, blocked).is_none());
+
+        let ring: HashSet<Position> = Ring::zero(2).unwrap().into_iter().collect();
+        let enclosed = |position: Position| ring.contains(&position);
+        assert!(super::shortest_path(start, Position::zero(), &hexagon, enclosed).is_none());
+    }
+
+    #[test]
+    fn distances() {
+        let hexagon = Hexagon::zero(3).unwrap();
+        let distances = super::distances(Position::zero(), &hexagon, |_| false);
+
+        assert_eq!(hexagon.area(), distances.len());
+        assert!(distances
+            .iter()
+            .all(|(position, distance)| position.distance() == *distance));
+
+        let ring: HashSet<Position> = Ring::zero(2).unwrap().into_iter().collect();
+        let enclosed = super::distances(Position::zero(), &hexagon, |position| {
+            ring.contains(&position)
+        });
+        assert_eq!(7, enclosed.len());
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt::Display;
+
+use super::offset::OffsetLayout;
+use super::region;
+use super::segment::Segment;
+use super::{Direction, Distance, Position};
+
+// A rectangular field of hexagons, made of rows of cells that are laid
+// out in the `XY` direction and staggered by half a cell, as in the
+// pointy-top layout. Positions of the field are addressed by offset
+// coordinates `(column, row)` from its origin, the first cell of the
+// first row, where odd rows are shifted half a cell to the right of
+// even rows.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rect {
+    origin: Position,
+    width: Distance,
+    height: Distance,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum RectError {
+    InsufficientSize(Distance, Distance),
+}
+
+impl Display for RectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RectError::InsufficientSize(width, height) => {
+                write!(f, "size {width}×{height} of a rectangle is not positive")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RectError {}
+
+impl Rect {
+    pub fn new(origin: Position, width: Distance, height: Distance) -> Result<Self, RectError> {
+        if width > 0 && height > 0 {
+            Ok(Rect {
+                origin,
+                width,
+                height,
+            })
+        } else {
+            Err(RectError::InsufficientSize(width, height))
+        }
+    }
+
+    pub fn zero(width: Distance, height: Distance) -> Result<Self, RectError> {
+        Self::new(Position::zero(), width, height)
+    }
+
+    pub fn origin(&self) -> Position {
+        self.origin
+    }
+
+    pub fn width(&self) -> Distance {
+        self.width
+    }
+
+    pub fn height(&self) -> Distance {
+        self.height
+    }
+
+    // The position at the given offset coordinates, if it is part of the
+    // field.
+    pub fn position(&self, column: Distance, row: Distance) -> Option<Position> {
+        if (0..self.width).contains(&column) && (0..self.height).contains(&row) {
+            Some(self.origin + Position::from_offset(OffsetLayout::OddR, column, row))
+        } else {
+            None
+        }
+    }
+
+    // The offset coordinates of the given position, if it is part of the
+    // field.
+    pub fn offset(&self, position: Position) -> Option<(Distance, Distance)> {
+        let (column, row) = (position - self.origin).to_offset(OffsetLayout::OddR);
+
+        self.position(column, row).map(|_| (column, row))
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.offset(position).is_some()
+    }
+
+    // The segments of the field in the given direction, keyed by their
+    // distance from the line through the origin, as with the segments
+    // of a hexagon. Rows of the field are the segments in the `XY`
+    // direction, while the segments in the other directions cross the
+    // rows diagonally, each of them ending at the sides of the field.
+    pub fn segments(&self, direction: Direction) -> impl Iterator<Item = (Distance, Segment)> {
+        region::unbroken_segments(self.origin, *self, direction)
+    }
+}
+
+impl IntoIterator for Rect {
+    type Item = Position;
+
+    type IntoIter = RectIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        RectIterator::new(self)
+    }
+}
+
+// Iterates over the positions of a field row by row.
+pub struct RectIterator {
+    rect: Rect,
+    index: Distance,
+}
+
+impl RectIterator {
+    pub fn new(rect: Rect) -> Self {
+        RectIterator { rect, index: 0 }
+    }
+}
+
+impl Iterator for RectIterator {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self
+            .rect
+            .position(self.index % self.rect.width, self.index / self.rect.width)?;
+        self.index += 1;
+        Some(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn offset() {
+        let rect = Rect::new(Position::new((3, -4, 1)).unwrap(), 4, 3).unwrap();
+
+        assert_eq!(Some(rect.origin()), rect.position(0, 0));
+        assert_eq!((5, -6, 1), rect.position(2, 0).unwrap().into());
+        assert_eq!((4, -4, 0), rect.position(0, 1).unwrap().into());
+        assert_eq!((4, -3, -1), rect.position(0, 2).unwrap().into());
+        assert!(rect.position(4, 0).is_none());
+        assert!(rect.position(0, -1).is_none());
+
+        assert_eq!(12, rect.into_iter().count());
+        for position in rect {
+            let (column, row) = rect.offset(position).unwrap();
+            assert_eq!(Some(position), rect.position(column, row));
+        }
+        assert!(!rect.contains(Position::new((2, -4, 2)).unwrap()));
+        assert!(Rect::zero(0, 3).is_err());
+    }
+
+    #[test]
+    fn segments() {
+        let rect = Rect::zero(4, 3).unwrap();
+
+        for direction in Direction::normalized() {
+            let mut covered = HashSet::new();
+
+            for (_distance, segment) in rect.segments(direction) {
+                assert_eq!(direction, segment.direction());
+                for position in segment {
+                    assert!(rect.contains(position));
+                    assert!(covered.insert(position));
+                }
+            }
+
+            assert_eq!(12, covered.len());
+        }
+
+        let rows: Vec<_> = rect.segments(Direction::XY).collect();
+        assert_eq!(3, rows.len());
+        assert!(rows
+            .iter()
+            .all(|(_distance, segment)| segment.length() == 4));
+        assert_eq!(5, rect.segments(Direction::YZ).count());
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+#[cfg(feature = "generate")]
+use rand::seq::IteratorRandom;
+#[cfg(feature = "generate")]
+use rand::Rng;
+
+use super::hexagon::Hexagon;
+use super::masked::MaskedHexagon;
+use super::parallelogram::Parallelogram;
+use super::rect::Rect;
+use super::segment::Segment;
+use super::triangle::Triangle;
+use super::{Direction, Distance, Position};
+
+// A bounded set of positions of the grid, such as a hexagon or a
+// rectangular field, which boards can be laid out on. Regions are plain
+// values, which puzzles on them carry across threads.
+pub trait Region:
+    IntoIterator<Item = Position> + Clone + PartialEq + Debug + Send + Sync + 'static
+{
+    fn contains(&self, position: Position) -> bool;
+
+    // The lines of the region in the given direction, keyed by their
+    // distance from the line through the origin of the region, in
+    // increasing order. Lines broken by gaps in the region are made of
+    // several segments, one for each of their pieces, keyed by the same
+    // distance.
+    fn segments(&self, direction: Direction) -> impl Iterator<Item = (Distance, Segment)>;
+
+    // The segment of the line at the given distance, unless the line is
+    // broken into several of them, none of which is the whole line.
+    fn segment(&self, distance: Distance, direction: Direction) -> Option<Segment> {
+        let mut pieces = self
+            .segments(direction)
+            .filter(|(other, _segment)| *other == distance)
+            .map(|(_distance, segment)| segment);
+        let segment = pieces.next()?;
+        pieces.next().is_none().then_some(segment)
+    }
+
+    // The first line of the region that is broken by gaps into several
+    // segments, if any. Clues count the cells of whole lines, so puzzles
+    // cannot be laid out on regions with broken lines.
+    fn broken_line(&self) -> Option<(Direction, Distance)> {
+        Direction::normalized().into_iter().find_map(|direction| {
+            let mut distances = self
+                .segments(direction)
+                .map(|(distance, _segment)| distance);
+            let mut previous = distances.next()?;
+            distances.find_map(|distance| {
+                let broken = distance == previous;
+                previous = distance;
+                broken.then_some((direction, distance))
+            })
+        })
+    }
+
+    // Picks the given number of distinct positions of the region at
+    // random, or all of them if there are fewer, in no particular order.
+    // The positions are sampled as they are walked, without collecting
+    // all of them first.
+    #[cfg(feature = "generate")]
+    fn sample(&self, rng: &mut impl Rng, count: usize) -> Vec<Position> {
+        self.clone().into_iter().choose_multiple(rng, count)
+    }
+}
+
+// The segments of a region whose lines are not broken by gaps, found
+// by grouping its positions by the distance of their line from the
+// given origin. Each segment starts at the position of its line that is
+// the furthest back along the direction.
+pub(super) fn unbroken_segments(
+    origin: Position,
+    positions: impl IntoIterator<Item = Position>,
+    direction: Direction,
+) -> impl Iterator<Item = (Distance, Segment)> {
+    let neutral = direction.neutral_axis();
+    let along = direction.positive_axis();
+    let unit = direction.rotate().position().axis(neutral);
+
+    let mut segments: BTreeMap<Distance, (Position, Distance)> = BTreeMap::new();
+    for position in positions {
+        let distance = (position - origin).axis(neutral) / unit;
+        let (start, length) = segments.entry(distance).or_insert((position, 0));
+
+        if position.axis(along) < start.axis(along) {
+            *start = position;
+        }
+        *length += 1;
+    }
+
+    segments
+        .into_iter()
+        .map(move |(distance, (start, length))| {
+            (distance, Segment::new(start, length, direction).unwrap())
+        })
+}
+
+impl Region for Hexagon {
+    fn contains(&self, position: Position) -> bool {
+        Hexagon::contains(self, position)
+    }
+
+    fn segments(&self, direction: Direction) -> impl Iterator<Item = (Distance, Segment)> {
+        Hexagon::segments(self, direction)
+    }
+
+    fn segment(&self, distance: Distance, direction: Direction) -> Option<Segment> {
+        Hexagon::segment(self, distance, direction)
+    }
+}
+
+impl Region for Rect {
+    fn contains(&self, position: Position) -> bool {
+        Rect::contains(self, position)
+    }
+
+    fn segments(&self, direction: Direction) -> impl Iterator<Item = (Distance, Segment)> {
+        Rect::segments(self, direction)
+    }
+}
+
+impl Region for MaskedHexagon {
+    fn contains(&self, position: Position) -> bool {
+        MaskedHexagon::contains(self, position)
+    }
+
+    fn segments(&self, direction: Direction) -> impl Iterator<Item = (Distance, Segment)> {
+        MaskedHexagon::segments(self, direction)
+    }
+}
+
+impl Region for Parallelogram {
+    fn contains(&self, position: Position) -> bool {
+        Parallelogram::contains(self, position)
+    }
+
+    fn segments(&self, direction: Direction) -> impl Iterator<Item = (Distance, Segment)> {
+        Parallelogram::segments(self, direction)
+    }
+}
+
+impl Region for Triangle {
+    fn contains(&self, position: Position) -> bool {
+        Triangle::contains(self, position)
+    }
+
+    fn segments(&self, direction: Direction) -> impl Iterator<Item = (Distance, Segment)> {
+        Triangle::segments(self, direction)
+    }
+}
+
+#[cfg(all(test, feature = "generate"))]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let hexagon = Hexagon::new(Position::new((3, -4, 1)).unwrap(), 2).unwrap();
+
+        let sample = hexagon.sample(&mut rng, 5);
+        assert_eq!(5, sample.len());
+        assert_eq!(5, sample.iter().collect::<HashSet<_>>().len());
+        assert!(sample.iter().all(|position| hexagon.contains(*position)));
+        assert_eq!(19, hexagon.sample(&mut rng, 100).len());
+
+        let rect = Rect::zero(4, 3).unwrap();
+        let sample = rect.sample(&mut rng, 12);
+        assert_eq!(
+            rect.into_iter().collect::<HashSet<_>>(),
+            sample.into_iter().collect()
+        );
+    }
+}
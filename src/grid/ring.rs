@@ -1,3 +1,8 @@
+use std::fmt::Display;
+
+#[cfg(feature = "generate")]
+use rand::Rng;
+
 use super::segment::Segment;
 use super::{Direction, Distance, Position};
 
@@ -12,6 +17,18 @@ pub enum RingError {
     InsufficientRadius(Distance),
 }
 
+impl Display for RingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RingError::InsufficientRadius(radius) => {
+                write!(f, "radius {radius} of a ring is not positive")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RingError {}
+
 impl Ring {
     pub fn new(origin: Position, radius: Distance) -> Result<Self, RingError> {
         if radius > 0 {
@@ -40,6 +57,42 @@ impl Ring {
         )
         .unwrap()
     }
+
+    // The position at the given index in the order the ring is walked,
+    // starting from the `XY` corner.
+    pub fn position(&self, index: Distance) -> Option<Position> {
+        if index < 0 || index >= self.radius * 6 {
+            return None;
+        }
+
+        let direction =
+            (0..index / self.radius).fold(Direction::XY, |direction, _| direction.rotate());
+        self.segment(direction).position(index % self.radius)
+    }
+
+    // The index of the given position in the order the ring is walked,
+    // the inverse of `position`.
+    pub fn index(&self, position: Position) -> Option<Distance> {
+        let mut direction = Direction::XY;
+
+        for side in 0..6 {
+            let segment = self.segment(direction);
+            match segment.line().distance_of(position) {
+                Some(step) if (0..self.radius).contains(&step) => {
+                    return Some(side * self.radius + step)
+                }
+                _ => direction = direction.rotate(),
+            }
+        }
+
+        None
+    }
+
+    // A position of the ring picked uniformly at random.
+    #[cfg(feature = "generate")]
+    pub fn random_position(&self, rng: &mut impl Rng) -> Position {
+        self.position(rng.gen_range(0..self.radius * 6)).unwrap()
+    }
 }
 
 impl IntoIterator for Ring {
@@ -90,6 +143,12 @@ impl Iterator for RingIterator {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "generate")]
+    use std::collections::HashSet;
+
+    #[cfg(feature = "generate")]
+    use rand::{rngs::StdRng, SeedableRng};
+
     use super::*;
 
     #[test]
@@ -137,5 +196,21 @@ mod tests {
         assert_eq!((1, -5, 4), iterator.next().unwrap().into());
         assert_eq!((2, -5, 3), iterator.next().unwrap().into());
         assert!(iterator.next().is_none());
+
+        for (index, position) in ring.into_iter().enumerate() {
+            assert_eq!(Some(position), ring.position(index as Distance));
+        }
+        assert!(ring.position(12).is_none());
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn random() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let ring = Ring::new(Position::new((1, -3, 2)).unwrap(), 2).unwrap();
+
+        let positions: HashSet<Position> =
+            (0..200).map(|_| ring.random_position(&mut rng)).collect();
+        assert_eq!(ring.into_iter().collect::<HashSet<_>>(), positions);
     }
 }
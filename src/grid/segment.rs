@@ -1,19 +1,57 @@
+use std::fmt::Display;
+use std::ops::Range;
+
+#[cfg(feature = "generate")]
+use rand::Rng;
+
 use super::line::{Line, LineIterator};
 use super::{Direction, Distance, Position};
 
 // A bounded set of points that stretch from a given start point
 // in a given direction.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SegmentFields"))]
 pub struct Segment {
     line: Line,
     length: Distance,
 }
 
+// Segments are read through their constructor, so that those without
+// any positions are rejected.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SegmentFields {
+    line: Line,
+    length: Distance,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SegmentFields> for Segment {
+    type Error = SegmentError;
+
+    fn try_from(fields: SegmentFields) -> Result<Self, Self::Error> {
+        Segment::new(fields.line.origin(), fields.length, fields.line.direction())
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum SegmentError {
     InsufficientLength(Distance),
 }
 
+impl Display for SegmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SegmentError::InsufficientLength(length) => {
+                write!(f, "length {length} of a segment is not positive")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SegmentError {}
+
 impl Segment {
     pub fn new(
         origin: Position,
@@ -50,6 +88,34 @@ impl Segment {
         }
     }
 
+    // The segments before and from the given distance, if both of them
+    // hold positions.
+    pub fn split_at(&self, distance: Distance) -> Option<(Segment, Segment)> {
+        Some((self.slice(0..distance)?, self.slice(distance..self.length)?))
+    }
+
+    // The segment of the positions at the distances in the given range,
+    // if it is not empty and within this one.
+    pub fn slice(&self, range: Range<Distance>) -> Option<Segment> {
+        if range.start < 0 || range.end > self.length {
+            return None;
+        }
+
+        let start = self.position(range.start)?;
+        Segment::new(start, range.end - range.start, self.direction()).ok()
+    }
+
+    // The same positions, walked from the end to the start.
+    pub fn reversed(&self) -> Segment {
+        Segment::new(self.end(), self.length, self.direction().opposite()).unwrap()
+    }
+
+    // A position of the segment picked uniformly at random.
+    #[cfg(feature = "generate")]
+    pub fn random_position(&self, rng: &mut impl Rng) -> Position {
+        self.line.position(rng.gen_range(0..self.length))
+    }
+
     pub fn direction(&self) -> Direction {
         self.line.direction()
     }
@@ -71,6 +137,12 @@ impl IntoIterator for Segment {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "generate")]
+    use std::collections::HashSet;
+
+    #[cfg(feature = "generate")]
+    use rand::{rngs::StdRng, SeedableRng};
+
     use super::*;
 
     #[test]
@@ -99,4 +171,57 @@ mod tests {
         assert!(segment.position(-1).is_none());
         assert!(segment.position(-3).is_none());
     }
+
+    #[test]
+    fn slice() {
+        let segment = Segment::new(Position::new((1, 2, -3)).unwrap(), 5, Direction::XZ).unwrap();
+
+        let (before, after) = segment.split_at(2).unwrap();
+        assert_eq!(segment.start(), before.start());
+        assert_eq!(2, before.length());
+        assert_eq!(segment.position(2), Some(after.start()));
+        assert_eq!(segment.end(), after.end());
+        assert!(segment.split_at(0).is_none());
+        assert!(segment.split_at(5).is_none());
+
+        let slice = segment.slice(1..4).unwrap();
+        assert_eq!(
+            segment.into_iter().skip(1).take(3).collect::<Vec<_>>(),
+            slice.into_iter().collect::<Vec<_>>()
+        );
+        assert!(segment.slice(2..2).is_none());
+        assert!(segment.slice(3..6).is_none());
+        assert!(segment.slice(-1..2).is_none());
+
+        let reversed = segment.reversed();
+        assert_eq!(segment.end(), reversed.start());
+        assert_eq!(segment.start(), reversed.end());
+        let mut positions: Vec<Position> = segment.into_iter().collect();
+        positions.reverse();
+        assert_eq!(positions, reversed.into_iter().collect::<Vec<_>>());
+        assert_eq!(segment, reversed.reversed());
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn random() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let segment = Segment::new(Position::new((1, 2, -3)).unwrap(), 3, Direction::XZ).unwrap();
+
+        let positions: HashSet<Position> = (0..100)
+            .map(|_| segment.random_position(&mut rng))
+            .collect();
+        assert_eq!(segment.into_iter().collect::<HashSet<_>>(), positions);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let segment = Segment::new(Position::new((1, 2, -3)).unwrap(), 3, Direction::XZ).unwrap();
+        let json = serde_json::to_string(&segment).unwrap();
+        assert_eq!(segment, serde_json::from_str(&json).unwrap());
+
+        let json = json.replace("\"length\":3", "\"length\":-3");
+        assert!(serde_json::from_str::<Segment>(&json).is_err());
+    }
 }
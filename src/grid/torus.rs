@@ -0,0 +1,217 @@
+use std::fmt::Display;
+
+use super::parallelogram::{Parallelogram, ParallelogramIterator};
+use super::segment::Segment;
+use super::{Direction, Distance, Position};
+
+// A field of hexagons shaped as a parallelogram whose opposite sides
+// wrap around onto each other, such that every position of the grid
+// stands for the position of the field it is reduced to. Positions are
+// reduced modulo the lattice spanned by `width` steps in the `XY`
+// direction and `height` steps in the `XZ` direction, which makes every
+// line of the grid a cycle through the field.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Torus {
+    origin: Position,
+    width: Distance,
+    height: Distance,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum TorusError {
+    InsufficientSize(Distance, Distance),
+}
+
+impl Display for TorusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TorusError::InsufficientSize(width, height) => {
+                write!(f, "size {width}×{height} of a torus is not positive")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TorusError {}
+
+impl Torus {
+    pub fn new(origin: Position, width: Distance, height: Distance) -> Result<Self, TorusError> {
+        if width > 0 && height > 0 {
+            Ok(Torus {
+                origin,
+                width,
+                height,
+            })
+        } else {
+            Err(TorusError::InsufficientSize(width, height))
+        }
+    }
+
+    pub fn zero(width: Distance, height: Distance) -> Result<Self, TorusError> {
+        Self::new(Position::zero(), width, height)
+    }
+
+    pub fn origin(&self) -> Position {
+        self.origin
+    }
+
+    pub fn width(&self) -> Distance {
+        self.width
+    }
+
+    pub fn height(&self) -> Distance {
+        self.height
+    }
+
+    // The positions every position of the grid is reduced to.
+    pub fn domain(&self) -> Parallelogram {
+        Parallelogram::new(self.origin, self.width, self.height).unwrap()
+    }
+
+    // The number of positions of the field.
+    pub fn area(&self) -> usize {
+        (self.width * self.height) as usize
+    }
+
+    // The position of the field that the given position is reduced to.
+    pub fn wrap(&self, position: Position) -> Position {
+        let (q, r) = (position - self.origin).to_axial();
+        self.origin + Position::from_axial(q.rem_euclid(self.width), r.rem_euclid(self.height))
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.domain().contains(position)
+    }
+
+    // The six positions adjacent to the given one, wrapped around the
+    // field, in the order of the directions given by `Direction::all`.
+    // On narrow fields, some of them may be the same position.
+    pub fn neighbors(&self, position: Position) -> [Position; 6] {
+        position.neighbors().map(|neighbor| self.wrap(neighbor))
+    }
+
+    // The number of steps in the given direction after which a line
+    // comes back to where it started.
+    pub fn period(&self, direction: Direction) -> Distance {
+        match direction.normalize() {
+            Direction::XY => self.width,
+            Direction::ZX => self.height,
+            _ => self.width / gcd(self.width, self.height) * self.height,
+        }
+    }
+
+    // The cycles of the field in the given direction, as segments of the
+    // grid that go once around them from a position of the field. Their
+    // positions must be wrapped to find the positions of the field they
+    // stand for. Rows, the cycles in the `XY` direction, are keyed by
+    // their `r` axial coordinate, and columns, those in the `ZX`
+    // direction, by their `q` axial coordinate. The cycles in the `YZ`
+    // direction are keyed by the `q` axial coordinate of their position
+    // in the first row, of which only the first few are needed to cover
+    // the field.
+    pub fn segment(&self, distance: Distance, direction: Direction) -> Option<Segment> {
+        let (q, r, count) = match direction.normalize() {
+            Direction::XY => (0, distance, self.height),
+            Direction::ZX => (distance, 0, self.width),
+            _ => (distance, 0, gcd(self.width, self.height)),
+        };
+
+        if (0..count).contains(&distance) {
+            let start = self.origin + Position::from_axial(q, r);
+            Some(Segment::new(start, self.period(direction), direction).unwrap())
+        } else {
+            None
+        }
+    }
+
+    pub fn segments(&self, direction: Direction) -> impl Iterator<Item = (Distance, Segment)> {
+        let torus = *self;
+        (0..).map_while(move |distance| Some((distance, torus.segment(distance, direction)?)))
+    }
+
+    // The positions of the field that the positions of the segment are
+    // reduced to, in order.
+    pub fn positions(&self, segment: Segment) -> impl Iterator<Item = Position> {
+        let torus = *self;
+        segment
+            .into_iter()
+            .map(move |position| torus.wrap(position))
+    }
+}
+
+fn gcd(a: Distance, b: Distance) -> Distance {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl IntoIterator for Torus {
+    type Item = Position;
+
+    type IntoIter = ParallelogramIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.domain().into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn wrap() {
+        let torus = Torus::new(Position::new((3, -4, 1)).unwrap(), 4, 3).unwrap();
+        let corner = torus.origin() + Position::from_axial(3, 0);
+
+        assert_eq!(12, torus.into_iter().count());
+        assert!(Torus::zero(0, 3).is_err());
+        assert!(torus
+            .into_iter()
+            .all(|position| torus.wrap(position) == position));
+        assert_eq!(
+            torus.origin(),
+            torus.wrap(torus.origin() + Position::from_axial(-4, 6))
+        );
+
+        // The last cell of the first row wraps around to the first cells
+        // of the first and of the last row.
+        let neighbors = torus.neighbors(corner);
+        assert!(neighbors.contains(&torus.origin()));
+        assert!(neighbors.contains(&(torus.origin() + Position::from_axial(0, 2))));
+        assert!(neighbors.iter().all(|neighbor| torus.contains(*neighbor)));
+    }
+
+    #[test]
+    fn segments() {
+        let torus = Torus::new(Position::new((3, -4, 1)).unwrap(), 4, 6).unwrap();
+
+        for direction in Direction::normalized() {
+            let mut covered = HashSet::new();
+
+            for (distance, segment) in torus.segments(direction) {
+                assert_eq!(Some(segment), torus.segment(distance, direction));
+                assert!(torus.contains(segment.start()));
+                assert_eq!(
+                    segment.start(),
+                    torus.wrap(segment.line().position(segment.length()))
+                );
+                for position in torus.positions(segment) {
+                    assert!(covered.insert(position));
+                }
+            }
+
+            assert_eq!(torus.area(), covered.len());
+        }
+
+        assert_eq!(6, torus.segments(Direction::XY).count());
+        assert_eq!(4, torus.segments(Direction::ZX).count());
+        assert_eq!(2, torus.segments(Direction::YZ).count());
+        assert_eq!(12, torus.period(Direction::YZ));
+        assert!(torus.segment(6, Direction::XY).is_none());
+    }
+}
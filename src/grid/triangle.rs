@@ -0,0 +1,167 @@
+use std::fmt::Display;
+
+use super::region;
+use super::segment::Segment;
+use super::{Direction, Distance, Position};
+
+// A field of hexagons shaped as a triangle with sides of `size` cells,
+// made of rows laid out in the `XY` direction, each row starting one
+// step further in the `XZ` direction than the one before and one cell
+// shorter. Its origin is the first cell of the first, longest row, and
+// its other corners are reached from the origin by going `size - 1`
+// steps in the `XY` or `XZ` direction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Triangle {
+    origin: Position,
+    size: Distance,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum TriangleError {
+    InsufficientSize(Distance),
+}
+
+impl Display for TriangleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriangleError::InsufficientSize(size) => {
+                write!(f, "size {size} of a triangle is not positive")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TriangleError {}
+
+impl Triangle {
+    pub fn new(origin: Position, size: Distance) -> Result<Self, TriangleError> {
+        if size > 0 {
+            Ok(Triangle { origin, size })
+        } else {
+            Err(TriangleError::InsufficientSize(size))
+        }
+    }
+
+    pub fn zero(size: Distance) -> Result<Self, TriangleError> {
+        Self::new(Position::zero(), size)
+    }
+
+    pub fn origin(&self) -> Position {
+        self.origin
+    }
+
+    pub fn size(&self) -> Distance {
+        self.size
+    }
+
+    // The number of positions of the triangle.
+    pub fn area(&self) -> usize {
+        (self.size * (self.size + 1) / 2) as usize
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        let relative = position - self.origin;
+        relative.y() <= 0 && relative.z() <= 0 && relative.x() < self.size
+    }
+
+    // The segments of the triangle in the given direction, keyed by
+    // their distance from the line through the origin.
+    pub fn segment(&self, distance: Distance, direction: Direction) -> Option<Segment> {
+        self.segments(direction)
+            .find(|(other, _segment)| *other == distance)
+            .map(|(_distance, segment)| segment)
+    }
+
+    pub fn segments(&self, direction: Direction) -> impl Iterator<Item = (Distance, Segment)> {
+        region::unbroken_segments(self.origin, *self, direction)
+    }
+}
+
+impl IntoIterator for Triangle {
+    type Item = Position;
+
+    type IntoIter = TriangleIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        TriangleIterator::new(self)
+    }
+}
+
+// Iterates over the positions of a triangle row by row, from the
+// longest row to the single cell at its tip.
+pub struct TriangleIterator {
+    triangle: Triangle,
+    q: Distance,
+    r: Distance,
+}
+
+impl TriangleIterator {
+    pub fn new(triangle: Triangle) -> Self {
+        TriangleIterator {
+            triangle,
+            q: 0,
+            r: 0,
+        }
+    }
+}
+
+impl Iterator for TriangleIterator {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.q + self.r >= self.triangle.size {
+            self.q = 0;
+            self.r += 1;
+        }
+        if self.r >= self.triangle.size {
+            return None;
+        }
+
+        let position = self.triangle.origin + Position::from_axial(self.q, self.r);
+        self.q += 1;
+        Some(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn segments() {
+        let triangle = Triangle::new(Position::new((3, -4, 1)).unwrap(), 4).unwrap();
+        let positions: HashSet<_> = triangle.into_iter().collect();
+
+        assert_eq!(triangle.area(), positions.len());
+        assert_eq!(10, positions.len());
+        assert!(positions
+            .iter()
+            .all(|position| triangle.contains(*position)));
+        assert!(triangle.contains(triangle.origin() + Direction::XY.position() * 3));
+        assert!(triangle.contains(triangle.origin() + Direction::XZ.position() * 3));
+        assert!(!triangle.contains(triangle.origin() + Direction::XY.position() * 4));
+        assert!(!triangle.contains(triangle.origin() + Direction::YZ.position()));
+        assert!(Triangle::zero(0).is_err());
+
+        // Every side of the triangle is as long as its longest lines.
+        for direction in Direction::normalized() {
+            let mut covered = HashSet::new();
+            let mut lengths = vec![];
+
+            for (distance, segment) in triangle.segments(direction) {
+                assert_eq!(Some(segment), triangle.segment(distance, direction));
+                lengths.push(segment.length());
+                for position in segment {
+                    assert!(positions.contains(&position));
+                    assert!(covered.insert(position));
+                }
+            }
+
+            lengths.sort();
+            assert_eq!(vec![1, 2, 3, 4], lengths);
+            assert_eq!(positions, covered);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use super::edge::Edge;
+use super::{Direction, Position};
+
+// The corner where three mutually adjacent cells meet. Each vertex is
+// owned by one of its three cells, the one it is the `XY` or `XZ`
+// corner of, so that equal vertices compare equal regardless of which
+// cell they were made from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vertex {
+    position: Position,
+    direction: Direction,
+}
+
+impl Vertex {
+    // The vertex between three positions, if they are all adjacent to
+    // each other.
+    pub fn new(a: Position, b: Position, c: Position) -> Option<Self> {
+        let direction = Direction::between(a, b)?;
+
+        [direction, direction.rotate_back()]
+            .into_iter()
+            .map(|direction| Vertex::corner(a, direction))
+            .find(|vertex| vertex.contains(c))
+    }
+
+    // The corner of the given position between the neighbors in the
+    // given direction and in the next clockwise direction.
+    pub fn corner(position: Position, direction: Direction) -> Self {
+        let neighbor = position + direction.position();
+        let next = position + direction.rotate().position();
+
+        // Seen from the other two cells, the same corner lies between
+        // neighbors two steps further around in either direction.
+        [
+            (position, direction),
+            (neighbor, direction.rotate().rotate()),
+            (next, direction.rotate_back().rotate_back()),
+        ]
+        .into_iter()
+        .find(|(_position, direction)| matches!(direction, Direction::XY | Direction::XZ))
+        .map(|(position, direction)| Vertex {
+            position,
+            direction,
+        })
+        .unwrap()
+    }
+
+    // The three positions around the vertex.
+    pub fn positions(&self) -> [Position; 3] {
+        [
+            self.position,
+            self.position + self.direction.position(),
+            self.position + self.direction.rotate().position(),
+        ]
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.positions().contains(&position)
+    }
+
+    // The three edges that meet at the vertex, between each pair of the
+    // positions around it.
+    pub fn edges(&self) -> [Edge; 3] {
+        let [a, b, c] = self.positions();
+        [(a, b), (b, c), (c, a)].map(|(a, b)| Edge::new(a, b).unwrap())
+    }
+
+    // The three vertices at the other ends of the edges that meet at
+    // this one.
+    pub fn neighbors(&self) -> Vec<Vertex> {
+        self.edges()
+            .into_iter()
+            .flat_map(|edge| edge.vertices())
+            .filter(|vertex| vertex != self)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::*;
+    use crate::grid::hexagon::Hexagon;
+
+    #[test]
+    fn new() {
+        let a = Position::new((2, -3, 1)).unwrap();
+
+        for direction in Direction::all() {
+            let vertex = Vertex::corner(a, direction);
+            let [b, c, d] = vertex.positions();
+
+            assert!(vertex.contains(a));
+            assert!(vertex.contains(a + direction.position()));
+            assert!(vertex.contains(a + direction.rotate().position()));
+            assert_eq!(Some(vertex), Vertex::new(b, c, d));
+            assert_eq!(Some(vertex), Vertex::new(d, b, c));
+            assert_eq!(Some(vertex), Vertex::new(c, d, b));
+            assert_eq!(3, vertex.neighbors().len());
+        }
+
+        let b = a + Direction::XY.position();
+        assert!(Vertex::new(a, b, a + Direction::YZ.position()).is_none());
+        assert!(Vertex::new(a, a, b).is_none());
+        assert_eq!(6, a.vertices().into_iter().collect::<HashSet<_>>().len());
+    }
+
+    #[test]
+    fn shared() {
+        // A hexagon of radius r has 6 (r + 1)² vertices, and the corners
+        // of its interior are all shared between three of its positions.
+        let hexagon = Hexagon::zero(3).unwrap();
+        let mut corners: HashMap<Vertex, usize> = HashMap::new();
+        for position in hexagon {
+            for vertex in position.vertices() {
+                *corners.entry(vertex).or_default() += 1;
+            }
+        }
+
+        assert_eq!(6 * 4 * 4, corners.len());
+        assert!(corners.values().all(|count| *count <= 3));
+        for position in hexagon.grow(-1).unwrap() {
+            for vertex in position.vertices() {
+                assert_eq!(3, corners[&vertex]);
+            }
+        }
+    }
+}
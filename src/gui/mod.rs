@@ -0,0 +1,270 @@
+use std::time::Duration;
+
+use eframe::egui::{self, Color32, Pos2, Sense, Shape, Stroke};
+#[cfg(feature = "rayon")]
+use rand::rngs::StdRng;
+#[cfg(not(feature = "rayon"))]
+use rand::thread_rng;
+
+use bestagons::grid::layout::{Layout, Orientation};
+use bestagons::grid::{Direction, Distance, Position};
+use bestagons::i18n;
+use bestagons::puzzle::puzzle::GeneratorFn;
+use bestagons::puzzle::session::Session;
+use bestagons::puzzle::solver::WhatIf;
+use bestagons::puzzle::Cell;
+
+use crate::cli::{self, Args};
+
+// Opens a window to play a puzzle generated with the options of the
+// `generate` command.
+pub fn run(args: &Args) -> Result<(), String> {
+    args.expect(cli::GENERATOR_OPTIONS, cli::GENERATOR_SWITCHES)?;
+    let radius = args.parsed("radius", cli::DEFAULT_RADIUS)?;
+    let refiner = cli::refiner(args)?;
+    let budget = cli::budget(args)?;
+
+    // Puzzles that are not found within the budget fail the command
+    // before any window opens, as they do for `generate`.
+    #[cfg(feature = "rayon")]
+    let puzzle = {
+        let generator: GeneratorFn<StdRng> = cli::generator(args, radius)?;
+        refiner.par_refined_within(&generator, &budget)
+    };
+    #[cfg(not(feature = "rayon"))]
+    let puzzle = {
+        let generator: GeneratorFn<_> = cli::generator(args, radius)?;
+        refiner.refined_within(&mut thread_rng(), &generator, &budget)
+    };
+
+    let puzzle = puzzle.map_err(cli::refine_error)?;
+    let session =
+        Session::from_puzzle(puzzle).ok_or_else(|| i18n::message("gui.unsolvable", &[]))?;
+
+    eframe::run_native(
+        &i18n::message("gui.title", &[]),
+        eframe::NativeOptions::default(),
+        Box::new(|_context| Ok(Box::new(Viewer { session }))),
+    )
+    .map_err(|error| i18n::message("gui.failed", &[("error", &error)]))
+}
+
+// Whether a line satisfies its clue, once all of its cells are colored,
+// counting the cells that are only pencilled in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum LineState {
+    Open,
+    Satisfied,
+    Broken,
+}
+
+struct Viewer {
+    session: Session,
+}
+
+impl Viewer {
+    // The color shown at a position, and whether it is only pencilled in.
+    fn cell(&self, position: Position) -> Option<(Cell, bool)> {
+        match self.session.board().cells().get(&position) {
+            Some(cell) => Some((*cell, false)),
+            None => self
+                .session
+                .tentative()
+                .get(&position)
+                .map(|cell| (*cell, true)),
+        }
+    }
+
+    // Pencils in the next color of the puzzle's palette at a position,
+    // going back to no color after the last one.
+    fn cycle(&mut self, position: Position) {
+        let cells = self.session.puzzle().palette().cells();
+        let next = match self.session.tentative().get(&position) {
+            None => cells.first().copied(),
+            Some(cell) => cells
+                .iter()
+                .skip_while(|other| *other != cell)
+                .nth(1)
+                .copied(),
+        };
+
+        match next {
+            Some(cell) => {
+                self.session.pencil(position, cell);
+            }
+            None => {
+                self.session.erase(position);
+            }
+        }
+    }
+
+    // Places the color pencilled in at a position.
+    fn place(&mut self, position: Position) {
+        if let Some(cell) = self.session.tentative().get(&position).copied() {
+            self.session.place(position, cell);
+        }
+    }
+
+    // The clues of the puzzle and the state of their lines, in the order
+    // of their directions and distances.
+    fn lines(&self) -> Vec<((Direction, Distance), String, LineState)> {
+        let hexagon = self.session.board().hexagon();
+        let colors = self.session.puzzle().palette().colors();
+        let mut lines: Vec<_> = self
+            .session
+            .puzzle()
+            .clue_rules()
+            .iter()
+            .filter_map(|(key, rule)| {
+                let (direction, distance) = *key;
+                let cells: Option<Vec<Cell>> = hexagon
+                    .segment(distance, direction)?
+                    .into_iter()
+                    .map(|position| self.cell(position).map(|(cell, _tentative)| cell))
+                    .collect();
+
+                let state = match cells {
+                    None => LineState::Open,
+                    Some(cells) if rule.is_satisfied(&cells) => LineState::Satisfied,
+                    Some(_) => LineState::Broken,
+                };
+
+                Some((*key, rule.describe(colors), state))
+            })
+            .collect();
+
+        lines.sort_by_key(|((direction, distance), _description, _state)| {
+            let index = Direction::normalized()
+                .iter()
+                .position(|other| other == direction);
+            (index, *distance)
+        });
+        lines
+    }
+
+    fn side_panel(&mut self, ui: &mut egui::Ui, what_if: &WhatIf) {
+        ui.label(i18n::message(
+            "gui.status",
+            &[
+                ("mistakes", &self.session.mistakes()),
+                ("hints", &self.session.hints()),
+                ("seconds", &self.session.elapsed().as_secs()),
+            ],
+        ));
+
+        if self.session.is_finished() {
+            ui.colored_label(Color32::GREEN, i18n::message("gui.finished", &[]));
+        } else if let WhatIf::Contradiction(_position) = what_if {
+            ui.colored_label(Color32::YELLOW, i18n::message("gui.contradiction", &[]));
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button(i18n::message("gui.hint", &[])).clicked() {
+                self.session.hint();
+            }
+            if ui.button(i18n::message("gui.erase", &[])).clicked() {
+                self.session.erase_all();
+            }
+        });
+        ui.label(i18n::message("gui.help", &[]));
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for ((direction, distance), description, state) in self.lines() {
+                let (mark, color) = match state {
+                    LineState::Open => ("…", ui.visuals().text_color()),
+                    LineState::Satisfied => ("✔", Color32::GREEN),
+                    LineState::Broken => ("✘", Color32::RED),
+                };
+                ui.colored_label(
+                    color,
+                    format!("{mark} {direction:?} {distance:>2}  {description}"),
+                );
+            }
+        });
+    }
+
+    fn board(&mut self, ui: &mut egui::Ui, what_if: &WhatIf) {
+        let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::click());
+        let rect = response.rect;
+
+        // Fits the hexagon in the panel, which is 2r + 1 cells wide and
+        // 1.5 (2r + 1) + 0.5 cell sizes high in the pointy-top layout.
+        let hexagon = self.session.board().hexagon();
+        let cells = (2 * hexagon.radius() + 1) as f64;
+        let size = (rect.width() as f64 / (3f64.sqrt() * cells))
+            .min(rect.height() as f64 / (1.5 * cells + 0.5));
+        let center = hexagon.origin();
+        let offset =
+            Layout::new(Orientation::PointyTop, size, (0.0, 0.0)).position_to_pixel(center);
+        let layout = Layout::new(
+            Orientation::PointyTop,
+            size,
+            (
+                rect.center().x as f64 - offset.0,
+                rect.center().y as f64 - offset.1,
+            ),
+        );
+
+        let contradiction = match what_if {
+            WhatIf::Contradiction(position) => Some(*position),
+            WhatIf::Follows(_cells) => None,
+        };
+
+        for position in hexagon {
+            let corners = layout
+                .cell_corners(position)
+                .iter()
+                .map(|(x, y)| Pos2::new(*x as f32, *y as f32))
+                .collect();
+            let fill = match self.cell(position) {
+                Some((cell, tentative)) => color(cell, tentative),
+                None => Color32::from_gray(90),
+            };
+            let stroke = match contradiction == Some(position) {
+                true => Stroke::new(3.0, Color32::YELLOW),
+                false => Stroke::new(1.0, Color32::BLACK),
+            };
+
+            painter.add(Shape::convex_polygon(corners, fill, stroke));
+        }
+
+        if let Some(point) = response.interact_pointer_pos() {
+            let position = layout.pixel_to_position((point.x as f64, point.y as f64));
+            if response.clicked() {
+                self.cycle(position);
+            } else if response.secondary_clicked() {
+                self.place(position);
+            }
+        }
+    }
+}
+
+impl eframe::App for Viewer {
+    fn update(&mut self, context: &egui::Context, _frame: &mut eframe::Frame) {
+        let what_if = self.session.what_if();
+
+        egui::SidePanel::right("lines").show(context, |ui| self.side_panel(ui, &what_if));
+        egui::CentralPanel::default().show(context, |ui| self.board(ui, &what_if));
+
+        // Keeps the elapsed time up to date.
+        context.request_repaint_after(Duration::from_secs(1));
+    }
+}
+
+// Placed cells are painted in their full color, and pencilled cells in
+// a translucent one.
+fn color(cell: Cell, tentative: bool) -> Color32 {
+    let (r, g, b) = match cell {
+        Cell::Red => (214, 69, 65),
+        Cell::Green => (76, 175, 80),
+        Cell::Blue => (66, 112, 214),
+        Cell::Orange => (240, 152, 41),
+        Cell::Purple => (142, 68, 173),
+    };
+
+    match tentative {
+        true => Color32::from_rgba_unmultiplied(r, g, b, 110),
+        false => Color32::from_rgb(r, g, b),
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+#[cfg(feature = "library")]
+use directories::ProjectDirs;
+
+// The messages shown to players, keyed by identifiers such as
+// `error.unknown-command` or `report.clue`, so that they can be
+// translated. English is built in, and other languages are read from
+// catalog files named after the language, such as `de.txt`, in the
+// `locales` directory of the platform's data directory for the game, or
+// in the directory given by the `BESTAGONS_LOCALES` variable.
+//
+// Catalogs are made of lines of `key = message`, where messages may
+// refer to named arguments as `{name}` and hold line breaks as `\n`.
+// Blank lines and lines starting with `#` are left out. Messages missing
+// from a catalog fall back to English.
+const ENGLISH: &str = include_str!("en.txt");
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+    fallback: Option<Box<Catalog>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatalogError {
+    // The line, counting from one, that is not of the form `key = message`.
+    InvalidLine(usize),
+}
+
+impl Display for CatalogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CatalogError::InvalidLine(line) => write!(f, "invalid line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for CatalogError {}
+
+impl Catalog {
+    pub fn parse(text: &str) -> Result<Self, CatalogError> {
+        let mut messages = HashMap::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, message) = line
+                .split_once('=')
+                .ok_or(CatalogError::InvalidLine(index + 1))?;
+            messages.insert(key.trim().to_string(), message.trim().replace("\\n", "\n"));
+        }
+
+        Ok(Catalog {
+            messages,
+            fallback: None,
+        })
+    }
+
+    pub fn english() -> Self {
+        Catalog::parse(ENGLISH).unwrap()
+    }
+
+    // Loads the catalog of the language from the given directory, falling
+    // back to English for its missing messages, or English alone if
+    // there is no catalog for the language.
+    pub fn load(directory: &Path, language: &str) -> Result<Self, CatalogError> {
+        match fs::read_to_string(directory.join(format!("{language}.txt"))) {
+            Ok(text) => Ok(Catalog::parse(&text)?.with_fallback(Catalog::english())),
+            Err(_error) => Ok(Catalog::english()),
+        }
+    }
+
+    pub fn with_fallback(mut self, fallback: Catalog) -> Self {
+        self.fallback = Some(Box::new(fallback));
+        self
+    }
+
+    // The message of the key, with its arguments filled in. Unknown keys
+    // are shown as they are, so that they stand out.
+    pub fn message(&self, key: &str, arguments: &[(&str, &dyn Display)]) -> String {
+        let Some(message) = self.lookup(key) else {
+            return key.to_string();
+        };
+
+        arguments
+            .iter()
+            .fold(message.to_string(), |message, (name, value)| {
+                message.replace(&format!("{{{name}}}"), &value.to_string())
+            })
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.lookup(key).is_some()
+    }
+
+    fn lookup(&self, key: &str) -> Option<&str> {
+        self.messages
+            .get(key)
+            .map(String::as_str)
+            .or_else(|| self.fallback.as_ref()?.lookup(key))
+    }
+}
+
+// The directory catalogs of other languages are read from, which is only
+// looked up in the platform's data directory with the `library` feature.
+pub fn locales() -> Option<PathBuf> {
+    match std::env::var_os("BESTAGONS_LOCALES") {
+        Some(directory) => Some(PathBuf::from(directory)),
+        #[cfg(feature = "library")]
+        None => ProjectDirs::from("", "", "bestagons")
+            .map(|directories| directories.data_dir().join("locales")),
+        #[cfg(not(feature = "library"))]
+        None => None,
+    }
+}
+
+// The language of the given locale, such as `de` for `de_DE.UTF-8`.
+pub fn language(locale: &str) -> &str {
+    locale.split(['_', '.', '@', '-']).next().unwrap_or(locale)
+}
+
+// Sets the catalog used for every message, unless one is set already.
+pub fn set(catalog: Catalog) {
+    let _ = CATALOG.set(catalog);
+}
+
+// The message of the key in the catalog that is set, or in English.
+pub fn message(key: &str, arguments: &[(&str, &dyn Display)]) -> String {
+    CATALOG
+        .get_or_init(Catalog::english)
+        .message(key, arguments)
+}
+
+// The message of the key, or the given label if the catalog has none,
+// for labels that may come from elsewhere, such as custom tier names.
+pub fn label(key: &str, label: &str) -> String {
+    let catalog = CATALOG.get_or_init(Catalog::english);
+
+    if catalog.contains(key) {
+        catalog.message(key, &[])
+    } else {
+        label.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog() {
+        let german =
+            Catalog::parse("# Deutsch\n\nerror.unknown-command = unbekannter Befehl `{command}`\n")
+                .unwrap()
+                .with_fallback(Catalog::english());
+
+        assert_eq!(
+            "unbekannter Befehl `play`",
+            german.message("error.unknown-command", &[("command", &"play")])
+        );
+        assert_eq!(
+            "invalid radius 0",
+            german.message("error.invalid-radius", &[("radius", &0)])
+        );
+        assert_eq!("missing.key", german.message("missing.key", &[]));
+        assert_eq!(
+            Err(CatalogError::InvalidLine(2)),
+            Catalog::parse("a = b\nc\n")
+        );
+
+        assert_eq!("de", language("de_DE.UTF-8"));
+        assert_eq!("en", language("en"));
+
+        let directory = std::env::temp_dir().join(format!("bestagons-i18n-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("fr.txt"), "tier.easy = Facile\n").unwrap();
+
+        let french = Catalog::load(&directory, "fr").unwrap();
+        assert_eq!("Facile", french.message("tier.easy", &[]));
+        assert_eq!("Medium", french.message("tier.medium", &[]));
+        assert_eq!(Catalog::english(), Catalog::load(&directory, "xx").unwrap());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,23 @@
+// The grid, puzzle model, solver and generators behind the `bestagons`
+// command, for front-ends of their own. The most common types are also
+// exported from the root of the crate.
+pub mod grid;
+pub mod i18n;
+pub mod puzzle;
+#[cfg(feature = "server")]
+pub mod server;
+
+pub use grid::hexagon::Hexagon;
+pub use grid::region::Region;
+pub use grid::{Axis, Direction, Distance, GridError, Position};
+pub use puzzle::board::Board;
+pub use puzzle::difficulty::{Difficulty, TierScheme};
+pub use puzzle::puzzle::Puzzle;
+#[cfg(feature = "generate")]
+pub use puzzle::puzzle::{Generator, GeneratorFn};
+#[cfg(feature = "generate")]
+pub use puzzle::refiner::Refiner;
+pub use puzzle::session::Session;
+pub use puzzle::solver::{SolveError, Solver};
+pub use puzzle::validator::Validator;
+pub use puzzle::{Cell, Clue, ClueKind, ClueMix, Hint};
@@ -1,31 +1,146 @@
-mod grid;
-mod puzzle;
+mod cli;
+#[cfg(feature = "gui")]
+mod gui;
 
-use puzzle::board::Board;
+#[cfg(feature = "generate")]
+use std::fs;
+use std::process;
+
+#[cfg(feature = "rayon")]
+use rand::rngs::StdRng;
+#[cfg(all(feature = "generate", not(feature = "rayon")))]
 use rand::thread_rng;
 
-use crate::puzzle::{
+use bestagons::i18n;
+#[cfg(feature = "generate")]
+use bestagons::puzzle::{
+    binary,
+    difficulty::{Difficulty, TierScheme},
     puzzle::GeneratorFn,
-    refiner::Refiner,
-    validator::{
-        MaximumSolvedClues, MaximumSolvedPositions, RequireClueSolving, RequireHintSolving,
-        Validator,
-    },
 };
 
+use crate::cli::Args;
+
 fn main() {
-    let mut rng = thread_rng();
-    // let generator = HeartGenerator;
-    let generator: GeneratorFn<_> = Board::generator(5);
-    let validator: Validator = Validator::new(vec![
-        Box::new(RequireClueSolving(true)),
-        Box::new(RequireHintSolving(true)),
-        Box::new(MaximumSolvedClues(0)),
-        Box::new(MaximumSolvedPositions(0)),
-    ]);
-    let refiner = Refiner::new(validator);
-    let puzzle = refiner.refined(&mut rng, generator);
+    let result: Result<(), String> = Args::parse(std::env::args().skip(1)).and_then(|args| {
+        i18n::set(cli::catalog(&args)?);
+
+        match args.command() {
+            #[cfg(feature = "generate")]
+            None | Some("generate") => generate(&args),
+            #[cfg(feature = "generate")]
+            Some("stats") => cli::stats::run(&args),
+            #[cfg(feature = "generate")]
+            Some("new") => cli::new::run(&args).and_then(|args| generate(&args)),
+            #[cfg(not(feature = "generate"))]
+            None | Some("generate" | "stats" | "new") => {
+                Err(i18n::message("error.generation-unavailable", &[]))
+            }
+            #[cfg(feature = "gui")]
+            Some("gui") => gui::run(&args),
+            #[cfg(feature = "library")]
+            Some("library") => cli::library::run(&args),
+            #[cfg(not(feature = "library"))]
+            Some("library") => Err(i18n::message("error.library-unavailable", &[])),
+            #[cfg(feature = "server")]
+            Some("serve") => cli::serve::run(&args),
+            #[cfg(not(feature = "server"))]
+            Some("serve") => Err(i18n::message("error.server-unavailable", &[])),
+            Some(command) => Err(i18n::message(
+                "error.unknown-command",
+                &[("command", &command)],
+            )),
+        }
+    });
+
+    if let Err(error) = result {
+        eprintln!("{}", i18n::message("error", &[("error", &error)]));
+        process::exit(2);
+    }
+}
+
+#[cfg(feature = "generate")]
+fn generate(args: &Args) -> Result<(), String> {
+    args.expect(
+        &[cli::GENERATOR_OPTIONS, &["output", "format", "count"]].concat(),
+        cli::GENERATOR_SWITCHES,
+    )?;
+    let radius = args.parsed("radius", cli::DEFAULT_RADIUS)?;
+
+    let refiner = cli::refiner(args)?;
+    let budget = cli::budget(args)?;
+
+    // With the `rayon` feature, candidates are refined on all cores at
+    // once, and the first puzzle to validate is kept. Puzzles that are
+    // not found within the budget fail the command.
+    #[cfg(feature = "rayon")]
+    let refined = {
+        let generator: GeneratorFn<StdRng> = cli::generator(args, radius)?;
+        move || {
+            refiner
+                .par_refined_within(&generator, &budget)
+                .map_err(cli::refine_error)
+        }
+    };
+    #[cfg(not(feature = "rayon"))]
+    let mut refined = {
+        let mut rng = thread_rng();
+        let generator: GeneratorFn<_> = cli::generator(args, radius)?;
+        move || {
+            refiner
+                .refined_within(&mut rng, &generator, &budget)
+                .map_err(cli::refine_error)
+        }
+    };
+
+    // Packs of `--count` puzzles are written to the `--output` file, in
+    // the binary format, instead of printing a single puzzle. A `--format`
+    // of `text` writes the puzzles as they are printed instead, for
+    // printing them out, which nothing reads back.
+    if let Some(path) = args.option("output") {
+        let format = args.option("format").unwrap_or("binary");
+        if !["binary", "text"].contains(&format) {
+            return Err(i18n::message(
+                "error.invalid-format",
+                &[("format", &format), ("expected", &"`binary` or `text`")],
+            ));
+        }
+
+        let count: usize = args.parsed("count", 1)?;
+        let puzzles = (0..count)
+            .map(|_| refined())
+            .collect::<Result<Vec<_>, _>>()?;
+        let bytes = match format {
+            "text" => puzzles
+                .iter()
+                .map(|puzzle| puzzle.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+                .into_bytes(),
+            _ => binary::encode_pack(&puzzles)
+                .map_err(|error| i18n::message("error.encode", &[("error", &error)]))?,
+        };
+        fs::write(path, bytes)
+            .map_err(|error| i18n::message("error.write", &[("path", &path), ("error", &error)]))?;
+        println!(
+            "{}",
+            i18n::message("generate.wrote", &[("count", &count), ("path", &path)])
+        );
+        return Ok(());
+    }
+
+    let puzzle = refined()?;
     println!("{puzzle}");
+
+    if let Some(difficulty) = Difficulty::rate(&puzzle) {
+        let scheme = TierScheme::default();
+        println!(
+            "{}",
+            cli::difficulty_message(difficulty, scheme.tier(difficulty))
+        );
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use crate::grid::region::Region;
+use crate::grid::Position;
+
+use super::solver::Solver;
+use super::{Cell, Clue};
+
+// How often each position takes each color among the solutions of a
+// puzzle, for telling which of its unsolved positions are almost forced
+// by its clues. Solutions are enumerated up to a limit, and when a puzzle
+// has more of them, the counts are of the first ones found only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frequencies {
+    solutions: usize,
+    exhaustive: bool,
+    counts: HashMap<Position, Clue>,
+}
+
+impl Frequencies {
+    // Counts the colors of the solutions the solver finds from where it
+    // stands, up to the given number of them.
+    pub fn new<R: Region>(solver: &Solver<R>, limit: usize) -> Self {
+        let mut frequencies = Frequencies {
+            solutions: 0,
+            exhaustive: true,
+            counts: HashMap::new(),
+        };
+
+        for solution in solver.solutions() {
+            if frequencies.solutions == limit {
+                frequencies.exhaustive = false;
+                break;
+            }
+
+            frequencies.solutions += 1;
+            for (position, cell) in solution.cells() {
+                let count = frequencies.counts.entry(*position).or_insert(Clue::zero());
+                *count = *count + Clue::from_cells([*cell].into_iter());
+            }
+        }
+
+        frequencies
+    }
+
+    // The number of solutions counted.
+    pub fn solutions(&self) -> usize {
+        self.solutions
+    }
+
+    // Whether every solution of the puzzle was counted.
+    pub fn is_exhaustive(&self) -> bool {
+        self.exhaustive
+    }
+
+    // The number of counted solutions in which the position takes each
+    // color.
+    pub fn counts(&self, position: Position) -> Clue {
+        self.counts.get(&position).copied().unwrap_or(Clue::zero())
+    }
+
+    // The share of counted solutions in which the position takes the
+    // color, or zero when there are none.
+    pub fn frequency(&self, position: Position, cell: Cell) -> f64 {
+        match self.solutions {
+            0 => 0.0,
+            solutions => self.counts(position).cell(cell) as f64 / solutions as f64,
+        }
+    }
+
+    // The positions that take one of the colors in at least the given
+    // share of the counted solutions, but not in all of them, with that
+    // color and its share, most forced first.
+    pub fn almost_forced(&self, threshold: f64) -> Vec<(Position, Cell, f64)> {
+        let mut almost: Vec<(Position, Cell, f64)> = self
+            .counts
+            .iter()
+            .filter_map(|(position, counts)| {
+                let cell = counts.max_cell()?;
+                let frequency = self.frequency(*position, cell);
+                (frequency >= threshold && frequency < 1.0).then_some((*position, cell, frequency))
+            })
+            .collect();
+
+        almost.sort_by(|a, b| b.2.total_cmp(&a.2));
+        almost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Direction;
+    use crate::puzzle::board::Board;
+    use crate::puzzle::puzzle::Puzzle;
+
+    #[test]
+    fn frequencies() {
+        let board = Board::rings_fixture();
+
+        // Without clues, the two positions left open take every color
+        // equally often, while the rest always keep theirs.
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+        for (key, _clue) in board.clues() {
+            puzzle.remove_clue_rule(key);
+        }
+        let mut solver = Solver::new(puzzle);
+        let open = [Position::zero(), Direction::XY.position()];
+        for (position, cell) in board.cells() {
+            if !open.contains(position) {
+                solver.mut_solution().insert(*position, *cell);
+            }
+        }
+
+        let frequencies = Frequencies::new(&solver, 100);
+        assert_eq!(9, frequencies.solutions());
+        assert!(frequencies.is_exhaustive());
+        assert_eq!(Clue::new(3, 3, 3), frequencies.counts(Position::zero()));
+        assert_eq!(
+            1.0,
+            frequencies.frequency(Direction::YZ.position(), Cell::Green)
+        );
+        assert!(frequencies.almost_forced(0.5).is_empty());
+        assert_eq!(2, frequencies.almost_forced(0.3).len());
+
+        let limited = Frequencies::new(&solver, 4);
+        assert_eq!(4, limited.solutions());
+        assert!(!limited.is_exhaustive());
+    }
+}
@@ -0,0 +1,555 @@
+use std::fmt::Display;
+
+use crate::grid::{Direction, Distance};
+
+use super::board::Board;
+use super::puzzle::Puzzle;
+use super::{Cell, Clue, ClueKind, Count, Palette};
+
+// A compact binary format for puzzles, for embedding them and for
+// shipping them in large packs. A puzzle starts with a header made of
+// the magic bytes and the version of the format, followed by:
+//
+// - the radius of the puzzle, as a varint,
+// - the number of colors of its palette, as a varint,
+// - the givens of every position, in the order of the hexagon's
+//   positions, as the index of their color plus one, or zero for none,
+//   at two bits each for palettes of up to three colors and three bits
+//   otherwise, as many to a byte as fit, starting from the lowest bits,
+// - the clue of every line, one direction at a time, from the lowest
+//   distance to the highest: its count of every color of the palette as
+//   varints, adding up to the length of the line, followed by a varint
+//   telling its kind of clue in the lowest two bits, then the number of
+//   colors it reveals beyond its counts, and the index of each of those
+//   colors, at the same number of bits as the givens.
+//
+// Packs share a single header, followed by the number of puzzles as a
+// varint, and then the puzzles without their headers.
+//
+// Varints hold seven bits per byte, starting from the lowest, with the
+// highest bit set on every byte but the last.
+//
+// The first version of the format had no palette, as its puzzles were
+// all of three colors, and is still read.
+const MAGIC: [u8; 2] = *b"BG";
+const VERSION: u8 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinaryError {
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    UnexpectedEnd,
+    InvalidRadius(u64),
+    InvalidColors(u64),
+    InvalidCell(u64),
+    InvalidCount(u64),
+    InvalidKind(u64),
+    TrailingBytes(usize),
+    // The rule of the line is not a built-in kind of clue.
+    UnsupportedRule(Direction, Distance),
+    // The puzzle has cells or clues of a color outside of its palette.
+    OutsidePalette(Cell),
+}
+
+impl Display for BinaryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BinaryError::InvalidMagic => write!(f, "not a puzzle file"),
+            BinaryError::UnsupportedVersion(version) => {
+                write!(f, "unsupported version {version}")
+            }
+            BinaryError::UnexpectedEnd => write!(f, "unexpected end of data"),
+            BinaryError::InvalidRadius(radius) => write!(f, "invalid radius {radius}"),
+            BinaryError::InvalidColors(colors) => write!(f, "invalid number of colors {colors}"),
+            BinaryError::InvalidCell(bits) => write!(f, "invalid cell {bits}"),
+            BinaryError::InvalidCount(count) => write!(f, "invalid count {count}"),
+            BinaryError::InvalidKind(kind) => write!(f, "invalid kind of clue {kind}"),
+            BinaryError::TrailingBytes(count) => write!(f, "{count} trailing bytes"),
+            BinaryError::UnsupportedRule(direction, distance) => {
+                write!(f, "unsupported rule for line {direction:?} {distance}")
+            }
+            BinaryError::OutsidePalette(cell) => {
+                write!(f, "{} is outside of the palette", cell.name())
+            }
+        }
+    }
+}
+
+impl std::error::Error for BinaryError {}
+
+pub fn encode(puzzle: &Puzzle) -> Result<Vec<u8>, BinaryError> {
+    let mut bytes = header();
+    write_puzzle(&mut bytes, puzzle)?;
+    Ok(bytes)
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Puzzle, BinaryError> {
+    let mut reader = Reader::new(bytes);
+    reader.header()?;
+    let puzzle = reader.puzzle()?;
+    reader.end()?;
+    Ok(puzzle)
+}
+
+pub fn encode_pack(puzzles: &[Puzzle]) -> Result<Vec<u8>, BinaryError> {
+    let mut bytes = header();
+    write_varint(&mut bytes, puzzles.len() as u64);
+
+    for puzzle in puzzles {
+        write_puzzle(&mut bytes, puzzle)?;
+    }
+
+    Ok(bytes)
+}
+
+pub fn decode_pack(bytes: &[u8]) -> Result<Vec<Puzzle>, BinaryError> {
+    let mut reader = Reader::new(bytes);
+    reader.header()?;
+
+    // Each puzzle takes at least a byte, which bounds the allocation.
+    let count = reader.varint()?;
+    let mut puzzles = Vec::with_capacity(count.min(bytes.len() as u64) as usize);
+    for _ in 0..count {
+        puzzles.push(reader.puzzle()?);
+    }
+
+    reader.end()?;
+    Ok(puzzles)
+}
+
+fn header() -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(VERSION);
+    bytes
+}
+
+fn write_puzzle(bytes: &mut Vec<u8>, puzzle: &Puzzle) -> Result<(), BinaryError> {
+    let hexagon = puzzle.board().hexagon();
+    let palette = puzzle.palette();
+    let bits = cell_bits(palette);
+    let index = |cell: Cell| match palette.contains(cell) {
+        true => Ok(cell.index() as u64),
+        false => Err(BinaryError::OutsidePalette(cell)),
+    };
+    write_varint(bytes, hexagon.radius() as u64);
+    write_varint(bytes, palette.colors() as u64);
+
+    let positions: Vec<_> = hexagon.into_iter().collect();
+    for chunk in positions.chunks(8 / bits) {
+        let mut byte = 0;
+        for (offset, position) in chunk.iter().enumerate() {
+            let value = match puzzle.board().cells().get(position) {
+                None => 0,
+                Some(cell) => index(*cell)? as u8 + 1,
+            };
+            byte |= value << (offset * bits);
+        }
+        bytes.push(byte);
+    }
+
+    for (direction, distance) in keys(hexagon.radius()) {
+        let key = (direction, distance);
+        let clue = puzzle.clues().get(&key).copied().unwrap_or(Clue::zero());
+        let (kind, cells) = match puzzle.clue_rules().get(&key) {
+            Some(rule) => rule
+                .kind()
+                .ok_or(BinaryError::UnsupportedRule(direction, distance))?,
+            None => (ClueKind::Full, vec![]),
+        };
+
+        for cell in Cell::all() {
+            match palette.contains(cell) {
+                true => write_varint(bytes, clue.cell(cell) as u64),
+                false if clue.cell(cell) > 0 => return Err(BinaryError::OutsidePalette(cell)),
+                false => {}
+            }
+        }
+
+        let mut word = kind_bits(kind) | (cells.len() as u64) << 2;
+        for (offset, cell) in cells.iter().enumerate() {
+            word |= index(*cell)? << (2 + (offset + 1) * bits);
+        }
+        write_varint(bytes, word);
+    }
+
+    Ok(())
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+// The lines of a puzzle of the given radius, in the order they are
+// written in.
+fn keys(radius: Distance) -> impl Iterator<Item = (Direction, Distance)> {
+    Direction::normalized()
+        .into_iter()
+        .flat_map(move |direction| (-radius..=radius).map(move |distance| (direction, distance)))
+}
+
+// The number of bits of the givens and revealed colors of puzzles of
+// the palette.
+fn cell_bits(palette: Palette) -> usize {
+    match palette.colors() {
+        ..=3 => 2,
+        _ => 3,
+    }
+}
+
+fn kind_bits(kind: ClueKind) -> u64 {
+    match kind {
+        ClueKind::Full => 0,
+        ClueKind::Totals => 1,
+        ClueKind::Ordered => 2,
+        ClueKind::Contiguous => 3,
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    version: u8,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader {
+            bytes,
+            offset: 0,
+            version: VERSION,
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, BinaryError> {
+        let byte = *self
+            .bytes
+            .get(self.offset)
+            .ok_or(BinaryError::UnexpectedEnd)?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<u64, BinaryError> {
+        let mut value = 0;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(BinaryError::InvalidCount(value))
+    }
+
+    fn header(&mut self) -> Result<(), BinaryError> {
+        if [self.byte()?, self.byte()?] != MAGIC {
+            return Err(BinaryError::InvalidMagic);
+        }
+
+        match self.byte()? {
+            version @ (1 | VERSION) => {
+                self.version = version;
+                Ok(())
+            }
+            version => Err(BinaryError::UnsupportedVersion(version)),
+        }
+    }
+
+    fn end(&self) -> Result<(), BinaryError> {
+        match self.bytes.len() - self.offset {
+            0 => Ok(()),
+            trailing => Err(BinaryError::TrailingBytes(trailing)),
+        }
+    }
+
+    fn puzzle(&mut self) -> Result<Puzzle, BinaryError> {
+        let radius = self.varint()?;
+        let palette = match self.version {
+            1 => Palette::default(),
+            _ => {
+                let colors = self.varint()?;
+                usize::try_from(colors)
+                    .ok()
+                    .and_then(|colors| Palette::new(colors).ok())
+                    .ok_or(BinaryError::InvalidColors(colors))?
+            }
+        };
+        let bits = cell_bits(palette);
+        let colors = palette.colors() as u64;
+
+        // The givens take a byte for every few positions, and the clue of
+        // every line at least a byte for each color and another for its
+        // kind, which bounds the size of the board before it is allocated.
+        let size = radius
+            .checked_add(1)
+            .and_then(|next| next.checked_mul(radius))
+            .and_then(|product| product.checked_mul(3))
+            .map(|area| (area + 1).div_ceil(8 / bits as u64) + (6 * radius + 3) * (colors + 1));
+        if size.is_none_or(|size| size > (self.bytes.len() - self.offset) as u64) {
+            return Err(BinaryError::UnexpectedEnd);
+        }
+        let mut board = Distance::try_from(radius)
+            .ok()
+            .and_then(|radius| Board::new(radius).ok())
+            .ok_or(BinaryError::InvalidRadius(radius))?;
+
+        let mask = (1 << bits) - 1;
+        let positions: Vec<_> = board.hexagon().into_iter().collect();
+        for chunk in positions.chunks(8 / bits) {
+            let byte = self.byte()?;
+            for (offset, position) in chunk.iter().enumerate() {
+                match ((byte >> (offset * bits)) & mask) as usize {
+                    0 => {}
+                    value if value <= palette.colors() => {
+                        board.insert(*position, palette.cells()[value - 1])
+                    }
+                    value => return Err(BinaryError::InvalidCell(value as u64)),
+                }
+            }
+        }
+
+        let mut lines = vec![];
+        for key in keys(board.hexagon().radius()) {
+            let mut counts = vec![0; palette.colors()];
+            for count in counts.iter_mut() {
+                let value = self.varint()?;
+                *count = Count::try_from(value).map_err(|_| BinaryError::InvalidCount(value))?;
+            }
+            // The counts of a clue add up to the length of its line, which
+            // the rules of every kind of clue rely on.
+            let (direction, distance) = key;
+            let line = board.hexagon().segment(distance, direction).unwrap();
+            let total: u64 = counts.iter().map(|count| *count as u64).sum();
+            if total != line.length() as u64 {
+                return Err(BinaryError::InvalidCount(total));
+            }
+            let clue = Clue::from_counts(&counts);
+
+            let word = self.varint()?;
+            let kind = ClueKind::all()[(word & 0b11) as usize];
+            let length = ((word >> 2) & mask as u64) as usize;
+            if length > palette.colors() {
+                return Err(BinaryError::InvalidKind(word));
+            }
+            let cells = (1..=length)
+                .map(|offset| match (word >> (2 + offset * bits)) & mask as u64 {
+                    index if index < colors => Ok(palette.cells()[index as usize]),
+                    _ => Err(BinaryError::InvalidKind(word)),
+                })
+                .collect::<Result<Vec<Cell>, BinaryError>>()?;
+
+            if word >> (2 + (length + 1) * bits) != 0 {
+                return Err(BinaryError::InvalidKind(word));
+            }
+
+            lines.push((key, clue, kind, cells));
+        }
+
+        let mut puzzle = Puzzle::new(
+            board,
+            lines.iter().map(|(key, clue, _kind, _cells)| (*key, *clue)),
+        )
+        .with_palette(palette);
+        for (key, clue, kind, cells) in lines {
+            puzzle.set_clue_rule(key, kind.rule_from_parts(clue, cells));
+        }
+
+        Ok(puzzle)
+    }
+}
+
+#[cfg(all(test, feature = "generate"))]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::grid::Position;
+    use crate::puzzle::rules::{ClueRule, FullClue};
+    use crate::puzzle::{ClueMix, Hint};
+
+    fn assert_same(expected: &Puzzle, actual: &Puzzle) {
+        assert_eq!(expected.board().cells(), actual.board().cells());
+        assert_eq!(expected.clues(), actual.clues());
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mix = ClueMix::new(ClueKind::all().map(|kind| (kind, 1.0)).to_vec());
+        let mut puzzles = vec![];
+
+        for radius in 1..=5 {
+            let board = Board::random(&mut rng, radius).unwrap();
+            let mut puzzle = Puzzle::with_clues(board.clone())
+                .with_clue_mix(&mut rng, &mix)
+                .unwrap();
+            puzzle.clear();
+            puzzle
+                .mut_board()
+                .insert(Position::zero(), board.cells()[&Position::zero()]);
+
+            let bytes = encode(&puzzle).unwrap();
+            assert_same(&puzzle, &decode(&bytes).unwrap());
+            puzzles.push(puzzle);
+        }
+
+        // A puzzle of radius 5 takes a byte for every four positions,
+        // and about four bytes for every line.
+        assert!(encode(&puzzles[4]).unwrap().len() < 200);
+
+        // Palettes of more colors take more bits for their givens and
+        // revealed colors, and a count more for every clue.
+        for colors in [2, 4, 5] {
+            let palette = Palette::new(colors).unwrap();
+            let board = Board::random_with_palette(&mut rng, 3, palette).unwrap();
+            let mut puzzle = Puzzle::with_clues(board.clone())
+                .with_palette(palette)
+                .with_clue_mix(&mut rng, &mix)
+                .unwrap();
+            puzzle.clear();
+            for position in board.hexagon().into_iter().step_by(3) {
+                puzzle
+                    .mut_board()
+                    .insert(position, board.cells()[&position]);
+            }
+
+            let decoded = decode(&encode(&puzzle).unwrap()).unwrap();
+            assert_same(&puzzle, &decoded);
+            assert_eq!(palette, decoded.palette());
+            puzzles.push(puzzle);
+        }
+
+        let pack = decode_pack(&encode_pack(&puzzles).unwrap()).unwrap();
+        assert_eq!(puzzles.len(), pack.len());
+        for (expected, actual) in puzzles.iter().zip(&pack) {
+            assert_same(expected, actual);
+        }
+    }
+
+    // Puzzles of the first version have no palette, and are all of three
+    // colors.
+    #[test]
+    fn first_version() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let puzzle = Puzzle::with_clues(Board::random(&mut rng, 2).unwrap());
+        let mut bytes = encode(&puzzle).unwrap();
+        assert_eq!(3, bytes.remove(4));
+        bytes[2] = 1;
+
+        let decoded = decode(&bytes).unwrap();
+        assert_same(&puzzle, &decoded);
+        assert_eq!(Palette::default(), decoded.palette());
+    }
+
+    #[test]
+    fn errors() {
+        let puzzle = Puzzle::with_clues(Board::random(&mut StdRng::seed_from_u64(2), 2).unwrap());
+        let bytes = encode(&puzzle).unwrap();
+
+        assert_eq!(
+            Err(BinaryError::UnexpectedEnd),
+            decode(&bytes[..bytes.len() - 1]).map(|_| ())
+        );
+        assert_eq!(
+            Err(BinaryError::InvalidMagic),
+            decode(b"XX\x01").map(|_| ())
+        );
+        assert_eq!(
+            Err(BinaryError::UnsupportedVersion(9)),
+            decode(b"BG\x09").map(|_| ())
+        );
+
+        // A board is only allocated once there are enough bytes left for
+        // its givens and clues, however large its radius.
+        let mut huge = b"BG\x01".to_vec();
+        write_varint(&mut huge, 1 << 20);
+        huge.extend([0; 64]);
+        assert_eq!(Err(BinaryError::UnexpectedEnd), decode(&huge).map(|_| ()));
+        let mut overflowing = b"BG\x01".to_vec();
+        write_varint(&mut overflowing, u64::MAX);
+        assert_eq!(
+            Err(BinaryError::UnexpectedEnd),
+            decode(&overflowing).map(|_| ())
+        );
+
+        // The counts of a clue must add up to the length of its line. The
+        // clue of the first line follows the radius, the palette and the
+        // two bytes of the givens of a board of radius 1.
+        let single = Puzzle::with_clues(Board::random(&mut StdRng::seed_from_u64(2), 1).unwrap())
+            .with_clue_kind(ClueKind::Contiguous)
+            .unwrap();
+        let mut longer = encode(&single).unwrap();
+        longer[7] += 5;
+        assert_eq!(
+            Err(BinaryError::InvalidCount(
+                single.clues()[&(Direction::XY, -1)].count() as u64 + 5
+            )),
+            decode(&longer).map(|_| ())
+        );
+
+        // Palettes have two to five colors, and givens are of one of them.
+        let mut colors = bytes.clone();
+        colors[4] = 6;
+        assert_eq!(
+            Err(BinaryError::InvalidColors(6)),
+            decode(&colors).map(|_| ())
+        );
+        let mut given = bytes.clone();
+        given[4] = 2;
+        given[5] = 0b11;
+        assert_eq!(Err(BinaryError::InvalidCell(3)), decode(&given).map(|_| ()));
+        assert_eq!(
+            Err(BinaryError::OutsidePalette(Cell::Blue)),
+            encode(&puzzle.clone().with_palette(Palette::new(2).unwrap()))
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            Err(BinaryError::TrailingBytes(1)),
+            decode(&trailing).map(|_| ())
+        );
+
+        // Rules other than the built-in kinds of clue cannot be written.
+        #[derive(Debug)]
+        struct Custom(FullClue);
+
+        impl ClueRule for Custom {
+            fn describe(&self, colors: usize) -> String {
+                self.0.describe(colors)
+            }
+
+            fn project(&self, cells: &[Option<Cell>], hints: &[Hint]) -> Vec<Hint> {
+                self.0.project(cells, hints)
+            }
+
+            fn exhaust(&self, cells: &[Option<Cell>], hints: &[Hint]) -> Vec<Option<Cell>> {
+                self.0.exhaust(cells, hints)
+            }
+
+            fn is_satisfied(&self, cells: &[Cell]) -> bool {
+                self.0.is_satisfied(cells)
+            }
+        }
+
+        let mut custom = puzzle.clone();
+        custom.set_clue_rule(
+            (Direction::YZ, 1),
+            Arc::new(Custom(FullClue::from(Clue::zero()))),
+        );
+        assert_eq!(
+            Err(BinaryError::UnsupportedRule(Direction::YZ, 1)),
+            encode(&custom)
+        );
+    }
+}
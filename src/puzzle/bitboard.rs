@@ -0,0 +1,230 @@
+use crate::grid::hexagon::Hexagon;
+use crate::grid::{Direction, Distance, Position};
+
+use super::board::Board;
+use super::{Cell, Clue, Count, COLORS};
+
+// A set of positions of a hexagon, as the bits of their indices in the
+// order the hexagon is walked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitSet(Vec<u64>);
+
+impl BitSet {
+    // An empty set with room for the given number of indices.
+    pub fn new(len: usize) -> Self {
+        BitSet(vec![0; len.div_ceil(64)])
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.0[index / 64] &= !(1 << (index % 64));
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.0
+            .get(index / 64)
+            .is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
+
+    // The number of indices in the set.
+    pub fn len(&self) -> Count {
+        self.0.iter().map(|word| word.count_ones()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|word| *word == 0)
+    }
+
+    // The number of indices in both this set and the other one.
+    pub fn intersection_len(&self, other: &BitSet) -> Count {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|(a, b)| (a & b).count_ones())
+            .sum()
+    }
+}
+
+// The positions of every line of a hexagon, as sets of their indices,
+// laid out once so that clues can be counted from bit boards of the
+// hexagon without walking its lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentMasks {
+    hexagon: Hexagon,
+    masks: Vec<((Direction, Distance), BitSet)>,
+}
+
+impl SegmentMasks {
+    pub fn new(hexagon: Hexagon) -> Self {
+        let masks = Direction::normalized()
+            .into_iter()
+            .flat_map(|direction| {
+                hexagon.segments(direction).map(move |(distance, segment)| {
+                    let mut mask = BitSet::new(hexagon.area());
+                    for position in segment {
+                        mask.insert(hexagon.index(position).unwrap());
+                    }
+                    ((direction, distance), mask)
+                })
+            })
+            .collect();
+
+        SegmentMasks { hexagon, masks }
+    }
+
+    pub fn hexagon(&self) -> Hexagon {
+        self.hexagon
+    }
+
+    // The masks of the lines in the order of their directions and
+    // distances.
+    pub fn masks(&self) -> &[((Direction, Distance), BitSet)] {
+        &self.masks
+    }
+
+    pub fn mask(&self, key: (Direction, Distance)) -> Option<&BitSet> {
+        self.masks
+            .iter()
+            .find(|(other, _mask)| *other == key)
+            .map(|(_key, mask)| mask)
+    }
+}
+
+// The cells of a board of a hexagon, as a set of the positions of each
+// color, for counting the clues of its lines by popcount.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitBoard {
+    hexagon: Hexagon,
+    colors: [BitSet; COLORS],
+}
+
+impl BitBoard {
+    pub fn new(hexagon: Hexagon) -> Self {
+        BitBoard {
+            hexagon,
+            colors: std::array::from_fn(|_| BitSet::new(hexagon.area())),
+        }
+    }
+
+    // The cells of the board, leaving out any outside of its hexagon.
+    pub fn from_board(board: &Board) -> Self {
+        let mut bits = BitBoard::new(board.hexagon());
+        for (position, cell) in board.cells() {
+            bits.insert(*position, *cell);
+        }
+        bits
+    }
+
+    pub fn hexagon(&self) -> Hexagon {
+        self.hexagon
+    }
+
+    // Places the cell, unless the position is outside of the hexagon.
+    pub fn insert(&mut self, position: Position, cell: Cell) {
+        if let Some(index) = self.hexagon.index(position) {
+            for (other, bits) in Cell::all().into_iter().zip(&mut self.colors) {
+                match other == cell {
+                    true => bits.insert(index),
+                    false => bits.remove(index),
+                }
+            }
+        }
+    }
+
+    pub fn remove(&mut self, position: Position) {
+        if let Some(index) = self.hexagon.index(position) {
+            for bits in &mut self.colors {
+                bits.remove(index);
+            }
+        }
+    }
+
+    pub fn get(&self, position: Position) -> Option<Cell> {
+        let index = self.hexagon.index(position)?;
+        Cell::all()
+            .into_iter()
+            .zip(&self.colors)
+            .find(|(_cell, bits)| bits.contains(index))
+            .map(|(cell, _bits)| cell)
+    }
+
+    // The positions of the cells of the given color.
+    pub fn color(&self, cell: Cell) -> &BitSet {
+        &self.colors[cell.index()]
+    }
+
+    // The number of cells of each color among the masked positions.
+    pub fn clue(&self, mask: &BitSet) -> Clue {
+        Clue::from_counts(
+            &self
+                .colors
+                .each_ref()
+                .map(|bits| bits.intersection_len(mask)),
+        )
+    }
+
+    // The clue of every line of the masks, which must be laid out for
+    // the hexagon of the board.
+    pub fn clues<'a>(
+        &'a self,
+        masks: &'a SegmentMasks,
+    ) -> impl Iterator<Item = ((Direction, Distance), Clue)> + 'a {
+        debug_assert_eq!(self.hexagon, masks.hexagon());
+        masks
+            .masks()
+            .iter()
+            .map(|(key, mask)| (*key, self.clue(mask)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn bit_set() {
+        let mut bits = BitSet::new(100);
+        assert!(bits.is_empty());
+
+        for index in [0, 63, 64, 99] {
+            bits.insert(index);
+        }
+        bits.insert(64);
+        assert_eq!(4, bits.len());
+        assert!(bits.contains(63) && bits.contains(64));
+        assert!(!bits.contains(1) && !bits.contains(1000));
+
+        bits.remove(63);
+        let mut other = BitSet::new(100);
+        other.insert(64);
+        other.insert(63);
+        assert_eq!(1, bits.intersection_len(&other));
+    }
+
+    #[test]
+    fn clues() {
+        let board = Board::rings_fixture();
+
+        let masks = SegmentMasks::new(board.hexagon());
+        let mut bits = BitBoard::from_board(&board);
+        assert_eq!(Some(Cell::Red), bits.get(Position::zero()));
+        assert_eq!(Some(Cell::Green), bits.get(Direction::XY.position()));
+        assert_eq!(6, bits.color(Cell::Green).len());
+
+        let clues: HashMap<_, _> = bits.clues(&masks).collect();
+        assert_eq!(board.clues().collect::<HashMap<_, _>>(), clues);
+
+        bits.insert(Position::zero(), Cell::Blue);
+        bits.remove(Direction::XY.position());
+        assert_eq!(None, bits.get(Direction::XY.position()));
+        assert_eq!(
+            Clue::new(0, 1, 3),
+            bits.clue(masks.mask((Direction::XY, 0)).unwrap())
+        );
+    }
+}
@@ -1,24 +1,117 @@
+#[cfg(feature = "generate")]
+use rand::seq::SliceRandom;
+#[cfg(feature = "generate")]
 use rand::Rng;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
 
-use super::puzzle::GeneratorFn;
-use super::puzzle::Puzzle;
-use super::{Cell, Clue, Hint};
+use super::bitboard::BitBoard;
+use super::mask::HintMask;
+#[cfg(feature = "generate")]
+use super::noise::ValueNoise;
+use super::puzzle::PuzzleError;
+#[cfg(feature = "generate")]
+use super::puzzle::{check_lines, GeneratorFn, Puzzle};
+use super::{Cell, Clue, Count, Hint, Palette};
 use crate::grid::hexagon::{Hexagon, HexagonError};
-use crate::grid::{Direction, Distance, Position};
+use crate::grid::isometry::Isometry;
+use crate::grid::region::Region;
+#[cfg(feature = "generate")]
+use crate::grid::Axis;
+use crate::grid::{self, Direction, Distance, Position};
 
+// The cells placed on a region of the grid. Puzzles are played on
+// boards of a hexagon centered on the origin, but boards of any other
+// region have the same lines and clues.
 #[derive(Debug, Clone)]
-pub struct Board {
-    hexagon: Hexagon,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Board<R = Hexagon> {
+    region: R,
+    #[cfg_attr(feature = "serde", serde(with = "cell_list"))]
     cells: HashMap<Position, Cell>,
 }
 
+// Cells are written as a list of positions and cells, as some formats
+// such as JSON only allow strings as the keys of maps.
+#[cfg(feature = "serde")]
+mod cell_list {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::{Cell, Position};
+
+    pub fn serialize<S: Serializer>(
+        cells: &HashMap<Position, Cell>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(cells.iter())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<Position, Cell>, D::Error> {
+        Ok(Vec::<(Position, Cell)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+// The errors of random boards, and of the generators of puzzles solved
+// by them, which check their arguments when they are built.
+#[derive(Debug, Copy, Clone)]
+pub enum BoardError {
+    Hexagon(HexagonError),
+    InvalidScale(f64),
+    // A hint allows no color, or is outside of the board.
+    InvalidHint(Position),
+    Puzzle(PuzzleError),
+}
+
+impl Display for BoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardError::Hexagon(error) => error.fmt(f),
+            BoardError::Puzzle(error) => error.fmt(f),
+            BoardError::InvalidScale(scale) => {
+                write!(f, "scale {scale} of the noise is not a positive number")
+            }
+            BoardError::InvalidHint(position) => {
+                let (x, y, z) = position.coordinates();
+                write!(
+                    f,
+                    "hint at ({x}, {y}, {z}) is empty or outside of the board"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BoardError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BoardError::Hexagon(error) => Some(error),
+            BoardError::Puzzle(error) => Some(error),
+            BoardError::InvalidScale(_) | BoardError::InvalidHint(_) => None,
+        }
+    }
+}
+
+impl From<HexagonError> for BoardError {
+    fn from(error: HexagonError) -> Self {
+        BoardError::Hexagon(error)
+    }
+}
+
+impl From<PuzzleError> for BoardError {
+    fn from(error: PuzzleError) -> Self {
+        BoardError::Puzzle(error)
+    }
+}
+
 impl Board {
     pub fn new(radius: Distance) -> Result<Self, HexagonError> {
-        Ok(Board {
-            hexagon: Hexagon::zero(radius)?,
-            cells: HashMap::new(),
-        })
+        Ok(Board::with_region(Hexagon::zero(radius)?))
     }
 
     pub fn from_cells(
@@ -32,57 +125,250 @@ impl Board {
 
         Ok(board)
     }
+}
+
+// Boards of any region with every cell picked at random, and the
+// generators of puzzles solved by them.
+#[cfg(feature = "generate")]
+impl<R: Region> Board<R> {
+    pub fn random_in(rng: &mut impl Rng, region: R) -> Self {
+        Self::random_in_palette(rng, region, Palette::default())
+    }
+
+    // A board of the region with every cell picked at random among the
+    // colors of the palette.
+    pub fn random_in_palette(rng: &mut impl Rng, region: R, palette: Palette) -> Self {
+        let mut board = Self::with_region(region.clone());
+
+        for position in region {
+            board.insert(position, palette.random(rng))
+        }
+
+        board
+    }
 
+    // Generates puzzles solved by random boards of the region, for
+    // puzzles on boards of other shapes than hexagons. The region must
+    // not have broken lines.
+    pub fn generator_in<T: Rng>(region: R) -> Result<GeneratorFn<T, R>, BoardError> {
+        check_lines(&region)?;
+        Ok(Box::new(move |rng: &mut T| {
+            Puzzle::with_clues_unchecked(Self::random_in(rng, region.clone()))
+        }))
+    }
+}
+
+// Random boards, and generators of puzzles solved by them. Generators
+// check their arguments when they are built, so that generating never
+// fails.
+#[cfg(feature = "generate")]
+impl Board {
     pub fn random(rng: &mut impl Rng, radius: Distance) -> Result<Self, HexagonError> {
-        let mut board = Self::new(radius)?;
+        Ok(Self::random_in(rng, Hexagon::zero(radius)?))
+    }
 
-        for position in board.hexagon() {
-            board.insert(position, Cell::random(rng))
+    pub fn generator<T: Rng>(radius: Distance) -> Result<GeneratorFn<T>, BoardError> {
+        let hexagon = Hexagon::zero(radius)?;
+        Ok(Box::new(move |rng: &mut T| {
+            Puzzle::with_clues(Board::random_in(rng, hexagon))
+        }))
+    }
+
+    pub fn random_with_palette(
+        rng: &mut impl Rng,
+        radius: Distance,
+        palette: Palette,
+    ) -> Result<Self, HexagonError> {
+        Ok(Self::random_in_palette(
+            rng,
+            Hexagon::zero(radius)?,
+            palette,
+        ))
+    }
+
+    pub fn palette_generator<T: Rng>(
+        radius: Distance,
+        palette: Palette,
+    ) -> Result<GeneratorFn<T>, BoardError> {
+        let hexagon = Hexagon::zero(radius)?;
+        Ok(Box::new(move |rng: &mut T| {
+            Puzzle::with_clues(Board::random_in_palette(rng, hexagon, palette))
+                .with_palette(palette)
+        }))
+    }
+
+    // A random board that is symmetric under reflection across the line
+    // through its center that keeps the given axis' coordinate constant.
+    pub fn random_mirrored(
+        rng: &mut impl Rng,
+        radius: Distance,
+        axis: Axis,
+    ) -> Result<Self, HexagonError> {
+        Ok(Self::mirrored_in(rng, Hexagon::zero(radius)?, axis))
+    }
+
+    fn mirrored_in(rng: &mut impl Rng, hexagon: Hexagon, axis: Axis) -> Self {
+        let mut board = Self::with_region(hexagon);
+        let reflection = Isometry::reflection(hexagon.origin(), axis);
+
+        for position in hexagon {
+            if !board.cells.contains_key(&position) {
+                let cell = Cell::random(rng);
+                board.insert(position, cell);
+                board.insert(reflection.apply(position), cell);
+            }
         }
 
-        Ok(board)
+        board
+    }
+
+    pub fn mirrored_generator<T: Rng>(
+        radius: Distance,
+        axis: Axis,
+    ) -> Result<GeneratorFn<T>, BoardError> {
+        let hexagon = Hexagon::zero(radius)?;
+        Ok(Box::new(move |rng: &mut T| {
+            Puzzle::with_clues(Board::mirrored_in(rng, hexagon, axis))
+        }))
     }
 
-    pub fn generator<T: Rng>(radius: Distance) -> GeneratorFn<T> {
-        Box::new(move |rng: &mut T| Puzzle::with_clues(Board::random(rng, radius).unwrap()))
+    // A random board where colors come in clusters, following smooth
+    // noise that varies over about `scale` cells. Cells are colored by
+    // splitting the range of noise values of the board into three bands
+    // of equal size, so that every color is about as common. The scale
+    // must be a finite, positive number.
+    pub fn random_clustered(
+        rng: &mut impl Rng,
+        radius: Distance,
+        scale: f64,
+    ) -> Result<Self, BoardError> {
+        let hexagon = Hexagon::zero(radius)?;
+        Ok(Self::clustered_in(rng, hexagon, clustering_scale(scale)?))
     }
 
+    fn clustered_in(rng: &mut impl Rng, hexagon: Hexagon, scale: f64) -> Self {
+        let mut board = Self::with_region(hexagon);
+        let noise = ValueNoise::new(rng);
+        let mut colors = Palette::default().cells().to_vec();
+        colors.shuffle(rng);
+
+        let mut samples: Vec<(f64, Position)> = hexagon
+            .into_iter()
+            .map(|position| (noise.sample_position(position, scale), position))
+            .collect();
+        samples.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let count = samples.len();
+        for (index, (_sample, position)) in samples.into_iter().enumerate() {
+            board.insert(position, colors[index * colors.len() / count]);
+        }
+
+        board
+    }
+
+    pub fn clustered_generator<T: Rng>(
+        radius: Distance,
+        scale: f64,
+    ) -> Result<GeneratorFn<T>, BoardError> {
+        let hexagon = Hexagon::zero(radius)?;
+        let scale = clustering_scale(scale)?;
+        Ok(Box::new(move |rng: &mut T| {
+            Puzzle::with_clues(Board::clustered_in(rng, hexagon, scale))
+        }))
+    }
+
+    // A random board with the cells of the hints picked among the colors
+    // they allow. Every hint must allow some color, at a position of the
+    // board.
     pub fn random_from_hints(
         rng: &mut impl Rng,
         radius: Distance,
         hints: impl Iterator<Item = (Position, Hint)>,
-    ) -> Result<Self, HexagonError> {
-        let mut board = Self::new(radius)?;
+    ) -> Result<Self, BoardError> {
+        let hexagon = Hexagon::zero(radius)?;
+        let hints = valid_hints(hexagon, hints)?;
+        Ok(Self::hinted_in(rng, hexagon, &hints))
+    }
+
+    fn hinted_in(rng: &mut impl Rng, hexagon: Hexagon, hints: &[(Position, Hint)]) -> Self {
+        let mut board = Self::with_region(hexagon);
 
         for (position, hint) in hints {
-            board.insert(position, hint.random(rng).unwrap())
+            if let Some(cell) = hint.random(rng) {
+                board.insert(*position, cell)
+            }
         }
 
-        Ok(board)
+        board
     }
 
+    // Generates puzzles of the palette allowing every color of the hints,
+    // even if their solutions happen to leave some of them out.
     pub fn generator_from_hints<T: Rng>(
         radius: Distance,
         hints: impl Iterator<Item = (Position, Hint)>,
-    ) -> GeneratorFn<T> {
-        let hints = hints.collect::<Vec<_>>();
-        Box::new(move |rng: &mut T| {
-            Puzzle::with_clues(
-                Board::random_from_hints(rng, radius, hints.clone().into_iter()).unwrap(),
-            )
+    ) -> Result<GeneratorFn<T>, BoardError> {
+        let hexagon = Hexagon::zero(radius)?;
+        let hints = valid_hints(hexagon, hints)?;
+        let palette = Palette::covering(hints.iter().flat_map(|(_position, hint)| hint.cells()));
+        Ok(Box::new(move |rng: &mut T| {
+            Puzzle::with_clues(Board::hinted_in(rng, hexagon, &hints)).with_palette(palette)
+        }))
+    }
+}
+
+#[cfg(feature = "generate")]
+fn clustering_scale(scale: f64) -> Result<f64, BoardError> {
+    if scale.is_finite() && scale > 0.0 {
+        Ok(scale)
+    } else {
+        Err(BoardError::InvalidScale(scale))
+    }
+}
+
+#[cfg(feature = "generate")]
+fn valid_hints(
+    hexagon: Hexagon,
+    hints: impl Iterator<Item = (Position, Hint)>,
+) -> Result<Vec<(Position, Hint)>, BoardError> {
+    hints
+        .map(|(position, hint)| {
+            if hexagon.contains(position) && !hint.is_empty() {
+                Ok((position, hint))
+            } else {
+                Err(BoardError::InvalidHint(position))
+            }
         })
+        .collect()
+}
+
+impl<R: Region> Board<R> {
+    pub fn with_region(region: R) -> Self {
+        Board {
+            region,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn region(&self) -> &R {
+        &self.region
     }
 
     pub fn is_solved(&self) -> bool {
-        self.hexagon
+        self.region
+            .clone()
             .into_iter()
-            .all(|position| self.cells.get(&position).is_some())
+            .all(|position| self.cells.contains_key(&position))
     }
 
     pub fn insert(&mut self, position: Position, cell: Cell) {
         self.cells.insert(position, cell);
     }
 
+    pub fn remove(&mut self, position: Position) -> Option<Cell> {
+        self.cells.remove(&position)
+    }
+
     pub fn cells(&self) -> &HashMap<Position, Cell> {
         &self.cells
     }
@@ -92,7 +378,7 @@ impl Board {
         distance: Distance,
         direction: Direction,
     ) -> Option<impl Iterator<Item = (Position, Option<Cell>)> + '_> {
-        self.hexagon.segment(distance, direction).map(|segment| {
+        self.region.segment(distance, direction).map(|segment| {
             segment
                 .into_iter()
                 .map(|position| (position, self.cells.get(&position).cloned()))
@@ -108,7 +394,7 @@ impl Board {
             impl Iterator<Item = (Position, Option<Cell>)> + '_,
         ),
     > {
-        self.hexagon.segments(direction).map(|(distance, segment)| {
+        self.region.segments(direction).map(|(distance, segment)| {
             (
                 distance,
                 segment
@@ -141,7 +427,690 @@ impl Board {
         })
     }
 
+    // The groups of adjacent cells of the given color, largest first.
+    pub fn components(&self, cell: Cell) -> Vec<HashSet<Position>> {
+        grid::components(
+            self.cells
+                .iter()
+                .filter(|(_position, other)| **other == cell)
+                .map(|(position, _cell)| *position),
+        )
+    }
+
+    // Whether the cells of the given color form a single group of
+    // adjacent cells, which they do trivially if there are none.
+    pub fn is_connected(&self, cell: Cell) -> bool {
+        self.components(cell).len() <= 1
+    }
+
+    // How often each color occurs among the cells of the board.
+    pub fn color_counts(&self) -> Clue {
+        Clue::from_cells(self.cells.values().copied())
+    }
+
+    // The most adjacent cells of a single color along any of the lines
+    // of the given direction.
+    pub fn longest_run(&self, direction: Direction) -> Count {
+        self.segments(direction)
+            .map(|(_distance, segment)| {
+                let mut longest = 0;
+                let mut run: Option<(Cell, Count)> = None;
+
+                for (_position, cell) in segment {
+                    run = match (run, cell) {
+                        (Some((previous, length)), Some(cell)) if previous == cell => {
+                            Some((cell, length + 1))
+                        }
+                        (_, Some(cell)) => Some((cell, 1)),
+                        (_, None) => None,
+                    };
+                    longest = longest.max(run.map_or(0, |(_cell, length)| length));
+                }
+
+                longest
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Returns a board where, for the given number of iterations, every
+    // cell takes the color of the majority of its neighbors, if more
+    // than half of its neighbors on the board share a color.
+    pub fn smoothed(&self, iterations: usize) -> Self {
+        let mut board = self.clone();
+
+        for _ in 0..iterations {
+            let mut next = board.clone();
+
+            for (position, cell) in &board.cells {
+                let neighbors: Vec<Cell> = position
+                    .neighbors()
+                    .iter()
+                    .filter_map(|neighbor| board.cells.get(neighbor))
+                    .copied()
+                    .collect();
+
+                let majority = Cell::all().into_iter().find(|candidate| {
+                    neighbors.iter().filter(|other| *other == candidate).count() * 2
+                        > neighbors.len()
+                });
+
+                if let Some(majority) = majority {
+                    if majority != *cell {
+                        next.insert(*position, majority);
+                    }
+                }
+            }
+
+            board = next;
+        }
+
+        board
+    }
+}
+
+// Rings, symmetries and scaling of boards of a hexagon.
+impl Board {
     pub fn hexagon(&self) -> Hexagon {
-        self.hexagon
+        self.region
+    }
+
+    // The cells of the board as a bit set of the positions of each color,
+    // for counting clues against precomputed masks of its lines.
+    pub fn bits(&self) -> BitBoard {
+        BitBoard::from_board(self)
+    }
+
+    // Cells placed outside of the board's hexagon are left out of the
+    // stats, as they are out of its lines and rings.
+    pub fn stats(&self) -> BoardStats {
+        let mut colors = Clue::zero();
+        let mut rings = vec![Clue::zero(); self.region.radius() as usize + 1];
+        for (position, cell) in &self.cells {
+            if !self.region.contains(*position) {
+                continue;
+            }
+
+            let clue = Clue::from_cells([*cell].into_iter());
+            let ring = self.region.origin().distance_to(*position) as usize;
+            rings[ring] = rings[ring] + clue;
+            colors = colors + clue;
+        }
+
+        BoardStats {
+            colors,
+            rings,
+            runs: Direction::normalized().map(|direction| self.longest_run(direction)),
+        }
+    }
+
+    // Returns a board with every cell moved by the given isometry. As
+    // hexagons are symmetric under rotation and reflection, the board's
+    // hexagon keeps its radius and is only re-centered.
+    pub fn transformed(&self, isometry: Isometry) -> Self {
+        let region =
+            Hexagon::new(isometry.apply(self.region.origin()), self.region.radius()).unwrap();
+
+        let cells = self
+            .cells
+            .iter()
+            .map(|(position, cell)| (isometry.apply(*position), *cell))
+            .collect();
+
+        Board { region, cells }
+    }
+
+    // Turns the picture drawn by the board back into a hint mask. Each
+    // position allows its own cell's color and up to `looseness` other
+    // colors, preferring those that are more common among its
+    // neighbors, so that the edges of the picture loosen up first. With
+    // no looseness, the mask only allows this board itself.
+    pub fn to_hints(&self, looseness: usize) -> HintMask {
+        let palette = Palette::covering(self.cells.values().copied());
+        let hints = self.cells.iter().map(|(position, cell)| {
+            let mut alternatives: Vec<(usize, Cell)> = palette
+                .cells()
+                .iter()
+                .copied()
+                .filter(|alternative| alternative != cell)
+                .map(|alternative| {
+                    let count = position
+                        .neighbors()
+                        .iter()
+                        .filter(|neighbor| self.cells.get(neighbor) == Some(&alternative))
+                        .count();
+                    (count, alternative)
+                })
+                .collect();
+            alternatives.sort_by_key(|(count, _alternative)| std::cmp::Reverse(*count));
+
+            let cells = alternatives
+                .into_iter()
+                .take(looseness)
+                .map(|(_count, alternative)| alternative);
+            (*position, Hint::from_cells(cells.chain([*cell])))
+        });
+
+        HintMask::new(self.region, hints)
+    }
+
+    // Returns a board on a hexagon scaled up by the given factor, where
+    // each position takes the cell of the nearest position on this
+    // board, so that every cell becomes a small cluster of cells.
+    pub fn upscaled(&self, factor: Distance) -> Result<Self, HexagonError> {
+        if factor < 1 {
+            return Err(HexagonError::InsufficientRadius(
+                self.region.radius() * factor,
+            ));
+        }
+
+        let origin = self.region.origin();
+        let mut board = Board::with_region(Hexagon::new(origin, self.region.radius() * factor)?);
+
+        for position in board.hexagon() {
+            let (x, y, z) = (position - origin).coordinates();
+            let factor = factor as f64;
+            let source =
+                origin + Position::round(x as f64 / factor, y as f64 / factor, z as f64 / factor);
+
+            if let Some(cell) = self.cells.get(&source) {
+                board.insert(position, *cell);
+            }
+        }
+
+        Ok(board)
+    }
+}
+
+// The composition of a board, computed in one go for the validators,
+// objectives and reports that weigh the colors of boards: how often
+// each color occurs, on the whole and on each ring, and the longest run
+// of a single color along the lines of each direction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardStats {
+    colors: Clue,
+    rings: Vec<Clue>,
+    runs: [Count; 3],
+}
+
+impl BoardStats {
+    pub fn color_counts(&self) -> Clue {
+        self.colors
+    }
+
+    // How often each color occurs on every ring, starting from the
+    // center of the board.
+    pub fn rings(&self) -> &[Clue] {
+        &self.rings
+    }
+
+    pub fn ring(&self, radius: Distance) -> Option<Clue> {
+        self.rings.get(usize::try_from(radius).ok()?).copied()
+    }
+
+    // Lines of opposite directions are the same, so they share a run.
+    pub fn longest_run(&self, direction: Direction) -> Count {
+        let direction = direction.normalize();
+        let index = Direction::normalized()
+            .into_iter()
+            .position(|normalized| normalized == direction)
+            .unwrap();
+
+        self.runs[index]
+    }
+
+    // The share of the most common color among the cells, from one over
+    // the number of colors if all of them are equally common to one if
+    // there is a single color, unless there are no cells.
+    pub fn dominance(&self) -> Option<f64> {
+        let max = self.colors.max_cell()?;
+        Some(self.colors.cell(max) as f64 / self.colors.count() as f64)
+    }
+}
+
+// The board most tests are played on: a red center, surrounded by a
+// ring of green cells and a ring of blue ones.
+#[cfg(test)]
+impl Board {
+    pub(crate) fn rings_fixture() -> Self {
+        Self::rings_fixture_in(Hexagon::zero(2).unwrap())
+    }
+}
+
+// The same rings on the positions of another region, which are left
+// empty beyond them.
+#[cfg(test)]
+impl<R: Region> Board<R> {
+    pub(crate) fn rings_fixture_in(region: R) -> Self {
+        let mut board = Self::with_region(region.clone());
+        for position in region {
+            if let Some(cell) =
+                [Cell::Red, Cell::Green, Cell::Blue].get(position.distance() as usize)
+            {
+                board.insert(position, *cell);
+            }
+        }
+
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::masked::MaskedHexagon;
+    #[cfg(feature = "generate")]
+    use crate::grid::parallelogram::Parallelogram;
+    use crate::grid::rect::Rect;
+    use crate::grid::ring::Ring;
+    #[cfg(feature = "generate")]
+    use crate::grid::triangle::Triangle;
+    #[cfg(feature = "generate")]
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn to_hints() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let board = Board::random(&mut rng, 3).unwrap();
+
+        let exact = board.to_hints(0);
+        for (position, cell) in board.cells() {
+            assert_eq!(Hint::from_cells([*cell].into_iter()), exact.get(*position));
+        }
+
+        let loose = board.to_hints(1);
+        for (position, cell) in board.cells() {
+            let hint = loose.get(*position);
+            assert!(hint.cell(*cell));
+            assert_eq!(2, Cell::all().into_iter().filter(|c| hint.cell(*c)).count());
+
+            let neighbors: Vec<Cell> = Direction::all()
+                .into_iter()
+                .filter_map(|direction| board.cells().get(&(*position + direction.position())))
+                .copied()
+                .collect();
+            let count = |cell: Cell| neighbors.iter().filter(|other| **other == cell).count();
+            let alternative = Cell::all()
+                .into_iter()
+                .find(|other| other != cell && hint.cell(*other))
+                .unwrap();
+            assert!(Cell::all()
+                .into_iter()
+                .filter(|other| other != cell)
+                .all(|other| count(other) <= count(alternative)));
+        }
+
+        for position in board.hexagon() {
+            assert_eq!(Palette::default().hint(), board.to_hints(2).get(position));
+        }
+
+        // Boards of more colors keep them, and allow all of them.
+        let board = Board::random_with_palette(&mut rng, 2, Palette::new(5).unwrap()).unwrap();
+        let exact = board.to_hints(0);
+        assert_eq!(5, exact.palette().colors());
+        for (position, cell) in board.cells() {
+            assert_eq!(Hint::from_cells([*cell].into_iter()), exact.get(*position));
+            assert_eq!(
+                Palette::new(5).unwrap().hint(),
+                board.to_hints(4).get(*position)
+            );
+        }
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn transformed() {
+        let mut board = Board::new(2).unwrap();
+        board.insert(Position::zero(), Cell::Red);
+        board.insert(Direction::XY.position(), Cell::Green);
+        board.insert(Direction::YZ.position() * 2, Cell::Blue);
+
+        let center = Position::new((1, 0, -1)).unwrap();
+        let rotated = board.transformed(Isometry::rotation(center, 1));
+
+        assert_eq!(
+            Isometry::rotation(center, 1).apply(Position::zero()),
+            rotated.hexagon().origin()
+        );
+        assert_eq!(2, rotated.hexagon().radius());
+        assert_eq!(3, rotated.cells().len());
+        assert!(rotated
+            .cells()
+            .keys()
+            .all(|position| rotated.hexagon().contains(*position)));
+
+        let reflection = Isometry::reflection(center, Axis::Y);
+        let reflected = board.transformed(reflection).transformed(reflection);
+
+        assert_eq!(board.hexagon(), reflected.hexagon());
+        assert_eq!(board.cells(), reflected.cells());
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn random_mirrored() {
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let board = Board::random_mirrored(&mut rng, 3, axis).unwrap();
+            let reflection = Isometry::reflection(Position::zero(), axis);
+
+            assert!(board.is_solved());
+            assert_eq!(board.cells(), board.transformed(reflection).cells());
+        }
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn invalid_generators() {
+        assert!(Board::generator::<StdRng>(-1).is_err());
+        assert!(Board::palette_generator::<StdRng>(-1, Palette::default()).is_err());
+        assert!(Board::mirrored_generator::<StdRng>(-1, Axis::X).is_err());
+
+        let hints = |position: Position, hint: Hint| [(position, hint)].into_iter();
+        let red = Hint::from_cells([Cell::Red].into_iter());
+        let outside = Direction::XY.position() * 3;
+        let mut rng = StdRng::seed_from_u64(5);
+
+        assert!(Board::generator_from_hints::<StdRng>(2, hints(Position::zero(), red)).is_ok());
+        assert!(Board::generator_from_hints::<StdRng>(-1, hints(Position::zero(), red)).is_err());
+        assert!(Board::generator_from_hints::<StdRng>(2, hints(outside, red)).is_err());
+        assert!(Board::random_from_hints(&mut rng, 2, hints(outside, red)).is_err());
+        assert!(matches!(
+            Board::random_from_hints(&mut rng, 2, hints(Position::zero(), Hint::none())),
+            Err(BoardError::InvalidHint(_))
+        ));
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn random_clustered() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let board = Board::random_clustered(&mut rng, 4, 3.0).unwrap();
+
+        for scale in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(Board::random_clustered(&mut rng, 4, scale).is_err());
+            assert!(Board::clustered_generator::<StdRng>(4, scale).is_err());
+        }
+        assert!(Board::clustered_generator::<StdRng>(-1, 3.0).is_err());
+
+        assert!(board.is_solved());
+        for cell in Palette::default().cells().iter().copied() {
+            let count = board
+                .cells()
+                .values()
+                .filter(|other| **other == cell)
+                .count();
+            assert!((20..=21).contains(&count));
+        }
+
+        // Clustered boards have more neighbors of the same color than
+        // uniformly random ones, which have a third of them.
+        let (mut same, mut total) = (0, 0);
+        for (position, cell) in board.cells() {
+            for direction in Direction::all() {
+                if let Some(other) = board.cells().get(&(*position + direction.position())) {
+                    total += 1;
+                    if other == cell {
+                        same += 1;
+                    }
+                }
+            }
+        }
+        let share = same as f64 / total as f64;
+        assert!(share > 0.5);
+    }
+
+    #[test]
+    fn smoothed() {
+        let mut board = Board::new(2).unwrap();
+        for position in board.hexagon() {
+            board.insert(position, Cell::Blue);
+        }
+        board.insert(Position::zero(), Cell::Red);
+        board.insert(Direction::XY.position() * 2, Cell::Green);
+
+        let smoothed = board.smoothed(1);
+
+        assert_eq!(Some(&Cell::Blue), smoothed.cells().get(&Position::zero()));
+        assert!(smoothed.cells().values().all(|cell| *cell == Cell::Blue));
+        assert_eq!(board.cells(), board.smoothed(0).cells());
+    }
+
+    #[test]
+    fn upscaled() {
+        let origin = Position::new((2, -1, -1)).unwrap();
+        let mut board = Board::with_region(Hexagon::new(origin, 1).unwrap());
+        board.insert(origin, Cell::Red);
+        for direction in Direction::all() {
+            board.insert(origin + direction.position(), Cell::Blue);
+        }
+
+        assert_eq!(board.cells(), board.upscaled(1).unwrap().cells());
+
+        let upscaled = board.upscaled(3).unwrap();
+
+        assert_eq!(origin, upscaled.hexagon().origin());
+        assert_eq!(3, upscaled.hexagon().radius());
+        assert!(upscaled.is_solved());
+        assert_eq!(Some(&Cell::Red), upscaled.cells().get(&origin));
+        assert_eq!(
+            Some(&Cell::Red),
+            upscaled.cells().get(&(origin + Direction::ZX.position()))
+        );
+        for direction in Direction::all() {
+            assert_eq!(
+                Some(&Cell::Blue),
+                upscaled.cells().get(&(origin + direction.position() * 3))
+            );
+        }
+
+        assert!(board.upscaled(0).is_err());
+    }
+
+    #[test]
+    fn stats() {
+        let mut board = Board::new(2).unwrap();
+        board.insert(Position::zero(), Cell::Red);
+        for direction in Direction::all() {
+            board.insert(direction.position(), Cell::Green);
+        }
+        for position in Ring::zero(2).unwrap() {
+            board.insert(position, Cell::Blue);
+        }
+        board.insert(Direction::XY.position() * 2, Cell::Red);
+
+        let stats = board.stats();
+        assert_eq!(Clue::new(2, 6, 11), board.color_counts());
+        assert_eq!(board.color_counts(), stats.color_counts());
+        assert_eq!(
+            vec![Clue::new(1, 0, 0), Clue::new(0, 6, 0), Clue::new(1, 0, 11)],
+            stats.rings()
+        );
+        assert_eq!(None, stats.ring(3));
+
+        // The outer lines are blue, except for the red corner.
+        assert_eq!(3, board.longest_run(Direction::XY));
+        assert_eq!(3, stats.longest_run(Direction::YX));
+        assert_eq!(Some(11.0 / 19.0), stats.dominance());
+
+        // Cells outside of the hexagon are left out.
+        let mut outside = board.clone();
+        outside.insert(Direction::XY.position() * 5, Cell::Green);
+        assert_eq!(stats, outside.stats());
+
+        // Runs are broken by unsolved positions.
+        let mut board = Board::new(1).unwrap();
+        assert_eq!(None, board.stats().dominance());
+        board.insert(Direction::XY.position(), Cell::Red);
+        board.insert(Direction::YX.position(), Cell::Red);
+        assert_eq!(1, board.longest_run(Direction::XY));
+        board.insert(Position::zero(), Cell::Red);
+        assert_eq!(3, board.longest_run(Direction::XY));
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn random_in() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let triangle = Triangle::zero(5).unwrap();
+        let board = Board::random_in(&mut rng, triangle);
+
+        assert!(board.is_solved());
+        assert_eq!(triangle.area(), board.cells().len());
+        assert_eq!(15, board.clues().count());
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn generator_in() {
+        use crate::puzzle::refiner::Refiner;
+        use crate::puzzle::solver::Solver;
+        use crate::puzzle::validator::{RequireUniqueSolution, Validator};
+
+        let mut rng = StdRng::seed_from_u64(4);
+        let parallelogram = Parallelogram::zero(5, 3).unwrap();
+        let triangle = Triangle::zero(6).unwrap();
+
+        let puzzle = Refiner::new(Validator::new(vec![Box::new(RequireUniqueSolution)]))
+            .refined(&mut rng, Board::generator_in(parallelogram).unwrap());
+        assert_eq!(&parallelogram, puzzle.board().region());
+        assert!(Solver::new(puzzle).solve());
+
+        let puzzle = Refiner::new(Validator::new(vec![Box::new(RequireUniqueSolution)]))
+            .refined(&mut rng, Board::generator_in(triangle).unwrap());
+        assert_eq!(&triangle, puzzle.board().region());
+        assert_eq!(18, puzzle.clues().len());
+        assert!(Solver::new(puzzle).solve());
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn random_with_palette() {
+        use crate::puzzle::puzzle::Generator;
+        use crate::puzzle::solver::Solver;
+
+        assert!(Palette::new(1).is_err() && Palette::new(6).is_err());
+        assert_eq!(Palette::default().cells(), &Cell::all()[..3]);
+        assert_eq!(Palette::new(5).unwrap().cells(), &Cell::all());
+
+        // Clues of two-color boards count no blue cells, and the solver
+        // only places the colors of the puzzle's palette, for palettes
+        // of fewer colors as for those of more.
+        let mut rng = StdRng::seed_from_u64(3);
+        for colors in [2, 4, 5] {
+            let palette = Palette::new(colors).unwrap();
+            let board = Board::random_with_palette(&mut rng, 3, palette).unwrap();
+
+            assert!(board.is_solved());
+            assert!(board.cells().values().all(|cell| palette.contains(*cell)));
+            assert!(board
+                .clues()
+                .all(|(_key, clue)| clue.counts()[colors..].iter().all(|count| *count == 0)));
+
+            let mut puzzle = Board::palette_generator(3, palette)
+                .unwrap()
+                .generate(&mut rng);
+            assert_eq!(palette, puzzle.palette());
+            puzzle.clear();
+            let mut solver = Solver::new(puzzle);
+            solver.solve();
+            assert!(solver
+                .solution()
+                .cells()
+                .values()
+                .all(|cell| palette.contains(*cell)));
+            assert!(solver
+                .computed_hints()
+                .values()
+                .all(|hint| hint.cells().all(|cell| palette.contains(cell))));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let board = Board::from_cells(
+            1,
+            [
+                (Position::zero(), Cell::Red),
+                (Direction::XY.position(), Cell::Blue),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+        let json = serde_json::to_string(&board).unwrap();
+        let parsed: Board = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(board.hexagon(), parsed.hexagon());
+        assert_eq!(board.cells(), parsed.cells());
+        assert_eq!(
+            Clue::new(1, 2, 3),
+            serde_json::from_str(&serde_json::to_string(&Clue::new(1, 2, 3)).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn components() {
+        let mut board = Board::new(3).unwrap();
+        for position in board.hexagon() {
+            board.insert(position, Cell::Blue);
+        }
+        for position in Ring::zero(1).unwrap() {
+            board.insert(position, Cell::Red);
+        }
+        board.insert(Direction::XY.position() * 3, Cell::Red);
+
+        // The red ring also cuts the blue center off from the rest.
+        let sizes = |cell| {
+            board
+                .components(cell)
+                .iter()
+                .map(HashSet::len)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![6, 1], sizes(Cell::Red));
+        assert_eq!(vec![29, 1], sizes(Cell::Blue));
+        assert!(!board.is_connected(Cell::Red));
+        assert!(board.is_connected(Cell::Green));
+    }
+
+    #[test]
+    fn masked() {
+        let masked = MaskedHexagon::new(Hexagon::zero(1).unwrap(), [Position::zero()]);
+        let mut board = Board::with_region(masked);
+        for position in Ring::zero(1).unwrap() {
+            board.insert(position, Cell::Green);
+        }
+
+        assert!(board.is_solved());
+        assert_eq!(12, board.clues().count());
+        assert!(board
+            .clues()
+            .all(|((_direction, _distance), clue)| clue == Clue::new(0, 1, 0)
+                || clue == Clue::new(0, 2, 0)));
+    }
+
+    #[test]
+    fn region() {
+        let rect = Rect::zero(4, 3).unwrap();
+        let mut board = Board::with_region(rect);
+        for position in rect {
+            let (column, _row) = rect.offset(position).unwrap();
+            let cell = if column < 2 { Cell::Red } else { Cell::Blue };
+            board.insert(position, cell);
+        }
+
+        assert!(board.is_solved());
+        assert_eq!(&rect, board.region());
+        assert_eq!(Clue::new(6, 0, 6), board.color_counts());
+        assert_eq!(2, board.longest_run(Direction::XY));
+        assert_eq!(5, board.segments(Direction::YZ).count());
+        for ((direction, distance), clue) in board.clues() {
+            let length = board.segment(distance, direction).unwrap().count() as Count;
+            assert_eq!(length, clue.count());
+        }
     }
 }
@@ -0,0 +1,250 @@
+use std::fmt::Display;
+
+use crate::grid::{Direction, Distance, Position};
+use crate::i18n;
+
+use super::puzzle::Puzzle;
+use super::solver::Solver;
+use super::Cell;
+
+// A clue or a given cell of a puzzle, as taking part in a conflict.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Constraint {
+    Clue(Direction, Distance),
+    Given(Position, Cell),
+}
+
+// A set of clues and givens that cannot all hold at once, from which no
+// constraint can be left out without the rest becoming consistent, as
+// far as the diagnoser can tell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict(Vec<Constraint>);
+
+impl Conflict {
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.0
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Constraint::Clue(direction, distance) => {
+                let direction = match direction {
+                    Direction::XY => "XY",
+                    Direction::YZ => "YZ",
+                    Direction::ZX => "ZX",
+                    _ => unreachable!(),
+                };
+                f.write_str(&i18n::message(
+                    "report.clue",
+                    &[("direction", &direction), ("distance", distance)],
+                ))
+            }
+            Constraint::Given(position, cell) => f.write_str(&i18n::message(
+                "report.given",
+                &[
+                    ("cell", &cell_name(*cell)),
+                    ("x", &position.x()),
+                    ("y", &position.y()),
+                    ("z", &position.z()),
+                ],
+            )),
+        }
+    }
+}
+
+// The name of the color, as reports show it.
+pub(super) fn cell_name(cell: Cell) -> String {
+    i18n::message(&format!("cell.{}", cell.name()), &[])
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for constraint in &self.0 {
+            writeln!(f, "{constraint}")?;
+        }
+
+        Ok(())
+    }
+}
+
+// Finds out why a puzzle cannot be solved. A puzzle is inconsistent when
+// solving it leads to a contradiction, either directly or, up to the
+// given depth, after trying every color of some position in turn.
+#[derive(Debug, Copy, Clone)]
+pub struct Diagnoser {
+    depth: usize,
+}
+
+impl Default for Diagnoser {
+    fn default() -> Self {
+        Diagnoser { depth: 1 }
+    }
+}
+
+impl Diagnoser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    // Whether no contradiction is found in the puzzle.
+    pub fn is_consistent(&self, puzzle: &Puzzle) -> bool {
+        !is_refuted(&Solver::new(puzzle.clone()), self.depth)
+    }
+
+    // Returns a minimal conflicting set of clues and givens of the
+    // puzzle, or `None` if no contradiction is found. Constraints are
+    // left out one at a time, keeping out those without which the rest
+    // are still refuted.
+    pub fn diagnose(&self, puzzle: &Puzzle) -> Option<Conflict> {
+        let depth =
+            (0..=self.depth).find(|depth| is_refuted(&Solver::new(puzzle.clone()), *depth))?;
+
+        let mut puzzle = puzzle.clone();
+        let mut conflict = vec![];
+
+        for constraint in constraints(&puzzle) {
+            let mut without = puzzle.clone();
+            match constraint {
+                Constraint::Clue(direction, distance) => {
+                    without.remove_clue_rule((direction, distance));
+                }
+                Constraint::Given(position, _cell) => {
+                    without.mut_board().remove(position);
+                }
+            }
+
+            if is_refuted(&Solver::new(without.clone()), depth) {
+                puzzle = without;
+            } else {
+                conflict.push(constraint);
+            }
+        }
+
+        Some(Conflict(conflict))
+    }
+}
+
+// The clues and givens of the puzzle, in a stable order.
+fn constraints(puzzle: &Puzzle) -> Vec<Constraint> {
+    let hexagon = puzzle.board().hexagon();
+    let radius = hexagon.radius();
+
+    let clues = Direction::normalized().into_iter().flat_map(|direction| {
+        (-radius..=radius)
+            .filter(move |distance| puzzle.clue_rules().contains_key(&(direction, *distance)))
+            .map(move |distance| Constraint::Clue(direction, distance))
+    });
+
+    let givens = hexagon.into_iter().filter_map(|position| {
+        puzzle
+            .board()
+            .cells()
+            .get(&position)
+            .map(|cell| Constraint::Given(position, *cell))
+    });
+
+    clues.chain(givens).collect()
+}
+
+// Whether solving leads to a contradiction, trying every color of each
+// unsolved position in turn for as many levels deep as given.
+fn is_refuted(solver: &Solver, depth: usize) -> bool {
+    let mut solver = solver.clone();
+
+    loop {
+        if solver.contradiction().is_some() {
+            return true;
+        }
+
+        if solver.step().is_none() {
+            break;
+        }
+    }
+
+    if depth == 0 {
+        return false;
+    }
+
+    let hints = solver.computed_hints();
+    solver
+        .puzzle()
+        .board()
+        .hexagon()
+        .into_iter()
+        .filter(|position| !solver.solution().cells().contains_key(position))
+        .any(|position| {
+            Cell::all()
+                .into_iter()
+                .filter(|cell| hints[&position].cell(*cell))
+                .all(|cell| {
+                    let mut guess = solver.clone();
+                    guess.insert(position, cell);
+                    is_refuted(&guess, depth - 1)
+                })
+        })
+}
+
+#[cfg(all(test, feature = "generate"))]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::puzzle::board::Board;
+    use crate::puzzle::rules::FullClue;
+    use std::sync::Arc;
+
+    #[test]
+    fn diagnose() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let board = Board::random(&mut rng, 2).unwrap();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+
+        assert_eq!(None, Diagnoser::new().diagnose(&puzzle));
+
+        // A line claiming another color for one of its cells conflicts
+        // with the lines crossing it.
+        let key = (Direction::XY, 0);
+        let cells: Vec<Cell> = board
+            .segment(0, Direction::XY)
+            .unwrap()
+            .filter_map(|(_position, cell)| cell)
+            .collect();
+        let mut wrong = cells.clone();
+        wrong[0] = Cell::all()
+            .into_iter()
+            .find(|cell| *cell != cells[0])
+            .unwrap();
+        puzzle.set_clue_rule(key, Arc::new(FullClue::new(&wrong)));
+
+        let conflict = Diagnoser::new().diagnose(&puzzle).unwrap();
+        assert!(conflict
+            .constraints()
+            .contains(&Constraint::Clue(Direction::XY, 0)));
+
+        // Every constraint of the conflict is needed for it.
+        for constraint in conflict.constraints() {
+            let mut without = puzzle.clone();
+            for key in puzzle.clue_rules().keys() {
+                if !conflict
+                    .constraints()
+                    .contains(&Constraint::Clue(key.0, key.1))
+                {
+                    without.remove_clue_rule(*key);
+                }
+            }
+            if let Constraint::Clue(direction, distance) = constraint {
+                without.remove_clue_rule((*direction, *distance));
+            }
+            assert!(!is_refuted(&Solver::new(without), 1));
+        }
+    }
+}
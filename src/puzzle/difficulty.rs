@@ -0,0 +1,147 @@
+use crate::grid::region::Region;
+
+use super::puzzle::Puzzle;
+use super::solver::Solver;
+
+// A numeric rating of how hard a puzzle is to solve logically.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Difficulty(f64);
+
+impl Difficulty {
+    pub fn new(value: f64) -> Self {
+        Difficulty(value)
+    }
+
+    // Rates a puzzle by the cost of the passes the solver needs to solve
+    // it, preferring cheaper techniques over costlier ones. Returns
+    // `None` if the puzzle cannot be solved logically.
+    pub fn rate<R: Region>(puzzle: &Puzzle<R>) -> Option<Self> {
+        let mut solver = Solver::new(puzzle.clone());
+        let mut value = 0.0;
+
+        while !solver.solution().is_solved() {
+            value += solver.step()?.cost();
+        }
+
+        Some(Difficulty(value))
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+// A named band of difficulty, starting at the given minimum rating.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tier {
+    name: String,
+    stars: u8,
+    minimum: f64,
+}
+
+impl Tier {
+    pub fn new(name: impl Into<String>, stars: u8, minimum: f64) -> Self {
+        Tier {
+            name: name.into(),
+            stars,
+            minimum,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn stars(&self) -> u8 {
+        self.stars
+    }
+
+    pub fn minimum(&self) -> f64 {
+        self.minimum
+    }
+}
+
+// Maps difficulty ratings to named tiers, so that every consumer of
+// ratings buckets puzzles in the same way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierScheme(Vec<Tier>);
+
+impl TierScheme {
+    pub fn new(mut tiers: Vec<Tier>) -> Self {
+        tiers.sort_by(|a, b| a.minimum.total_cmp(&b.minimum));
+        TierScheme(tiers)
+    }
+
+    pub fn tiers(&self) -> &[Tier] {
+        &self.0
+    }
+
+    // Returns the highest tier whose minimum the difficulty reaches,
+    // or `None` if it is below the minimum of every tier.
+    pub fn tier(&self, difficulty: Difficulty) -> Option<&Tier> {
+        self.0
+            .iter()
+            .rev()
+            .find(|tier| difficulty.value() >= tier.minimum)
+    }
+
+    pub fn tier_by_name(&self, name: &str) -> Option<&Tier> {
+        self.0
+            .iter()
+            .find(|tier| tier.name.eq_ignore_ascii_case(name))
+    }
+
+    // The range of difficulties in the tier, from its minimum up to the
+    // minimum of the next tier, excluded.
+    pub fn bounds(&self, tier: &Tier) -> (f64, f64) {
+        let maximum = self
+            .0
+            .iter()
+            .map(|other| other.minimum)
+            .find(|minimum| *minimum > tier.minimum)
+            .unwrap_or(f64::INFINITY);
+
+        (tier.minimum, maximum)
+    }
+}
+
+impl Default for TierScheme {
+    fn default() -> Self {
+        TierScheme::new(vec![
+            Tier::new("Easy", 1, 0.0),
+            Tier::new("Medium", 2, 8.0),
+            Tier::new("Hard", 3, 16.0),
+            Tier::new("Expert", 4, 28.0),
+            Tier::new("Fiendish", 5, 45.0),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tier() {
+        let scheme = TierScheme::new(vec![
+            Tier::new("Hard", 3, 10.0),
+            Tier::new("Easy", 1, 0.0),
+            Tier::new("Medium", 2, 5.0),
+        ]);
+
+        assert_eq!("Easy", scheme.tier(Difficulty::new(0.0)).unwrap().name());
+        assert_eq!("Easy", scheme.tier(Difficulty::new(4.5)).unwrap().name());
+        assert_eq!("Medium", scheme.tier(Difficulty::new(5.0)).unwrap().name());
+        assert_eq!(3, scheme.tier(Difficulty::new(50.0)).unwrap().stars());
+        assert!(scheme.tier(Difficulty::new(-1.0)).is_none());
+        assert_eq!(2, scheme.tier_by_name("medium").unwrap().stars());
+        assert_eq!(
+            (5.0, 10.0),
+            scheme.bounds(scheme.tier_by_name("medium").unwrap())
+        );
+        assert_eq!(
+            (10.0, f64::INFINITY),
+            scheme.bounds(scheme.tier_by_name("hard").unwrap())
+        );
+    }
+}
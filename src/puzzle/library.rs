@@ -0,0 +1,381 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+
+use crate::grid::Distance;
+
+use super::binary::{self, BinaryError};
+use super::difficulty::{Difficulty, Tier, TierScheme};
+use super::puzzle::Puzzle;
+
+const PUZZLES: &str = "puzzles.bg";
+const INDEX: &str = "index";
+
+// A puzzle of the library, with its rating, the tier of the rating in
+// the default scheme and whether it was played.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    puzzle: Puzzle,
+    difficulty: Option<Difficulty>,
+    tier: Option<Tier>,
+    played: bool,
+}
+
+impl Entry {
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        self.difficulty
+    }
+
+    pub fn tier(&self) -> Option<&Tier> {
+        self.tier.as_ref()
+    }
+
+    pub fn is_played(&self) -> bool {
+        self.played
+    }
+}
+
+#[derive(Debug)]
+pub enum LibraryError {
+    Io(io::Error),
+    Binary(BinaryError),
+    // The line of the index that could not be read.
+    InvalidIndex(usize),
+    NoDataDirectory,
+    UnknownPuzzle(usize),
+}
+
+impl Display for LibraryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LibraryError::Io(error) => error.fmt(f),
+            LibraryError::Binary(error) => error.fmt(f),
+            LibraryError::InvalidIndex(line) => write!(f, "invalid line {line} in the index"),
+            LibraryError::NoDataDirectory => write!(f, "no data directory"),
+            LibraryError::UnknownPuzzle(id) => write!(f, "unknown puzzle {id}"),
+        }
+    }
+}
+
+impl std::error::Error for LibraryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LibraryError::Io(error) => Some(error),
+            LibraryError::Binary(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LibraryError {
+    fn from(error: io::Error) -> Self {
+        LibraryError::Io(error)
+    }
+}
+
+impl From<BinaryError> for LibraryError {
+    fn from(error: BinaryError) -> Self {
+        LibraryError::Binary(error)
+    }
+}
+
+// Which puzzles to pick from the library. Puzzles that cannot be rated
+// only match filters without a difficulty range or tier.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    radius: Option<Distance>,
+    difficulty: Option<(f64, f64)>,
+    tier: Option<Tier>,
+}
+
+impl Filter {
+    pub fn with_radius(mut self, radius: Distance) -> Self {
+        self.radius = Some(radius);
+        self
+    }
+
+    pub fn with_difficulty(mut self, minimum: f64, maximum: f64) -> Self {
+        self.difficulty = Some((minimum, maximum));
+        self
+    }
+
+    // Only matches puzzles of the tier, one of those of the default
+    // scheme that entries are kept in.
+    pub fn with_tier(mut self, tier: Tier) -> Self {
+        self.tier = Some(tier);
+        self
+    }
+
+    pub fn matches(&self, entry: &Entry) -> bool {
+        let radius = self
+            .radius
+            .is_none_or(|radius| entry.puzzle.board().hexagon().radius() == radius);
+        let difficulty = self.difficulty.is_none_or(|(minimum, maximum)| {
+            entry
+                .difficulty
+                .is_some_and(|difficulty| (minimum..=maximum).contains(&difficulty.value()))
+        });
+
+        let tier = self
+            .tier
+            .as_ref()
+            .is_none_or(|tier| entry.tier.as_ref() == Some(tier));
+
+        radius && difficulty && tier
+    }
+}
+
+// A local store of puzzles, kept in a directory as a binary pack of the
+// puzzles, next to an index holding the rating of each puzzle, its tier
+// and whether it was played, one line per puzzle. Puzzles are identified by
+// their position in the library, starting from zero.
+#[derive(Debug, Clone)]
+pub struct Library {
+    root: PathBuf,
+    entries: Vec<Entry>,
+}
+
+impl Library {
+    // Opens the library in the platform's data directory for the game.
+    pub fn open_default() -> Result<Self, LibraryError> {
+        let directories =
+            ProjectDirs::from("", "", "bestagons").ok_or(LibraryError::NoDataDirectory)?;
+        Self::open(directories.data_dir())
+    }
+
+    // Opens the library in the given directory, which is created when
+    // the library is first saved.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, LibraryError> {
+        let root = root.as_ref().to_path_buf();
+
+        let puzzles = match fs::read(root.join(PUZZLES)) {
+            Ok(bytes) => binary::decode_pack(&bytes)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => vec![],
+            Err(error) => return Err(error.into()),
+        };
+
+        let index = match fs::read_to_string(root.join(INDEX)) {
+            Ok(index) => index,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error.into()),
+        };
+        let lines: Vec<&str> = index.lines().collect();
+
+        if lines.len() != puzzles.len() {
+            return Err(LibraryError::InvalidIndex(lines.len().min(puzzles.len())));
+        }
+
+        let scheme = TierScheme::default();
+        let entries = puzzles
+            .into_iter()
+            .zip(lines)
+            .enumerate()
+            .map(|(line, (puzzle, index))| {
+                let fields: Vec<&str> = index.split(' ').collect();
+                let [difficulty, tier, played] = fields[..] else {
+                    return Err(LibraryError::InvalidIndex(line));
+                };
+
+                Ok(Entry {
+                    puzzle,
+                    difficulty: match difficulty {
+                        "-" => None,
+                        value => Some(Difficulty::new(
+                            value
+                                .parse()
+                                .map_err(|_| LibraryError::InvalidIndex(line))?,
+                        )),
+                    },
+                    tier: match tier {
+                        "-" => None,
+                        name => Some(
+                            scheme
+                                .tier_by_name(name)
+                                .ok_or(LibraryError::InvalidIndex(line))?
+                                .clone(),
+                        ),
+                    },
+                    played: match played {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(LibraryError::InvalidIndex(line)),
+                    },
+                })
+            })
+            .collect::<Result<_, LibraryError>>()?;
+
+        Ok(Library { root, entries })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    // Adds the puzzles to the library, rating each of them, and returns
+    // the number of puzzles added. Puzzles the library already holds are
+    // skipped, as are repeated ones, going by their binary encoding.
+    pub fn import(&mut self, puzzles: impl IntoIterator<Item = Puzzle>) -> usize {
+        let first = self.entries.len();
+        let scheme = TierScheme::default();
+        let mut encoded: HashSet<Vec<u8>> = self
+            .entries
+            .iter()
+            .filter_map(|entry| binary::encode(&entry.puzzle).ok())
+            .collect();
+
+        for puzzle in puzzles {
+            if let Ok(bytes) = binary::encode(&puzzle) {
+                if !encoded.insert(bytes) {
+                    continue;
+                }
+            }
+
+            let difficulty = Difficulty::rate(&puzzle);
+            self.entries.push(Entry {
+                tier: difficulty.and_then(|difficulty| scheme.tier(difficulty).cloned()),
+                difficulty,
+                puzzle,
+                played: false,
+            });
+        }
+
+        self.entries.len() - first
+    }
+
+    // Adds the puzzles of a binary pack to the library, returning the
+    // number of puzzles added.
+    pub fn import_pack(&mut self, bytes: &[u8]) -> Result<usize, LibraryError> {
+        let puzzles = binary::decode_pack(bytes)?;
+        Ok(self.import(puzzles))
+    }
+
+    pub fn mark_played(&mut self, id: usize) -> Result<(), LibraryError> {
+        self.entries
+            .get_mut(id)
+            .ok_or(LibraryError::UnknownPuzzle(id))?
+            .played = true;
+        Ok(())
+    }
+
+    // The first puzzle not played yet that matches the filter.
+    pub fn next(&self, filter: &Filter) -> Option<(usize, &Entry)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .find(|(_id, entry)| !entry.played && filter.matches(entry))
+    }
+
+    pub fn save(&self) -> Result<(), LibraryError> {
+        fs::create_dir_all(&self.root)?;
+
+        let puzzles: Vec<Puzzle> = self
+            .entries
+            .iter()
+            .map(|entry| entry.puzzle.clone())
+            .collect();
+        fs::write(self.root.join(PUZZLES), binary::encode_pack(&puzzles)?)?;
+
+        let index: String = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let difficulty = entry
+                    .difficulty
+                    .map_or("-".to_string(), |difficulty| difficulty.value().to_string());
+                let tier = entry
+                    .tier
+                    .as_ref()
+                    .map_or("-".to_string(), |tier| tier.name().to_lowercase());
+                format!("{difficulty} {tier} {}\n", if entry.played { 1 } else { 0 })
+            })
+            .collect();
+        fs::write(self.root.join(INDEX), index)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "generate"))]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::puzzle::board::Board;
+    use crate::puzzle::refiner::Refiner;
+    use crate::puzzle::validator::Validator;
+
+    #[test]
+    fn library() {
+        let root = std::env::temp_dir().join(format!("bestagons-library-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let refiner = Refiner::new(Validator::new(vec![]));
+        let puzzles: Vec<Puzzle> = [2, 3, 2]
+            .into_iter()
+            .map(|radius| refiner.refined(&mut rng, Board::generator(radius).unwrap()))
+            .collect();
+
+        let mut library = Library::open(&root).unwrap();
+        assert!(library.entries().is_empty());
+        assert_eq!(
+            3,
+            library
+                .import_pack(&binary::encode_pack(&puzzles).unwrap())
+                .unwrap()
+        );
+        library.mark_played(0).unwrap();
+        assert!(library.mark_played(3).is_err());
+        library.save().unwrap();
+
+        // Importing the pack again adds none of its puzzles.
+        let mut library = Library::open(&root).unwrap();
+        let pack = binary::encode_pack(&puzzles).unwrap();
+        assert_eq!(0, library.import_pack(&pack).unwrap());
+        assert_eq!(3, library.entries().len());
+        assert!(library.entries()[0].is_played());
+        assert!(library.entries()[1].difficulty().is_some());
+        let scheme = TierScheme::default();
+        for entry in library.entries() {
+            let tier = entry
+                .difficulty()
+                .and_then(|difficulty| scheme.tier(difficulty));
+            assert_eq!(tier, entry.tier());
+        }
+
+        let filter = Filter::default().with_radius(2);
+        assert_eq!(Some(2), library.next(&filter).map(|(id, _entry)| id));
+        assert_eq!(
+            Some(1),
+            library.next(&Filter::default()).map(|(id, _entry)| id)
+        );
+        assert!(library
+            .next(&Filter::default().with_difficulty(1000.0, 2000.0))
+            .is_none());
+
+        let tier = library.entries()[1].tier().unwrap().clone();
+        let (_id, entry) = library
+            .next(&Filter::default().with_tier(tier.clone()))
+            .unwrap();
+        assert_eq!(Some(&tier), entry.tier());
+        let fiendish = scheme.tier_by_name("fiendish").unwrap().clone();
+        assert!(library
+            .next(&Filter::default().with_tier(fiendish))
+            .is_none());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::str::FromStr;
+
+#[cfg(feature = "generate")]
+use rand::Rng;
+
+use crate::grid::hexagon::Hexagon;
+use crate::grid::{Direction, Position};
+
+#[cfg(feature = "generate")]
+use super::{board::Board, puzzle::GeneratorFn};
+use super::{Cell, Hint, Palette};
+
+// A picture template, giving each position of a hexagon the colors its
+// cell may take when generating solutions from it. Positions without
+// a hint may take any color of the mask's palette, the default one
+// unless its hints need more colors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HintMask {
+    hexagon: Hexagon,
+    hints: HashMap<Position, Hint>,
+    palette: Palette,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintMaskError {
+    RowCount(usize),
+    RowLength(usize),
+    UnknownHint(char),
+}
+
+impl Display for HintMaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HintMaskError::RowCount(count) => write!(f, "invalid number of rows {count}"),
+            HintMaskError::RowLength(length) => write!(f, "invalid row length {length}"),
+            HintMaskError::UnknownHint(letter) => write!(f, "unknown hint `{letter}`"),
+        }
+    }
+}
+
+impl std::error::Error for HintMaskError {}
+
+// Each hint is written as a single letter, mixing the colors it allows
+// as light does: red and green make yellow, red and blue make magenta,
+// green and blue make cyan, and all of them make white. Orange and
+// purple are written as the letters of their cells, and any other mix
+// of colors as the letters of its cells within parentheses, as in
+// `(RGO)`.
+const LETTERS: [(char, Hint); 9] = [
+    ('R', Hint([true, false, false, false, false])),
+    ('G', Hint([false, true, false, false, false])),
+    ('B', Hint([false, false, true, false, false])),
+    ('O', Hint([false, false, false, true, false])),
+    ('P', Hint([false, false, false, false, true])),
+    ('Y', Hint([true, true, false, false, false])),
+    ('M', Hint([true, false, true, false, false])),
+    ('C', Hint([false, true, true, false, false])),
+    ('W', Hint([true, true, true, false, false])),
+];
+
+impl HintMask {
+    // Hints for positions outside of the hexagon, and hints that allow
+    // no color, are ignored. The palette of the mask is the smallest
+    // one allowing the colors of every hint.
+    pub fn new(hexagon: Hexagon, hints: impl Iterator<Item = (Position, Hint)>) -> Self {
+        let hints: HashMap<Position, Hint> = hints
+            .filter(|(position, hint)| hexagon.contains(*position) && !hint.is_empty())
+            .collect();
+        let palette = Palette::covering(hints.values().flat_map(|hint| hint.cells()));
+
+        HintMask {
+            hexagon,
+            hints,
+            palette,
+        }
+    }
+
+    pub fn hexagon(&self) -> Hexagon {
+        self.hexagon
+    }
+
+    pub fn hints(&self) -> &HashMap<Position, Hint> {
+        &self.hints
+    }
+
+    pub fn palette(&self) -> Palette {
+        self.palette
+    }
+
+    pub fn get(&self, position: Position) -> Hint {
+        self.hints
+            .get(&position)
+            .copied()
+            .unwrap_or(self.palette.hint())
+    }
+
+    // Generates solutions drawing the picture on a hexagon of the same
+    // radius, centered on the origin.
+    #[cfg(feature = "generate")]
+    pub fn generator<T: Rng>(&self) -> GeneratorFn<T> {
+        let origin = self.hexagon.origin();
+        let hints = self
+            .hexagon
+            .into_iter()
+            .map(|position| (position - origin, self.get(position)))
+            .collect::<Vec<_>>();
+
+        // Every position of the mask's hexagon has a hint of some color.
+        Board::generator_from_hints(self.hexagon.radius(), hints.into_iter()).unwrap()
+    }
+}
+
+// Masks are written as rows of letters, one row for each line along
+// the `XY` direction, in the same order as puzzles are displayed.
+impl Display for HintMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (distance, segment) in self.hexagon.segments(Direction::XY) {
+            for _ in 0..distance.abs() {
+                f.write_char(' ')?;
+            }
+
+            let letters: Vec<String> = segment
+                .into_iter()
+                .map(|position| {
+                    let hint = self.get(position);
+                    match LETTERS.iter().find(|(_, other)| *other == hint) {
+                        Some((letter, _hint)) => letter.to_string(),
+                        None => format!(
+                            "({})",
+                            hint.cells().map(|cell| cell.letter()).collect::<String>()
+                        ),
+                    }
+                })
+                .collect();
+
+            f.write_str(&letters.join(" "))?;
+            f.write_char('\n')?;
+        }
+
+        Ok(())
+    }
+}
+
+// Parses masks as they are displayed, for a hexagon centered on the
+// origin. Whitespace between letters and around rows is ignored.
+impl FromStr for HintMask {
+    type Err = HintMaskError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<Vec<Hint>> = value
+            .lines()
+            .map(row)
+            .filter(|row| !matches!(row, Ok(row) if row.is_empty()))
+            .collect::<Result<_, _>>()?;
+
+        if rows.len().is_multiple_of(2) {
+            return Err(HintMaskError::RowCount(rows.len()));
+        }
+
+        let hexagon = Hexagon::zero(rows.len() as i32 / 2)
+            .map_err(|_| HintMaskError::RowCount(rows.len()))?;
+        let mut hints = HashMap::new();
+
+        for ((_distance, segment), row) in hexagon.segments(Direction::XY).zip(rows) {
+            let positions: Vec<Position> = segment.into_iter().collect();
+            if positions.len() != row.len() {
+                return Err(HintMaskError::RowLength(row.len()));
+            }
+
+            hints.extend(positions.into_iter().zip(row));
+        }
+
+        Ok(HintMask::new(hexagon, hints.into_iter()))
+    }
+}
+
+// Parses the hints of a row, written as letters or as groups of the
+// letters of their cells within parentheses.
+fn row(line: &str) -> Result<Vec<Hint>, HintMaskError> {
+    let mut hints = vec![];
+    let mut chars = line.chars().filter(|c| !c.is_whitespace());
+
+    while let Some(letter) = chars.next() {
+        let hint = if letter == '(' {
+            let mut cells = vec![];
+            loop {
+                match chars.next() {
+                    Some(')') if !cells.is_empty() => break,
+                    Some(letter) => cells.push(cell(letter)?),
+                    None => return Err(HintMaskError::UnknownHint('(')),
+                }
+            }
+            Hint::from_cells(cells.into_iter())
+        } else {
+            LETTERS
+                .iter()
+                .find(|(other, _)| *other == letter.to_ascii_uppercase())
+                .map(|(_letter, hint)| *hint)
+                .ok_or(HintMaskError::UnknownHint(letter))?
+        };
+        hints.push(hint);
+    }
+
+    Ok(hints)
+}
+
+fn cell(letter: char) -> Result<Cell, HintMaskError> {
+    Cell::all()
+        .into_iter()
+        .find(|cell| cell.letter() == letter.to_ascii_uppercase())
+        .ok_or(HintMaskError::UnknownHint(letter))
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "generate")]
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    #[cfg(feature = "generate")]
+    use crate::puzzle::puzzle::Generator;
+
+    #[test]
+    fn parse() {
+        let text = "  R G B\n Y M C W\nR R R R R\n G G G G\n  B B B\n";
+        let mask: HintMask = text.parse().unwrap();
+
+        assert_eq!(2, mask.hexagon().radius());
+        assert_eq!(text, mask.to_string());
+        assert_eq!(mask, mask.to_string().parse().unwrap());
+
+        assert_eq!(
+            Err(HintMaskError::RowCount(2)),
+            "R R\nR R R".parse::<HintMask>()
+        );
+        assert_eq!(
+            Err(HintMaskError::RowLength(2)),
+            "R R\nR R\nR R".parse::<HintMask>()
+        );
+        assert_eq!(
+            Err(HintMaskError::UnknownHint('X')),
+            "R X\nR R R\nR R".parse::<HintMask>()
+        );
+        assert_eq!(
+            Err(HintMaskError::UnknownHint('(')),
+            "R R\nR (RG R\nR R".parse::<HintMask>()
+        );
+
+        // Masks of more colors write unhinted positions as all of them.
+        let mask: HintMask = "  R G B\n O P (GO) W\nR R R R R\n G G G G\n  B B B\n"
+            .parse()
+            .unwrap();
+        assert_eq!(5, mask.palette().colors());
+        assert!(mask
+            .hints()
+            .values()
+            .any(|hint| *hint == Hint::from_cells([Cell::Green, Cell::Orange].into_iter())));
+        assert_eq!(mask, mask.to_string().parse().unwrap());
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn generator() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mask: HintMask = "  R R R\n C C C C\nC C W C C\n C C C C\n  R R R\n"
+            .parse()
+            .unwrap();
+        let puzzle = mask.generator().generate(&mut rng);
+
+        assert!(puzzle.board().is_solved());
+        for (position, cell) in puzzle.board().cells() {
+            assert!(mask.get(*position).cell(*cell));
+        }
+        assert!(puzzle
+            .board()
+            .cells()
+            .values()
+            .any(|cell| *cell != Cell::Red));
+    }
+}
@@ -1,46 +1,197 @@
+pub mod analysis;
+pub mod binary;
+pub mod bitboard;
 pub mod board;
-pub mod heart;
+pub mod diagnosis;
+pub mod difficulty;
+#[cfg(feature = "library")]
+pub mod library;
+pub mod mask;
+#[cfg(feature = "generate")]
+pub mod noise;
+#[cfg(feature = "generate")]
+pub mod optimizer;
+#[allow(clippy::module_inception)]
 pub mod puzzle;
+#[cfg(feature = "generate")]
 pub mod refiner;
+pub mod repair;
+pub mod rules;
+pub mod scoring;
+pub mod session;
+#[cfg(feature = "generate")]
+pub mod shapes;
 pub mod solver;
+#[cfg(feature = "generate")]
+pub mod strategy;
+pub mod svg;
+#[cfg(feature = "tokio")]
+pub mod tasks;
+pub mod technique;
+pub mod trace;
 pub mod validator;
 
+#[cfg(feature = "generate")]
 use rand::{seq::IteratorRandom, seq::SliceRandom, Rng};
-use std::ops::{Add, BitAnd, Sub};
+use std::fmt::Display;
+use std::ops::{Add, BitAnd, BitOr, Sub};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use rules::{ClueRule, ContiguousClue, FullClue, OrderedClue, TotalsClue};
 
 type Count = u32;
 
+// The colors of the cells. Puzzles are painted with the first few of
+// them, as given by their palette, three of them unless told otherwise.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cell {
     Red,
     Green,
     Blue,
+    Orange,
+    Purple,
 }
 
-const CELLS: [Cell; 3] = {
+// The number of colors of the largest palette.
+const COLORS: usize = 5;
+
+const CELLS: [Cell; COLORS] = {
     use Cell::*;
 
-    [Red, Green, Blue]
+    [Red, Green, Blue, Orange, Purple]
 };
 
 impl Cell {
+    #[cfg(feature = "generate")]
     pub fn random(rng: &mut impl Rng) -> Self {
-        *CELLS.choose(rng).unwrap()
+        Palette::default().random(rng)
     }
 
-    pub fn all() -> [Cell; 3] {
+    // Every color, including those left out of the default palette.
+    pub fn all() -> [Cell; COLORS] {
         CELLS
     }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Cell::Red => "red",
+            Cell::Green => "green",
+            Cell::Blue => "blue",
+            Cell::Orange => "orange",
+            Cell::Purple => "purple",
+        }
+    }
+
+    // The letter of the color, as cells are written out.
+    pub fn letter(&self) -> char {
+        match self {
+            Cell::Red => 'R',
+            Cell::Green => 'G',
+            Cell::Blue => 'B',
+            Cell::Orange => 'O',
+            Cell::Purple => 'P',
+        }
+    }
+
+    // The position of the color among all of them, from zero.
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+// The colors a board is painted with: the first few of the colors, from
+// two of them for beginners to all five for a harder game, with three
+// by default. Puzzles are solved within their palette, so the colors
+// left out of it are never considered for their positions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Palette(usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PaletteError {
+    UnsupportedColors(usize),
+}
+
+impl Display for PaletteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaletteError::UnsupportedColors(colors) => write!(
+                f,
+                "unsupported number of colors {colors}, expected 2 to {COLORS}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PaletteError {}
+
+impl Palette {
+    pub fn new(colors: usize) -> Result<Self, PaletteError> {
+        if (2..=COLORS).contains(&colors) {
+            Ok(Palette(colors))
+        } else {
+            Err(PaletteError::UnsupportedColors(colors))
+        }
+    }
+
+    // The default palette, or a larger one if the cells need more colors.
+    pub fn covering(cells: impl Iterator<Item = Cell>) -> Self {
+        cells.fold(Palette::default(), |palette, cell| {
+            Palette(palette.0.max(cell.index() + 1))
+        })
+    }
+
+    // The number of colors of the palette.
+    pub fn colors(&self) -> usize {
+        self.0
+    }
+
+    pub fn cells(&self) -> &'static [Cell] {
+        &CELLS[..self.0]
+    }
+
+    pub fn contains(&self, cell: Cell) -> bool {
+        self.cells().contains(&cell)
+    }
+
+    // The hint that allows every color of the palette.
+    pub fn hint(&self) -> Hint {
+        Hint::from_cells(self.cells().iter().copied())
+    }
+
+    #[cfg(feature = "generate")]
+    pub fn random(&self, rng: &mut impl Rng) -> Cell {
+        *self.cells().choose(rng).unwrap()
+    }
 }
 
+impl Default for Palette {
+    fn default() -> Self {
+        Palette(3)
+    }
+}
+
+// The number of cells of each color of a line, with none of the colors
+// left out of the puzzle's palette.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct Clue(Count, Count, Count);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Clue([Count; COLORS]);
 
-const ZERO: Clue = Clue(0, 0, 0);
+const ZERO: Clue = Clue([0; COLORS]);
 
 impl Clue {
+    // A clue of the three colors of the default palette.
     pub fn new(red: Count, green: Count, blue: Count) -> Self {
-        Clue(red, green, blue)
+        Clue([red, green, blue, 0, 0])
+    }
+
+    // A clue of the given counts, in the order of the colors, with none
+    // of the colors after them.
+    pub fn from_counts(counts: &[Count]) -> Self {
+        let mut clue = ZERO;
+        clue.0[..counts.len()].copy_from_slice(counts);
+        clue
     }
 
     pub fn zero() -> Self {
@@ -48,11 +199,11 @@ impl Clue {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.red() == 0 && self.green() == 0 && self.blue() == 0
+        *self == ZERO
     }
 
     pub fn count(&self) -> Count {
-        self.red() + self.blue() + self.green()
+        self.0.iter().sum()
     }
 
     // Returns the cell type with the lowest non-zero value.
@@ -72,55 +223,59 @@ impl Clue {
     }
 
     pub fn from_cells(cells: impl Iterator<Item = Cell>) -> Self {
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
-
+        let mut clue = ZERO;
         for cell in cells {
-            use Cell::*;
-
-            match cell {
-                Red => red += 1,
-                Green => green += 1,
-                Blue => blue += 1,
-            }
+            clue.0[cell.index()] += 1;
         }
 
-        Clue::new(red, green, blue)
+        clue
     }
 
-    pub fn red(&self) -> Count {
+    // The counts of every color, in the order of the colors.
+    pub fn counts(&self) -> [Count; COLORS] {
         self.0
     }
 
+    pub fn red(&self) -> Count {
+        self.cell(Cell::Red)
+    }
+
     pub fn green(&self) -> Count {
-        self.1
+        self.cell(Cell::Green)
     }
 
     pub fn blue(&self) -> Count {
-        self.2
+        self.cell(Cell::Blue)
     }
 
     pub fn cell(&self, cell: Cell) -> Count {
-        use Cell::*;
+        self.0[cell.index()]
+    }
+
+    pub fn hint(&self) -> Hint {
+        Hint(self.0.map(|count| count > 0))
+    }
 
-        match cell {
-            Red => self.red(),
-            Green => self.green(),
-            Blue => self.blue(),
+    // Subtracts the other clue, unless it has more of any color.
+    pub fn checked_sub(&self, other: Clue) -> Option<Clue> {
+        let mut clue = ZERO;
+        for (index, count) in clue.0.iter_mut().enumerate() {
+            *count = self.0[index].checked_sub(other.0[index])?;
         }
+
+        Some(clue)
     }
 
-    pub fn hint(&self) -> Hint {
-        Hint(self.red() > 0, self.green() > 0, self.blue() > 0)
+    // The counts of the clue from highest to lowest, without the colors
+    // they belong to.
+    pub fn totals(&self) -> [Count; COLORS] {
+        let mut totals = self.0;
+        totals.sort_unstable_by(|a, b| b.cmp(a));
+        totals
     }
 
     pub fn is_solved(&self) -> bool {
-        [self.red(), self.green(), self.blue()]
-            .into_iter()
-            .filter(|count| *count > 0)
-            .count()
-            == 1
+        self.0.iter().filter(|count| **count > 0).count() == 1
     }
 }
 
@@ -128,11 +283,7 @@ impl Add for Clue {
     type Output = Clue;
 
     fn add(self, other: Self) -> Self::Output {
-        Clue(
-            self.red() + other.red(),
-            self.green() + other.green(),
-            self.blue() + other.blue(),
-        )
+        Clue(std::array::from_fn(|index| self.0[index] + other.0[index]))
     }
 }
 
@@ -140,77 +291,200 @@ impl Sub for Clue {
     type Output = Clue;
 
     fn sub(self, other: Self) -> Self::Output {
-        Clue(
-            self.red() - other.red(),
-            self.green() - other.green(),
-            self.blue() - other.blue(),
-        )
+        Clue(std::array::from_fn(|index| self.0[index] - other.0[index]))
     }
 }
 
+// How much of a line's clue is revealed to the player.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum ClueKind {
+    // The number of cells of each color.
+    #[default]
+    Full,
+    // The numbers of cells of each color, without saying which color
+    // each of the numbers belongs to.
+    Totals,
+    // The number of cells of each color, listed in the order in which
+    // the colors first appear, reading the line from its clue's end.
+    Ordered,
+    // The number of cells of each color, and whether the cells of each
+    // color form a single contiguous run.
+    Contiguous,
+}
+
+const CLUE_KINDS: [ClueKind; 4] = {
+    use ClueKind::*;
+
+    [Full, Totals, Ordered, Contiguous]
+};
+
+impl ClueKind {
+    pub fn all() -> [ClueKind; 4] {
+        CLUE_KINDS
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClueKind::Full => "full",
+            ClueKind::Totals => "totals",
+            ClueKind::Ordered => "ordered",
+            ClueKind::Contiguous => "contiguous",
+        }
+    }
+
+    // The kinds of clue that reveal everything this kind does about a
+    // line, and more.
+    pub fn stronger(&self) -> &'static [ClueKind] {
+        match self {
+            ClueKind::Full => &[ClueKind::Ordered, ClueKind::Contiguous],
+            ClueKind::Totals => &[ClueKind::Full],
+            ClueKind::Ordered | ClueKind::Contiguous => &[],
+        }
+    }
+
+    // The rule of a line of this kind of clue, revealing what it does
+    // about the line's solution cells.
+    pub fn rule(&self, cells: &[Cell]) -> Arc<dyn ClueRule> {
+        match self {
+            ClueKind::Full => Arc::new(FullClue::new(cells)),
+            ClueKind::Totals => Arc::new(TotalsClue::new(cells)),
+            ClueKind::Ordered => Arc::new(OrderedClue::new(cells)),
+            ClueKind::Contiguous => Arc::new(ContiguousClue::new(cells)),
+        }
+    }
+
+    // The rule of a line of this kind of clue, from the counts of the
+    // clue and the colors it reveals beyond them, as written out.
+    pub fn rule_from_parts(&self, clue: Clue, cells: Vec<Cell>) -> Arc<dyn ClueRule> {
+        match self {
+            ClueKind::Full => Arc::new(FullClue::from(clue)),
+            ClueKind::Totals => Arc::new(TotalsClue::from(clue)),
+            ClueKind::Ordered => Arc::new(OrderedClue::from_parts(clue, cells)),
+            ClueKind::Contiguous => Arc::new(ContiguousClue::from_parts(clue, cells)),
+        }
+    }
+}
+
+impl FromStr for ClueKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        CLUE_KINDS
+            .into_iter()
+            .find(|kind| kind.name() == value)
+            .ok_or_else(|| format!("unknown clue kind `{value}`"))
+    }
+}
+
+// Weights for picking the kind of clue of each line of a puzzle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClueMix(Vec<(ClueKind, f64)>);
+
+impl ClueMix {
+    pub fn new(weights: Vec<(ClueKind, f64)>) -> Self {
+        ClueMix(weights)
+    }
+
+    // The kinds of clue that may be picked.
+    pub fn kinds(&self) -> impl Iterator<Item = ClueKind> + '_ {
+        self.0
+            .iter()
+            .filter(|(_kind, weight)| *weight > 0.0)
+            .map(|(kind, _weight)| *kind)
+    }
+
+    // Picks a kind of clue with a probability proportional to its
+    // weight, falling back to full clues if no kind has any weight.
+    #[cfg(feature = "generate")]
+    pub fn pick(&self, rng: &mut impl Rng) -> ClueKind {
+        self.0
+            .choose_weighted(rng, |(_kind, weight)| weight.max(0.0))
+            .map_or(ClueKind::Full, |(kind, _weight)| *kind)
+    }
+}
+
+impl Default for ClueMix {
+    fn default() -> Self {
+        ClueMix(vec![(ClueKind::Full, 1.0)])
+    }
+}
+
+// Mixes are written as a single kind of clue, or as weighted kinds of
+// clue, such as `full=2,ordered=1`.
+impl FromStr for ClueMix {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        value
+            .split(',')
+            .map(|part| match part.split_once('=') {
+                Some((kind, weight)) => Ok((
+                    kind.trim().parse()?,
+                    weight
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("invalid weight `{weight}` for `{kind}`"))?,
+                )),
+                None => Ok((part.trim().parse()?, 1.0)),
+            })
+            .collect::<Result<_, String>>()
+            .map(ClueMix)
+    }
+}
+
+// The colors a position could still take.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Hint(bool, bool, bool);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hint([bool; COLORS]);
 
 impl Hint {
-    fn any() -> Self {
-        Hint(true, true, true)
+    // A hint of the three colors of the default palette.
+    pub fn new(red: bool, green: bool, blue: bool) -> Self {
+        Hint([red, green, blue, false, false])
     }
 
-    fn none() -> Self {
-        Hint(false, false, false)
+    // The hint that allows exactly the given cells.
+    pub fn from_cells(cells: impl Iterator<Item = Cell>) -> Self {
+        let mut hint = Hint::none();
+        for cell in cells {
+            hint.0[cell.index()] = true;
+        }
+
+        hint
     }
 
-    fn red(&self) -> bool {
-        self.0
+    fn is_empty(&self) -> bool {
+        *self == Hint::none()
     }
 
-    fn green(&self) -> bool {
-        self.1
+    fn none() -> Self {
+        Hint([false; COLORS])
     }
 
-    fn blue(&self) -> bool {
-        self.2
+    // The colors the hint allows, in their order.
+    fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        CELLS.into_iter().filter(|cell| self.cell(*cell))
     }
 
+    #[cfg(feature = "generate")]
     fn random(&self, rng: &mut impl Rng) -> Option<Cell> {
-        use Cell::*;
-        [
-            (Red, self.red()),
-            (Green, self.green()),
-            (Blue, self.blue()),
-        ]
-        .into_iter()
-        .filter(|(_cell, hint)| *hint)
-        .map(|(cell, _hint)| cell)
-        .choose(rng)
+        self.cells().choose(rng)
     }
 
     fn cell(&self, cell: Cell) -> bool {
-        use Cell::*;
-        match cell {
-            Red => self.red(),
-            Green => self.green(),
-            Blue => self.blue(),
-        }
+        self.0[cell.index()]
     }
 
     fn solution(&self) -> Option<Cell> {
-        use Cell::*;
-
-        match (self.red(), self.green(), self.blue()) {
-            (true, false, false) => Some(Red),
-            (false, true, false) => Some(Green),
-            (false, false, true) => Some(Blue),
+        let mut cells = self.cells();
+        match (cells.next(), cells.next()) {
+            (Some(cell), None) => Some(cell),
             _ => None,
         }
     }
 
     fn clue(&self) -> Clue {
-        Clue(
-            if self.red() { 1 } else { 0 },
-            if self.green() { 1 } else { 0 },
-            if self.blue() { 1 } else { 0 },
-        )
+        Clue(self.0.map(Count::from))
     }
 }
 
@@ -218,10 +492,14 @@ impl BitAnd for Hint {
     type Output = Hint;
 
     fn bitand(self, other: Self) -> Self::Output {
-        Hint(
-            self.red() && other.red(),
-            self.green() && other.green(),
-            self.blue() && other.blue(),
-        )
+        Hint(std::array::from_fn(|index| self.0[index] && other.0[index]))
+    }
+}
+
+impl BitOr for Hint {
+    type Output = Hint;
+
+    fn bitor(self, other: Self) -> Self::Output {
+        Hint(std::array::from_fn(|index| self.0[index] || other.0[index]))
     }
 }
@@ -0,0 +1,83 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::grid::layout::{Layout, Orientation, Point};
+use crate::grid::Position;
+
+const TABLE_SIZE: usize = 256;
+
+// Smooth two-dimensional value noise: random values at the points of a
+// square lattice, interpolated in between them.
+pub struct ValueNoise {
+    permutation: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl ValueNoise {
+    pub fn new(rng: &mut impl Rng) -> Self {
+        let mut permutation: Vec<usize> = (0..TABLE_SIZE).collect();
+        permutation.shuffle(rng);
+        let values = (0..TABLE_SIZE).map(|_| rng.gen()).collect();
+
+        ValueNoise {
+            permutation,
+            values,
+        }
+    }
+
+    fn lattice(&self, x: i64, y: i64) -> f64 {
+        let x = x.rem_euclid(TABLE_SIZE as i64) as usize;
+        let y = y.rem_euclid(TABLE_SIZE as i64) as usize;
+        self.values[self.permutation[(self.permutation[x] + y) % TABLE_SIZE]]
+    }
+
+    // Samples the noise at the given point, returning a value between
+    // zero and one. Points one unit apart are mostly unrelated.
+    pub fn sample(&self, point: Point) -> f64 {
+        let (x, y) = point;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (ix, iy) = (x0 as i64, y0 as i64);
+        let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+        let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+
+        // Points too far away for their lattice points to fit saturate,
+        // so their neighbors wrap around, as the lattice does.
+        let (jx, jy) = (ix.wrapping_add(1), iy.wrapping_add(1));
+
+        let top = self.lattice(ix, iy) * (1.0 - tx) + self.lattice(jx, iy) * tx;
+        let bottom = self.lattice(ix, jy) * (1.0 - tx) + self.lattice(jx, jy) * tx;
+
+        top * (1.0 - ty) + bottom * ty
+    }
+
+    // Samples the noise at the center of the cell at the given position,
+    // such that the noise varies over about `scale` cells.
+    pub fn sample_position(&self, position: Position, scale: f64) -> f64 {
+        let layout = Layout::new(Orientation::PointyTop, 1.0 / scale, (0.0, 0.0));
+        self.sample(layout.position_to_pixel(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let noise = ValueNoise::new(&mut StdRng::seed_from_u64(1));
+
+        for step in 0..100 {
+            let point = (step as f64 * 0.37 - 10.0, step as f64 * -0.21 + 3.0);
+            let value = noise.sample(point);
+
+            assert!((0.0..=1.0).contains(&value));
+            assert!((value - noise.sample((point.0 + 0.01, point.1))).abs() < 0.05);
+        }
+
+        assert_eq!(noise.lattice(3, 4), noise.sample((3.0, 4.0)));
+        noise.sample((f64::INFINITY, f64::MAX));
+    }
+}
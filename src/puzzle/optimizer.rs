@@ -0,0 +1,366 @@
+use rand::seq::IteratorRandom;
+use rand::Rng;
+
+use crate::grid::hexagon::Hexagon;
+use crate::grid::region::Region;
+use crate::grid::Position;
+
+use super::board::Board;
+use super::difficulty::Difficulty;
+use super::puzzle::Puzzle;
+use super::solver::Solver;
+use super::validator::Validator;
+use super::Clue;
+
+// A weighted combination of the qualities of a puzzle, where higher
+// values are better. Weights can be negative to penalize a quality.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Objective {
+    pub difficulty: f64,
+    pub givens: f64,
+    pub symmetry: f64,
+    pub balance: f64,
+}
+
+const DEFAULT: Objective = Objective {
+    difficulty: 1.0,
+    givens: -1.0,
+    symmetry: 0.0,
+    balance: 0.0,
+};
+
+// Named sets of weights, for use from configuration.
+const PROFILES: [(&str, Objective); 4] = [
+    ("default", DEFAULT),
+    (
+        "hard",
+        Objective {
+            difficulty: 2.0,
+            givens: -0.5,
+            ..DEFAULT
+        },
+    ),
+    (
+        "minimal",
+        Objective {
+            givens: -4.0,
+            ..DEFAULT
+        },
+    ),
+    (
+        "symmetric",
+        Objective {
+            symmetry: 20.0,
+            ..DEFAULT
+        },
+    ),
+];
+
+impl Default for Objective {
+    fn default() -> Self {
+        DEFAULT
+    }
+}
+
+impl Objective {
+    pub fn profile(name: &str) -> Option<Self> {
+        PROFILES
+            .iter()
+            .find(|(profile, _objective)| *profile == name)
+            .map(|(_profile, objective)| *objective)
+    }
+
+    // Returns `None` if the puzzle cannot be solved logically, as its
+    // difficulty cannot be rated.
+    pub fn evaluate<R: Region>(&self, puzzle: &Puzzle<R>) -> Option<f64> {
+        let difficulty = Difficulty::rate(puzzle)?;
+        Some(self.evaluate_with(puzzle, difficulty.value()))
+    }
+
+    // Evaluates the puzzle with an already known or projected
+    // difficulty, without rating it.
+    pub fn evaluate_with<R: Region>(&self, puzzle: &Puzzle<R>, difficulty: f64) -> f64 {
+        let mut value =
+            self.difficulty * difficulty + self.givens * puzzle.board().cells().len() as f64;
+
+        if self.symmetry != 0.0 {
+            value += self.symmetry * symmetry(puzzle);
+        }
+        if self.balance != 0.0 {
+            value += self.balance * balance(puzzle);
+        }
+
+        value
+    }
+}
+
+// Parses either the name of a profile or a comma-separated list of
+// weights, such as `difficulty=2,givens=-1`, where missing weights take
+// their default value.
+impl std::str::FromStr for Objective {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Some(objective) = Self::profile(value) {
+            return Ok(objective);
+        }
+
+        let mut objective = DEFAULT;
+        for weight in value.split(',') {
+            let (name, weight) = weight
+                .split_once('=')
+                .ok_or_else(|| format!("unknown objective profile `{value}`"))?;
+            let weight: f64 = weight
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid weight `{weight}` for `{name}`"))?;
+
+            match name.trim() {
+                "difficulty" => objective.difficulty = weight,
+                "givens" => objective.givens = weight,
+                "symmetry" => objective.symmetry = weight,
+                "balance" => objective.balance = weight,
+                other => return Err(format!("unknown objective `{other}`")),
+            }
+        }
+
+        Ok(objective)
+    }
+}
+
+// The share of given cells whose point reflection through the center
+// of the board is also a given cell. Regions whose center is not at a
+// cell or between two of them, such as most triangles, have no point
+// symmetry at all.
+pub fn symmetry<R: Region>(puzzle: &Puzzle<R>) -> f64 {
+    let cells = puzzle.board().cells();
+    if cells.is_empty() {
+        return 1.0;
+    }
+
+    let Some(doubled) = doubled_center(puzzle.board().region()) else {
+        return 0.0;
+    };
+    let symmetric = cells
+        .keys()
+        .filter(|position| cells.contains_key(&(doubled - **position)))
+        .count();
+
+    symmetric as f64 / cells.len() as f64
+}
+
+// Twice the center of mass of the positions of the region, which a
+// position and its point reflection add up to, if it is a position.
+fn doubled_center(region: &impl Region) -> Option<Position> {
+    let (mut sum, mut count) = ((0, 0, 0), 0);
+    for position in region.clone() {
+        let (x, y, z) = position.coordinates();
+        sum = (sum.0 + x, sum.1 + y, sum.2 + z);
+        count += 1;
+    }
+
+    let (x, y, z) = (sum.0 * 2, sum.1 * 2, sum.2 * 2);
+    if count == 0 || x % count != 0 || y % count != 0 || z % count != 0 {
+        return None;
+    }
+    Position::new((x / count, y / count, z / count)).ok()
+}
+
+// How evenly the colors of the palette are spread across the clues of
+// a puzzle, from zero if all of them count a single color, to one if all
+// colors are counted equally often.
+pub fn balance<R: Region>(puzzle: &Puzzle<R>) -> f64 {
+    let total = puzzle
+        .clues()
+        .values()
+        .fold(Clue::zero(), |total, clue| total + *clue);
+    if total.count() == 0 {
+        return 1.0;
+    }
+
+    let max = total.counts().into_iter().max().unwrap_or(0);
+    let share = max as f64 / total.count() as f64;
+    let colors = puzzle.palette().colors() as f64;
+
+    (1.0 - share) * colors / (colors - 1.0)
+}
+
+// Polishes a valid puzzle by simulated annealing: given cells are
+// repeatedly added, removed or moved, keeping changes that leave the
+// puzzle solvable and valid when they improve the objective, and
+// sometimes when they do not, less often as the temperature cools.
+pub struct Annealer<R: Region = Hexagon> {
+    validator: Validator<R>,
+    objective: Objective,
+    iterations: usize,
+    temperature: f64,
+    cooling: f64,
+}
+
+impl<R: Region> Annealer<R> {
+    pub fn new(validator: Validator<R>, objective: Objective) -> Self {
+        Annealer {
+            validator,
+            objective,
+            iterations: 200,
+            temperature: 2.0,
+            cooling: 0.98,
+        }
+    }
+
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    // The temperature is multiplied by the cooling factor after every
+    // iteration.
+    pub fn with_temperature(mut self, temperature: f64, cooling: f64) -> Self {
+        self.temperature = temperature;
+        self.cooling = cooling;
+        self
+    }
+
+    // Returns the best puzzle found, which is the given puzzle if no
+    // change improved it. The solution is the board that the puzzle's
+    // given cells are taken from.
+    pub fn optimize(
+        &self,
+        rng: &mut impl Rng,
+        puzzle: Puzzle<R>,
+        solution: &Board<R>,
+    ) -> Puzzle<R> {
+        let Some(score) = self.evaluate(&puzzle) else {
+            return puzzle;
+        };
+
+        let mut current = (puzzle.clone(), score);
+        let mut best = (puzzle, score);
+        let mut temperature = self.temperature;
+
+        for _ in 0..self.iterations {
+            let candidate = Self::mutate(rng, &current.0, solution);
+
+            if let Some(score) = self.evaluate(&candidate) {
+                let delta = score - current.1;
+                if delta >= 0.0 || rng.gen::<f64>() < (delta / temperature).exp() {
+                    if score > best.1 {
+                        best = (candidate.clone(), score);
+                    }
+                    current = (candidate, score);
+                }
+            }
+
+            temperature *= self.cooling;
+        }
+
+        best.0
+    }
+
+    fn evaluate(&self, puzzle: &Puzzle<R>) -> Option<f64> {
+        if !Solver::new(puzzle.clone()).solve() || !self.validator.is_valid(puzzle) {
+            return None;
+        }
+
+        self.objective.evaluate(puzzle)
+    }
+
+    // Adds, removes or moves a random given cell.
+    fn mutate(rng: &mut impl Rng, puzzle: &Puzzle<R>, solution: &Board<R>) -> Puzzle<R> {
+        let givens = puzzle.board().cells();
+        let given: Option<Position> = givens.keys().copied().choose(rng);
+
+        // Puzzles leave most positions hidden, so random positions are
+        // drawn until one of them is.
+        let positions: Vec<Position> = solution.region().clone().into_iter().collect();
+        let hidden: Option<Position> = (givens.len() < positions.len()).then(|| loop {
+            let position = positions[rng.gen_range(0..positions.len())];
+            if !givens.contains_key(&position) {
+                break position;
+            }
+        });
+
+        let (removed, added) = match (given, hidden) {
+            (Some(given), Some(hidden)) => match rng.gen_range(0..3) {
+                0 => (Some(given), None),
+                1 => (None, Some(hidden)),
+                _ => (Some(given), Some(hidden)),
+            },
+            other => other,
+        };
+
+        let mut candidate = puzzle.clone();
+        if let Some(position) = removed {
+            candidate.mut_board().remove(position);
+        }
+        if let Some(position) = added {
+            candidate
+                .mut_board()
+                .insert(position, solution.cells()[&position]);
+        }
+
+        candidate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::puzzle::refiner::Refiner;
+    use crate::puzzle::Cell;
+
+    #[test]
+    fn optimize() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let refiner = Refiner::new(Validator::new(vec![]));
+        let annealer = Annealer::new(Validator::new(vec![]), Objective::default());
+
+        for _ in 0..3 {
+            let solution = Board::random(&mut rng, 3).unwrap();
+            let puzzle = refiner
+                .refine(Puzzle::with_clues(solution.clone()))
+                .unwrap();
+            let optimized = annealer.optimize(&mut rng, puzzle.clone(), &solution);
+
+            assert!(Solver::new(optimized.clone()).solve());
+            assert!(
+                Objective::default().evaluate(&optimized).unwrap()
+                    >= Objective::default().evaluate(&puzzle).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn symmetric() {
+        let mut board = Board::new(2).unwrap();
+        let position = Position::new((1, -2, 1)).unwrap();
+        board.insert(position, Cell::Red);
+        let mut puzzle = Puzzle::with_clues(board.clone());
+
+        assert_eq!(0.0, symmetry(&puzzle));
+
+        board.insert(-position, Cell::Blue);
+        *puzzle.mut_board() = board;
+
+        assert_eq!(1.0, symmetry(&puzzle));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Objective::profile("hard"), "hard".parse().ok());
+        assert_eq!(
+            Ok(Objective {
+                givens: -3.0,
+                balance: 2.0,
+                ..Objective::default()
+            }),
+            "givens=-3, balance=2".parse()
+        );
+        assert!("unknown".parse::<Objective>().is_err());
+        assert!("givens=x".parse::<Objective>().is_err());
+        assert!("color=1".parse::<Objective>().is_err());
+    }
+}
@@ -1,40 +1,156 @@
 use std::collections::HashMap;
 use std::fmt::{Display, Write};
+use std::sync::Arc;
 
+#[cfg(feature = "generate")]
 use rand::Rng;
 
 use super::board::Board;
-use super::{Cell, Clue};
-use crate::grid::{Direction, Distance};
+use super::rules::{ClueRule, FullClue};
+#[cfg(feature = "generate")]
+use super::ClueMix;
+use super::{Cell, Clue, ClueKind, Count, Palette};
+use crate::grid::hexagon::Hexagon;
+use crate::grid::region::Region;
+use crate::grid::{Direction, Distance, Position};
 
+// Why a line could not be given a kind of clue.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ClueKindError {
+    // The puzzle has no clue for the line.
+    MissingClue(Direction, Distance),
+    // The kind of clue reveals more than the counts of the line's clue,
+    // and the puzzle's board does not hold the line's solution.
+    UnsolvedLine(Direction, Distance),
+}
+
+impl Display for ClueKindError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClueKindError::MissingClue(direction, distance) => {
+                write!(f, "missing clue for line {direction:?} {distance}")
+            }
+            ClueKindError::UnsolvedLine(direction, distance) => {
+                write!(f, "line {direction:?} {distance} is not solved")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClueKindError {}
+
+// Why a board could not be made into a puzzle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PuzzleError {
+    // The line of the board's region is broken by gaps into several
+    // segments, which clues of whole lines cannot count.
+    BrokenLine(Direction, Distance),
+}
+
+impl Display for PuzzleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PuzzleError::BrokenLine(direction, distance) => {
+                write!(f, "line {direction:?} {distance} is broken by gaps")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {}
+
+// The clues of the lines of a board, and the cells of it given away.
+// Puzzles are played on hexagons, but can be laid out on boards of any
+// other region just as well, as long as its lines are not broken.
 #[derive(Debug, Clone)]
-pub struct Puzzle {
-    board: Board,
+pub struct Puzzle<R = Hexagon> {
+    board: Board<R>,
     clues: HashMap<(Direction, Distance), Clue>,
+    rules: HashMap<(Direction, Distance), Arc<dyn ClueRule>>,
+    palette: Palette,
 }
 
+// A way in which a player's board breaks the rules of a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    // The board leaves out or changes the given cell at the position.
+    Given(Position, Cell),
+    // The line has more cells of the color than its clue allows, at the
+    // positions.
+    Exceeded {
+        line: (Direction, Distance),
+        cell: Cell,
+        clue: Count,
+        positions: Vec<Position>,
+    },
+    // The line is complete, but does not satisfy the rule of its clue.
+    Unsatisfied((Direction, Distance)),
+}
+
+// Puzzles on hexagons, whose lines are never broken.
 impl Puzzle {
     pub fn new(
         board: Board,
         clue_iterator: impl Iterator<Item = ((Direction, Distance), Clue)>,
+    ) -> Self {
+        Self::new_unchecked(board, clue_iterator)
+    }
+
+    // Lines reveal their full clues until given another rule.
+    pub fn with_clues(board: Board) -> Self {
+        Self::with_clues_unchecked(board)
+    }
+}
+
+impl<R: Region> Puzzle<R> {
+    // Puzzles on boards of any other region, as long as none of its
+    // lines are broken by gaps.
+    pub fn new_in(
+        board: Board<R>,
+        clue_iterator: impl Iterator<Item = ((Direction, Distance), Clue)>,
+    ) -> Result<Self, PuzzleError> {
+        check_lines(board.region())?;
+        Ok(Self::new_unchecked(board, clue_iterator))
+    }
+
+    pub fn with_clues_in(board: Board<R>) -> Result<Self, PuzzleError> {
+        check_lines(board.region())?;
+        Ok(Self::with_clues_unchecked(board))
+    }
+
+    fn new_unchecked(
+        board: Board<R>,
+        clue_iterator: impl Iterator<Item = ((Direction, Distance), Clue)>,
     ) -> Self {
         let mut clues: HashMap<(Direction, Distance), Clue> = HashMap::new();
+        let mut rules: HashMap<(Direction, Distance), Arc<dyn ClueRule>> = HashMap::new();
 
         for (key, clue) in clue_iterator {
             clues.insert(key, clue);
+            rules.insert(key, Arc::new(FullClue::from(clue)));
         }
 
+        let palette = Palette::covering(
+            board
+                .cells()
+                .values()
+                .copied()
+                .chain(clues.values().filter_map(Clue::max_cell)),
+        );
+
         Puzzle {
             board,
-            clues: HashMap::new(),
+            clues,
+            rules,
+            palette,
         }
     }
 
-    pub fn board(&self) -> &Board {
+    pub fn board(&self) -> &Board<R> {
         &self.board
     }
 
-    pub fn mut_board(&mut self) -> &mut Board {
+    pub fn mut_board(&mut self) -> &mut Board<R> {
         &mut self.board
     }
 
@@ -43,22 +159,190 @@ impl Puzzle {
     }
 
     pub fn clear(&mut self) {
-        self.board = Board::new(self.board().hexagon().radius()).unwrap();
+        self.board = Board::with_region(self.board.region().clone());
     }
 
-    pub fn with_clues(board: Board) -> Self {
+    // For boards whose lines were already checked, such as those of
+    // generators, which check their region when they are built.
+    pub(super) fn with_clues_unchecked(board: Board<R>) -> Self {
         let mut clues: HashMap<(Direction, Distance), Clue> = HashMap::new();
+        let mut rules: HashMap<(Direction, Distance), Arc<dyn ClueRule>> = HashMap::new();
 
         for (key, clue) in board.clues() {
             clues.insert(key, clue);
         }
 
-        Puzzle { board, clues }
+        for (key, segment) in board.normalized_segments() {
+            let cells: Vec<Cell> = segment.filter_map(|(_position, cell)| cell).collect();
+            rules.insert(key, Arc::new(FullClue::new(&cells)));
+        }
+
+        let palette = Palette::covering(board.cells().values().copied());
+
+        Puzzle {
+            board,
+            clues,
+            rules,
+            palette,
+        }
+    }
+
+    // The colors the puzzle is played with: three of them, unless its
+    // board or clues hold more, or it is given another palette.
+    pub fn palette(&self) -> Palette {
+        self.palette
+    }
+
+    pub fn with_palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    // The rules by which the clue of each line constrains its cells.
+    pub fn clue_rules(&self) -> &HashMap<(Direction, Distance), Arc<dyn ClueRule>> {
+        &self.rules
+    }
+
+    pub fn set_clue_rule(&mut self, key: (Direction, Distance), rule: Arc<dyn ClueRule>) {
+        self.rules.insert(key, rule);
+    }
+
+    // Replaces the clue of the line with a full clue of the given counts.
+    pub fn set_clue(&mut self, key: (Direction, Distance), clue: Clue) {
+        self.clues.insert(key, clue);
+        self.rules.insert(key, Arc::new(FullClue::from(clue)));
+    }
+
+    // Leaves the line without a clue, so that it does not constrain its
+    // cells at all.
+    pub fn remove_clue_rule(&mut self, key: (Direction, Distance)) -> Option<Arc<dyn ClueRule>> {
+        self.rules.remove(&key)
+    }
+
+    // Gives the line a built-in kind of clue. Full clues and totals are
+    // taken from the line's clue, while the other kinds reveal how the
+    // cells of the line are laid out, which is taken from the puzzle's
+    // board. It must hold the line's whole solution, which puzzles only
+    // do before they are cleared.
+    pub fn set_clue_kind(
+        &mut self,
+        key: (Direction, Distance),
+        kind: ClueKind,
+    ) -> Result<(), ClueKindError> {
+        let (direction, distance) = key;
+        let clue = *self
+            .clues
+            .get(&key)
+            .ok_or(ClueKindError::MissingClue(direction, distance))?;
+
+        let rule = match kind {
+            ClueKind::Full | ClueKind::Totals => kind.rule_from_parts(clue, vec![]),
+            ClueKind::Ordered | ClueKind::Contiguous => {
+                let cells: Option<Vec<Cell>> = self
+                    .board
+                    .segment(distance, direction)
+                    .ok_or(ClueKindError::MissingClue(direction, distance))?
+                    .map(|(_position, cell)| cell)
+                    .collect();
+
+                match cells {
+                    Some(cells) if Clue::from_cells(cells.iter().copied()) == clue => {
+                        kind.rule(&cells)
+                    }
+                    _ => return Err(ClueKindError::UnsolvedLine(direction, distance)),
+                }
+            }
+        };
+
+        self.rules.insert(key, rule);
+        Ok(())
+    }
+
+    // Sets the kind of clue of every line of the puzzle.
+    pub fn with_clue_kind(mut self, kind: ClueKind) -> Result<Self, ClueKindError> {
+        let keys: Vec<(Direction, Distance)> = self.clues.keys().copied().collect();
+        for key in keys {
+            self.set_clue_kind(key, kind)?;
+        }
+        Ok(self)
+    }
+
+    // Picks the kind of clue of every line of the puzzle from the mix.
+    #[cfg(feature = "generate")]
+    pub fn with_clue_mix(
+        mut self,
+        rng: &mut impl Rng,
+        mix: &ClueMix,
+    ) -> Result<Self, ClueKindError> {
+        let keys: Vec<(Direction, Distance)> = self
+            .board
+            .normalized_segments()
+            .map(|(key, _segment)| key)
+            .collect();
+
+        for key in keys {
+            self.set_clue_kind(key, mix.pick(rng))?;
+        }
+        Ok(self)
+    }
+
+    // The ways in which a player's partial or complete board breaks the
+    // puzzle, line by line. A board without violations is a solution
+    // once it is complete.
+    pub fn check(&self, board: &Board<R>) -> Vec<Violation> {
+        let mut givens: Vec<(Position, Cell)> = self
+            .board
+            .cells()
+            .iter()
+            .filter(|(position, cell)| board.cells().get(position) != Some(cell))
+            .map(|(position, cell)| (*position, *cell))
+            .collect();
+        givens.sort_by_key(|(position, _cell)| position.coordinates());
+        let mut violations: Vec<Violation> = givens
+            .into_iter()
+            .map(|(position, cell)| Violation::Given(position, cell))
+            .collect();
+
+        for (key, segment) in board.normalized_segments() {
+            let segment: Vec<(Position, Option<Cell>)> = segment.collect();
+            let Some(clue) = self.clues.get(&key) else {
+                continue;
+            };
+
+            let mut exceeded = false;
+            for cell in Cell::all() {
+                let positions: Vec<Position> = segment
+                    .iter()
+                    .filter(|(_position, other)| *other == Some(cell))
+                    .map(|(position, _cell)| *position)
+                    .collect();
+
+                if positions.len() > clue.cell(cell) as usize {
+                    exceeded = true;
+                    violations.push(Violation::Exceeded {
+                        line: key,
+                        cell,
+                        clue: clue.cell(cell),
+                        positions,
+                    });
+                }
+            }
+
+            let cells: Option<Vec<Cell>> = segment.iter().map(|(_position, cell)| *cell).collect();
+            if let (false, Some(cells), Some(rule)) = (exceeded, cells, self.rules.get(&key)) {
+                if !rule.is_satisfied(&cells) {
+                    violations.push(Violation::Unsatisfied(key));
+                }
+            }
+        }
+
+        violations
     }
 }
 
 impl Display for Puzzle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let colors = self.palette.colors();
         for direction in Direction::normalized() {
             for _ in 0..self.board.hexagon().radius() * 3 + 1 {
                 f.write_char(' ')?;
@@ -85,29 +369,15 @@ impl Display for Puzzle {
                     f.write_char(' ')?;
                 }
                 for (_position, cell) in segment {
-                    use Cell::*;
-
-                    f.write_char(match cell {
-                        Some(Red) => 'R',
-                        Some(Green) => 'G',
-                        Some(Blue) => 'B',
-                        None => '?',
-                    })?;
+                    f.write_char(cell.map_or('?', |cell| cell.letter()))?;
                     f.write_char(' ')?;
                 }
 
-                let clue = self
-                    .clues
-                    .get(&(direction, distance))
-                    .cloned()
-                    .unwrap_or(Clue::zero());
-
-                f.write_str(&format!(
-                    "- ({} {} {})",
-                    clue.red(),
-                    clue.green(),
-                    clue.blue()
-                ))?;
+                let clue = match self.rules.get(&(direction, distance)) {
+                    Some(rule) => rule.describe(colors),
+                    None => FullClue::new(&[]).describe(colors),
+                };
+                f.write_str(&format!("- {clue}"))?;
 
                 f.write_char('\n')?;
             }
@@ -118,14 +388,196 @@ impl Display for Puzzle {
     }
 }
 
-pub type GeneratorFn<T> = Box<dyn Fn(&mut T) -> Puzzle>;
+// Checks that the region has no broken lines, for puzzles to be laid out
+// on it.
+pub(super) fn check_lines(region: &impl Region) -> Result<(), PuzzleError> {
+    match region.broken_line() {
+        Some((direction, distance)) => Err(PuzzleError::BrokenLine(direction, distance)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(feature = "generate")]
+pub type GeneratorFn<T, R = Hexagon> = Box<dyn Fn(&mut T) -> Puzzle<R> + Send + Sync>;
 
-pub trait Generator<T: Rng> {
-    fn generate(&self, rng: &mut T) -> Puzzle;
+#[cfg(feature = "generate")]
+pub trait Generator<T: Rng, R: Region = Hexagon> {
+    fn generate(&self, rng: &mut T) -> Puzzle<R>;
 }
 
-impl<T: Rng> Generator<T> for GeneratorFn<T> {
-    fn generate(&self, rng: &mut T) -> Puzzle {
+#[cfg(feature = "generate")]
+impl<T: Rng, R: Region> Generator<T, R> for GeneratorFn<T, R> {
+    fn generate(&self, rng: &mut T) -> Puzzle<R> {
         self(rng)
     }
 }
+
+#[cfg(feature = "generate")]
+impl<T: Rng, R: Region, G: Generator<T, R> + ?Sized> Generator<T, R> for &G {
+    fn generate(&self, rng: &mut T) -> Puzzle<R> {
+        (**self).generate(rng)
+    }
+}
+
+// Smooths the solution boards of another generator, reducing the
+// number of isolated cells that differ from all of their neighbors.
+#[cfg(feature = "generate")]
+pub struct Smoothed<G> {
+    generator: G,
+    iterations: usize,
+}
+
+#[cfg(feature = "generate")]
+impl<G> Smoothed<G> {
+    pub fn new(generator: G, iterations: usize) -> Self {
+        Smoothed {
+            generator,
+            iterations,
+        }
+    }
+}
+
+#[cfg(feature = "generate")]
+impl<T: Rng, R: Region, G: Generator<T, R>> Generator<T, R> for Smoothed<G> {
+    fn generate(&self, rng: &mut T) -> Puzzle<R> {
+        let puzzle = self.generator.generate(rng);
+        Puzzle::with_clues_unchecked(puzzle.board().smoothed(self.iterations))
+            .with_palette(puzzle.palette())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::masked::MaskedHexagon;
+    use crate::grid::ring::Ring;
+    use crate::puzzle::solver::Solver;
+    use crate::puzzle::Palette;
+
+    #[test]
+    fn check() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        assert!(puzzle.check(&board).is_empty());
+        puzzle.clear();
+        assert!(puzzle.check(&Board::new(2).unwrap()).is_empty());
+
+        // A second red cell on the middle row is one too many for it, and
+        // the other lines through it have no red cells at all.
+        let mut partial = Board::new(2).unwrap();
+        partial.insert(Position::zero(), Cell::Red);
+        partial.insert(Direction::XY.position(), Cell::Red);
+        let violations = puzzle.check(&partial);
+        assert_eq!(3, violations.len());
+        assert!(violations.contains(&Violation::Exceeded {
+            line: (Direction::XY, 0),
+            cell: Cell::Red,
+            clue: 1,
+            positions: vec![Position::zero(), Direction::XY.position()],
+        }));
+
+        // Swapping two cells of a row keeps its counts, but breaks the
+        // lines across it.
+        let mut swapped = board.clone();
+        swapped.insert(Position::zero(), Cell::Green);
+        swapped.insert(Direction::XY.position(), Cell::Red);
+        assert!(!puzzle.check(&swapped).is_empty());
+
+        puzzle.mut_board().insert(Position::zero(), Cell::Red);
+        assert_eq!(
+            vec![Violation::Given(Position::zero(), Cell::Red)],
+            puzzle.check(&Board::new(2).unwrap())
+        );
+    }
+
+    #[test]
+    fn display() {
+        let mut board = Board::new(1).unwrap();
+        board.insert(Position::zero(), Cell::Red);
+        for position in Ring::zero(1).unwrap() {
+            board.insert(position, Cell::Green);
+        }
+
+        // Clues show the counts of every color of the palette, and no
+        // other.
+        let puzzle = Puzzle::with_clues(board);
+        assert!(puzzle.to_string().contains("- (1 2 0)\n"));
+        let puzzle = puzzle.with_palette(Palette::new(2).unwrap());
+        assert!(puzzle.to_string().contains("- (1 2)\n"));
+        let puzzle = puzzle.with_palette(Palette::new(5).unwrap());
+        assert!(puzzle.to_string().contains("- (1 2 0 0 0)\n"));
+    }
+
+    #[test]
+    fn set_clue_kind() {
+        let board = Board::rings_fixture();
+        let key = (Direction::XY, 0);
+        let line: Vec<Cell> = board
+            .segment(0, Direction::XY)
+            .unwrap()
+            .filter_map(|(_position, cell)| cell)
+            .collect();
+
+        // Once cleared, the puzzle's board no longer tells how the cells
+        // of its lines are laid out, but still has their counts.
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+        for kind in [ClueKind::Full, ClueKind::Totals] {
+            puzzle.set_clue_kind(key, kind).unwrap();
+            assert!(puzzle.clue_rules()[&key].is_satisfied(&line));
+            assert!(!puzzle.clue_rules()[&key].is_satisfied(&line[1..]));
+        }
+        for kind in [ClueKind::Ordered, ClueKind::Contiguous] {
+            assert_eq!(
+                Err(ClueKindError::UnsolvedLine(Direction::XY, 0)),
+                puzzle.set_clue_kind(key, kind)
+            );
+        }
+
+        let mut solution = Puzzle::with_clues(board);
+        solution.set_clue_kind(key, ClueKind::Ordered).unwrap();
+        assert!(solution.clue_rules()[&key].is_satisfied(&line));
+        let mut swapped = line.clone();
+        swapped.swap(3, 4);
+        assert!(!solution.clue_rules()[&key].is_satisfied(&swapped));
+
+        solution.remove_clue_rule(key);
+        assert!(solution.set_clue_kind(key, ClueKind::Full).is_ok());
+        assert_eq!(
+            Err(ClueKindError::MissingClue(Direction::XY, 5)),
+            solution.set_clue_kind((Direction::XY, 5), ClueKind::Full)
+        );
+    }
+
+    #[test]
+    fn masked() {
+        let hexagon = Hexagon::zero(2).unwrap();
+
+        // A hole on a corner of the hexagon only shortens the lines
+        // through it, which the puzzle and its solutions follow.
+        let board =
+            Board::rings_fixture_in(MaskedHexagon::new(hexagon, [Direction::XY.position() * 2]));
+        let mut puzzle = Puzzle::with_clues_in(board.clone()).unwrap();
+        assert!(puzzle.check(&board).is_empty());
+        puzzle.clear();
+        assert!(puzzle.check(&board).is_empty());
+        let solutions: Vec<_> = Solver::new(puzzle).solutions().collect();
+        assert!(solutions
+            .iter()
+            .any(|solution| solution.cells() == board.cells()));
+        assert!(solutions
+            .iter()
+            .all(|solution| solution.clues().eq(board.clues())));
+
+        // A hole in the center breaks the middle lines in two.
+        let board = Board::rings_fixture_in(MaskedHexagon::new(hexagon, [Position::zero()]));
+        assert_eq!(
+            Some(PuzzleError::BrokenLine(Direction::XY, 0)),
+            Puzzle::with_clues_in(board.clone()).err()
+        );
+        assert_eq!(
+            Some(PuzzleError::BrokenLine(Direction::XY, 0)),
+            Puzzle::new_in(board.clone(), board.clues()).err()
+        );
+    }
+}
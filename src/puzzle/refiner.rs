@@ -1,100 +1,1382 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::mem;
+use std::ops::{Deref, Range};
+#[cfg(feature = "rayon")]
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "rayon")]
+use rayon::iter::ParallelIterator;
 
+use crate::grid::hexagon::Hexagon;
+use crate::grid::region::Region;
 use crate::grid::{Direction, Distance, Position};
 
 use super::{
+    difficulty::Difficulty,
+    optimizer::{Annealer, Objective},
     puzzle::{Generator, Puzzle},
-    solver::Solver,
+    solver::{Solver, SolverWorkspace},
+    strategy::{self, LowestClue, RefineStrategy},
     validator::Validator,
-    Cell, Clue,
+    Cell, ClueKind,
 };
 
+// Receives the progress of a refiner as it refines puzzles, on whichever
+// thread is refining them. Events that are not handled are ignored.
+pub trait RefinerEvents<R: Region = Hexagon>: Send + Sync {
+    // A solution was generated and is about to be refined.
+    fn attempt_started(&self) {}
+
+    // A puzzle was given up on because the named validator strategy did
+    // not find it valid. Puzzles outside of the refiner's difficulty range
+    // are rejected as `difficulty`, and those whose clues the solver
+    // cannot compute, such as puzzles missing the clue of a line, as
+    // `malformed`.
+    fn candidate_rejected(&self, _strategy: &'static str) {}
+
+    // A cell of the solution was revealed for the solver to go on.
+    fn given_added(&self, _position: Position, _cell: Cell) {}
+
+    // The clue of a line was strengthened to the given kind for the
+    // solver to go on.
+    fn clue_strengthened(&self, _key: (Direction, Distance), _kind: ClueKind) {}
+
+    // The solver took a pass with the named technique.
+    fn pass_taken(&self, _technique: &'static str) {}
+
+    // A puzzle was refined, validated and polished.
+    fn puzzle_accepted(&self, _puzzle: &Puzzle<R>) {}
+}
+
+// Ignores every event, for refiners without an observer.
+struct NoEvents;
+
+impl<R: Region> RefinerEvents<R> for NoEvents {}
+
+// What it took to refine a puzzle, for tuning validators and documenting
+// batches of puzzles.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GenerationStats {
+    attempts: usize,
+    rejections: HashMap<&'static str, usize>,
+    givens: usize,
+    strengthened: usize,
+    passes: usize,
+    elapsed: Duration,
+}
+
+impl GenerationStats {
+    // The number of solutions generated to be refined.
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    // The number of puzzles each validator strategy rejected, by name.
+    pub fn rejections(&self) -> &HashMap<&'static str, usize> {
+        &self.rejections
+    }
+
+    pub fn rejected_by(&self, strategy: &str) -> usize {
+        self.rejections.get(strategy).copied().unwrap_or(0)
+    }
+
+    // The number of cells revealed over all attempts, including those
+    // of puzzles that were rejected.
+    pub fn givens(&self) -> usize {
+        self.givens
+    }
+
+    // The number of clues strengthened over all attempts.
+    pub fn strengthened(&self) -> usize {
+        self.strengthened
+    }
+
+    // The number of passes the solver took over all attempts.
+    pub fn passes(&self) -> usize {
+        self.passes
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+impl Display for GenerationStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} attempts, {} givens added, {} solver passes in {:.2?}",
+            self.attempts, self.givens, self.passes, self.elapsed
+        )?;
+
+        let mut rejections: Vec<_> = self.rejections.iter().collect();
+        rejections.sort();
+        for (strategy, count) in rejections {
+            write!(f, ", {count} rejected by {strategy}")?;
+        }
+
+        Ok(())
+    }
+}
+
+// Counts the events of refinements into statistics, passing them on to
+// the refiner's own observer.
+struct StatsCollector<'a, R: Region> {
+    stats: Mutex<GenerationStats>,
+    events: &'a dyn RefinerEvents<R>,
+}
+
+impl<R: Region> RefinerEvents<R> for StatsCollector<'_, R> {
+    fn attempt_started(&self) {
+        self.stats.lock().unwrap().attempts += 1;
+        self.events.attempt_started();
+    }
+
+    fn candidate_rejected(&self, strategy: &'static str) {
+        *self
+            .stats
+            .lock()
+            .unwrap()
+            .rejections
+            .entry(strategy)
+            .or_default() += 1;
+        self.events.candidate_rejected(strategy);
+    }
+
+    fn given_added(&self, position: Position, cell: Cell) {
+        self.stats.lock().unwrap().givens += 1;
+        self.events.given_added(position, cell);
+    }
+
+    fn clue_strengthened(&self, key: (Direction, Distance), kind: ClueKind) {
+        self.stats.lock().unwrap().strengthened += 1;
+        self.events.clue_strengthened(key, kind);
+    }
+
+    fn pass_taken(&self, technique: &'static str) {
+        self.stats.lock().unwrap().passes += 1;
+        self.events.pass_taken(technique);
+    }
+
+    fn puzzle_accepted(&self, puzzle: &Puzzle<R>) {
+        self.events.puzzle_accepted(puzzle);
+    }
+}
+
 // Attempts to refine a solution into a puzzle that meets the criteria
 // of the given validator.
-pub struct Refiner {
-    validator: Validator,
+pub struct Refiner<R: Region = Hexagon> {
+    validator: Validator<R>,
+    beam_width: Option<usize>,
+    annealer: Option<Annealer<R>>,
+    minimize: bool,
+    strengthen: bool,
+    difficulty: Option<Range<f64>>,
+    objective: Objective,
+    strategy: Arc<dyn RefineStrategy<R>>,
+    events: Option<Arc<dyn RefinerEvents<R>>>,
+}
+
+// A partially refined puzzle, advanced by the solver up to the point
+// where it can no longer make progress without another given cell.
+#[derive(Clone)]
+struct Candidate<R: Region> {
+    puzzle: Puzzle<R>,
+    solver: Solver<R>,
+    passes: f64,
+}
+
+impl<R: Region> Candidate<R> {
+    fn new(puzzle: Puzzle<R>, events: &dyn RefinerEvents<R>) -> Self {
+        let mut candidate = Candidate {
+            solver: Solver::new(puzzle.clone()),
+            puzzle,
+            passes: 0.0,
+        };
+
+        candidate.advance(events);
+        candidate
+    }
+
+    // Solves as much as possible, tracking the passes needed in the
+    // same way difficulty ratings do.
+    fn advance(&mut self, events: &dyn RefinerEvents<R>) {
+        while let Some(technique) = self.solver.step() {
+            self.passes += technique.cost();
+            events.pass_taken(technique.name());
+        }
+    }
+
+    fn is_solved(&self) -> bool {
+        self.solver.solution().is_solved()
+    }
+
+    fn reveal(&mut self, position: Position, cell: Cell) {
+        self.puzzle.mut_board().insert(position, cell);
+        self.solver.reveal(position, cell);
+    }
+
+    // Evaluates the candidate using the passes needed so far as its
+    // projected difficulty.
+    fn score(&self, objective: &Objective) -> f64 {
+        objective.evaluate_with(&self.puzzle, self.passes)
+    }
+
+    fn givens(&self) -> Vec<Position> {
+        let mut givens: Vec<Position> = self.puzzle.board().cells().keys().copied().collect();
+        givens.sort_by_key(|position| position.coordinates());
+        givens
+    }
+}
+
+// Limits how long a refiner keeps attempting to refine a puzzle. The
+// limits are checked between attempts, so an attempt that has started
+// always runs to its end. A budget without limits never runs out.
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    attempts: Option<usize>,
+    duration: Option<Duration>,
+    cancelled: Option<Arc<AtomicBool>>,
+}
+
+impl Budget {
+    pub fn unlimited() -> Self {
+        Budget::default()
+    }
+
+    // Gives up after the given number of attempts.
+    pub fn with_attempts(mut self, attempts: usize) -> Self {
+        self.attempts = Some(attempts);
+        self
+    }
+
+    // Gives up once the given time has passed since refining started.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    // Gives up once the given flag is set, from any thread.
+    pub fn with_cancellation(mut self, cancelled: Arc<AtomicBool>) -> Self {
+        self.cancelled = Some(cancelled);
+        self
+    }
+
+    pub fn attempts(&self) -> Option<usize> {
+        self.attempts
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+            .as_ref()
+            .is_some_and(|cancelled| cancelled.load(Ordering::Relaxed))
+    }
+
+    fn is_exhausted(&self, attempts: usize, start: Instant) -> bool {
+        self.attempts.is_some_and(|limit| attempts >= limit)
+            || self.duration.is_some_and(|limit| start.elapsed() >= limit)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RefineError {
+    // The budget ran out after the given number of attempts.
+    Exhausted(usize),
+    // The budget was cancelled after the given number of attempts.
+    Cancelled(usize),
+}
+
+impl Display for RefineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RefineError::Exhausted(attempts) => {
+                write!(f, "no valid puzzle found within {attempts} attempts")
+            }
+            RefineError::Cancelled(attempts) => {
+                write!(f, "refining cancelled after {attempts} attempts")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RefineError {}
+
+// Configures the background threads that refine puzzles for a stream,
+// and the generator of the solutions they refine.
+pub struct StreamConfig<G> {
+    generator: G,
+    threads: usize,
+    buffer: usize,
 }
 
-impl Refiner {
-    pub fn new(validator: Validator) -> Self {
-        Refiner { validator }
+impl<G> StreamConfig<G> {
+    // Refines puzzles on a thread for every available core, keeping as
+    // many of them ready.
+    pub fn new(generator: G) -> Self {
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        StreamConfig {
+            generator,
+            threads,
+            buffer: threads,
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    // The buffer is the number of refined puzzles that are kept ready
+    // in the stream. Once it is full, the threads wait for puzzles to
+    // be received before refining more of them.
+    pub fn with_buffer(mut self, buffer: usize) -> Self {
+        self.buffer = buffer;
+        self
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn buffer(&self) -> usize {
+        self.buffer
+    }
+}
+
+// The receiving end of a stream of refined puzzles, which dereferences to
+// the channel they are delivered through. Dropping it stops the threads
+// refining them once they are done with their current attempts.
+pub struct PuzzleStream<R: Region = Hexagon> {
+    receiver: Receiver<Puzzle<R>>,
+    stopped: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl<R: Region> PuzzleStream<R> {
+    // Stops the threads refining puzzles, waiting for them to be done
+    // with their current attempts.
+    pub fn stop(mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        // Threads waiting for room in the buffer are let go by dropping
+        // the channel, in place of which is left one that is never used.
+        drop(mem::replace(&mut self.receiver, sync_channel(0).1));
+        for thread in mem::take(&mut self.threads) {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<R: Region> Deref for PuzzleStream<R> {
+    type Target = Receiver<Puzzle<R>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.receiver
+    }
+}
+
+impl<R: Region> Drop for PuzzleStream<R> {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<R: Region> Refiner<R> {
+    pub fn new(validator: Validator<R>) -> Self {
+        Refiner {
+            validator,
+            beam_width: None,
+            annealer: None,
+            minimize: false,
+            strengthen: false,
+            difficulty: None,
+            objective: Objective::default(),
+            strategy: Arc::new(LowestClue),
+            events: None,
+        }
+    }
+
+    // Instead of greedily revealing a single cell whenever the solver
+    // stalls, keeps the best refinements out of several possible
+    // reveals, up to the given number of them. Among the refinements
+    // that solve the puzzle, the one that scores best on the refiner's
+    // objective is picked. This finds better puzzles at the cost of
+    // solving many more partial puzzles.
+    pub fn with_beam_search(mut self, width: usize) -> Self {
+        self.beam_width = Some(width.max(1));
+        self
+    }
+
+    // Sets the objective that refinements are ranked by when searching
+    // for the best of them. By default, more difficult puzzles with
+    // fewer givens are preferred.
+    pub fn with_objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
     }
 
-    pub fn refined<T: Rng>(&self, rng: &mut T, generator: impl Generator<T>) -> Puzzle {
+    // Polishes every refined puzzle with the given annealer.
+    pub fn with_annealer(mut self, annealer: Annealer<R>) -> Self {
+        self.annealer = Some(annealer);
+        self
+    }
+
+    // Removes the givens that every refined puzzle can do without before
+    // polishing it, as with `minimized`.
+    pub fn with_minimization(mut self) -> Self {
+        self.minimize = true;
+        self
+    }
+
+    // Whenever the solver stalls, strengthens the clue of a line to a
+    // kind that reveals more about it, if that lets the solver go on,
+    // before revealing a cell. This keeps puzzles with fewer givens, or
+    // none at all, at the cost of clues of mixed kinds. Beam search
+    // only ever reveals cells.
+    pub fn with_clue_strengthening(mut self) -> Self {
+        self.strengthen = true;
+        self
+    }
+
+    // Only accepts puzzles whose difficulty rating falls within the
+    // range. Rather than throwing away a refined puzzle outside of it,
+    // its givens are minimized when it is too easy, and cells of its
+    // solution are revealed when it is too difficult, before another
+    // solution is generated.
+    pub fn with_difficulty(mut self, range: Range<f64>) -> Self {
+        self.difficulty = Some(range);
+        self
+    }
+
+    // Sets the strategy that picks the cell to reveal whenever the solver
+    // stalls, which is `LowestClue` by default. Beam search considers
+    // cells of its own instead.
+    pub fn with_strategy(mut self, strategy: Arc<dyn RefineStrategy<R>>) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    // Reports the progress of every refinement to the given observer.
+    pub fn with_events(mut self, events: Arc<dyn RefinerEvents<R>>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    fn events(&self) -> &dyn RefinerEvents<R> {
+        self.events.as_deref().unwrap_or(&NoEvents)
+    }
+
+    pub fn refined<T: Rng>(&self, rng: &mut T, generator: impl Generator<T, R>) -> Puzzle<R> {
+        self.refined_while(rng, generator, || true).unwrap()
+    }
+
+    // Like `refined`, but keeps refining puzzles until one can be brought
+    // within the range of difficulty, the way `with_difficulty` does for
+    // every puzzle of the refiner.
+    pub fn refined_with_difficulty<T: Rng>(
+        &self,
+        rng: &mut T,
+        generator: impl Generator<T, R>,
+        range: Range<f64>,
+    ) -> Puzzle<R> {
+        loop {
+            let puzzle = self.refined(rng, &generator);
+            match self.fit_difficulty(puzzle, &range) {
+                Some(puzzle) => return puzzle,
+                None => self.events().candidate_rejected("difficulty"),
+            }
+        }
+    }
+
+    // Refines the given number of puzzles, at least one, and returns the
+    // one that scores highest, such as the most difficult one or the one
+    // with the fewest givens. Ties go to the puzzle refined first.
+    pub fn refined_best<T: Rng>(
+        &self,
+        rng: &mut T,
+        generator: impl Generator<T, R>,
+        count: usize,
+        score: impl Fn(&Puzzle<R>) -> f64,
+    ) -> Puzzle<R> {
+        let mut best = self.refined(rng, &generator);
+        let mut best_score = score(&best);
+
+        for _ in 1..count {
+            let puzzle = self.refined(rng, &generator);
+            let puzzle_score = score(&puzzle);
+            if puzzle_score > best_score {
+                best = puzzle;
+                best_score = puzzle_score;
+            }
+        }
+
+        best
+    }
+
+    // Like `refined`, but checks the given condition before each attempt,
+    // giving up on refining a puzzle once it no longer holds.
+    pub fn refined_while<T: Rng>(
+        &self,
+        rng: &mut T,
+        generator: impl Generator<T, R>,
+        condition: impl FnMut() -> bool,
+    ) -> Option<Puzzle<R>> {
+        self.refined_observed(rng, generator, condition, self.events())
+    }
+
+    fn refined_observed<T: Rng>(
+        &self,
+        rng: &mut T,
+        generator: impl Generator<T, R>,
+        mut condition: impl FnMut() -> bool,
+        events: &dyn RefinerEvents<R>,
+    ) -> Option<Puzzle<R>> {
         let mut refined = None;
+        let mut workspace = SolverWorkspace::new();
 
         while refined.is_none() {
-            let solution = generator.generate(rng);
-            refined = self.refine(solution);
+            if !condition() {
+                return None;
+            }
+
+            refined = self.attempt(rng, &generator, &mut workspace, events);
+        }
+
+        refined
+    }
+
+    // Like `refined`, but gives up once the budget runs out, so that an
+    // unsatisfiable validator cannot keep it refining forever.
+    pub fn refined_within<T: Rng>(
+        &self,
+        rng: &mut T,
+        generator: impl Generator<T, R>,
+        budget: &Budget,
+    ) -> Result<Puzzle<R>, RefineError> {
+        self.refined_within_observed(rng, generator, budget, self.events())
+    }
+
+    // Like `refined_within`, but also tells what it took to refine the
+    // puzzle, or to run out of budget.
+    pub fn refined_with_stats<T: Rng>(
+        &self,
+        rng: &mut T,
+        generator: impl Generator<T, R>,
+        budget: &Budget,
+    ) -> (Result<Puzzle<R>, RefineError>, GenerationStats) {
+        let start = Instant::now();
+        let collector = StatsCollector {
+            stats: Mutex::new(GenerationStats::default()),
+            events: self.events(),
+        };
+
+        let refined = self.refined_within_observed(rng, generator, budget, &collector);
+
+        let mut stats = collector.stats.into_inner().unwrap();
+        stats.elapsed = start.elapsed();
+        (refined, stats)
+    }
+
+    fn refined_within_observed<T: Rng>(
+        &self,
+        rng: &mut T,
+        generator: impl Generator<T, R>,
+        budget: &Budget,
+        events: &dyn RefinerEvents<R>,
+    ) -> Result<Puzzle<R>, RefineError> {
+        let start = Instant::now();
+        let mut attempts = 0;
+        let mut cancelled = false;
+
+        let condition = || {
+            cancelled = budget.is_cancelled();
+            if cancelled || budget.is_exhausted(attempts, start) {
+                return false;
+            }
+
+            attempts += 1;
+            true
+        };
+        let refined = self.refined_observed(rng, generator, condition, events);
+
+        match refined {
+            Some(puzzle) => Ok(puzzle),
+            None if cancelled => Err(RefineError::Cancelled(attempts)),
+            None => Err(RefineError::Exhausted(attempts)),
+        }
+    }
+
+    // Refines puzzles from the generator on rayon's thread pool, each of
+    // its threads generating solutions with a random generator of its
+    // own, and returns the first of them that validates.
+    #[cfg(feature = "rayon")]
+    pub fn par_refined<G>(&self, generator: G) -> Puzzle<R>
+    where
+        G: Generator<StdRng, R> + Sync,
+    {
+        rayon::iter::repeat(())
+            .map_init(
+                || (StdRng::from_entropy(), SolverWorkspace::new()),
+                |(rng, workspace), ()| self.attempt(rng, &generator, workspace, self.events()),
+            )
+            .find_map_any(|refined| refined)
+            .unwrap()
+    }
+
+    // Like `par_refined`, but gives up once the budget runs out. Attempts
+    // that have started by then still run to their end on their threads.
+    #[cfg(feature = "rayon")]
+    pub fn par_refined_within<G>(
+        &self,
+        generator: G,
+        budget: &Budget,
+    ) -> Result<Puzzle<R>, RefineError>
+    where
+        G: Generator<StdRng, R> + Sync,
+    {
+        let start = Instant::now();
+        let attempts = AtomicUsize::new(0);
+
+        rayon::iter::repeat(())
+            .map_init(
+                || (StdRng::from_entropy(), SolverWorkspace::new()),
+                |(rng, workspace), ()| {
+                    let attempt = attempts.fetch_add(1, Ordering::Relaxed);
+                    if budget.is_cancelled() {
+                        Some(Err(RefineError::Cancelled(attempt)))
+                    } else if budget.is_exhausted(attempt, start) {
+                        Some(Err(RefineError::Exhausted(attempt)))
+                    } else {
+                        self.attempt(rng, &generator, workspace, self.events())
+                            .map(Ok)
+                    }
+                },
+            )
+            .find_map_any(|refined| refined)
+            .unwrap()
+    }
+
+    // Generates a solution and refines it, minimizing its givens,
+    // polishing the puzzle with the refiner's annealer and bringing it
+    // within the refiner's range of difficulty if it has them.
+    fn attempt<T: Rng>(
+        &self,
+        rng: &mut T,
+        generator: &impl Generator<T, R>,
+        workspace: &mut SolverWorkspace,
+        events: &dyn RefinerEvents<R>,
+    ) -> Option<Puzzle<R>> {
+        events.attempt_started();
+        let solution = generator.generate(rng);
+
+        let puzzle = self
+            .refine_observed(&solution, workspace, events)
+            .map(|puzzle| match self.minimize {
+                true => self.minimized(puzzle),
+                false => puzzle,
+            })
+            .map(|puzzle| match &self.annealer {
+                Some(annealer) => annealer.optimize(rng, puzzle, solution.board()),
+                None => puzzle,
+            })?;
+
+        let puzzle = match &self.difficulty {
+            Some(range) => match self.fit_difficulty(puzzle, range) {
+                Some(puzzle) => puzzle,
+                None => {
+                    events.candidate_rejected("difficulty");
+                    return None;
+                }
+            },
+            None => puzzle,
+        };
+
+        events.puzzle_accepted(&puzzle);
+        Some(puzzle)
+    }
+
+    // Brings the difficulty of the puzzle within the range, if it can be
+    // while the puzzle stays valid. Puzzles that are too easy have their
+    // givens minimized, and those that are too difficult have the cells
+    // the solver places last revealed until they are not.
+    fn fit_difficulty(&self, puzzle: Puzzle<R>, range: &Range<f64>) -> Option<Puzzle<R>> {
+        let mut difficulty = Difficulty::rate(&puzzle)?.value();
+        let mut puzzle = match difficulty < range.start {
+            true => {
+                let minimized = self.minimized(puzzle);
+                difficulty = Difficulty::rate(&minimized)?.value();
+                minimized
+            }
+            false => puzzle,
+        };
+
+        while difficulty >= range.end {
+            let mut solver = Solver::new(puzzle.clone());
+            solver.solve();
+            let last = solver.trace().last()?;
+
+            puzzle.mut_board().insert(last.position(), last.cell());
+            if !self.validator.is_valid(&puzzle) {
+                return None;
+            }
+            difficulty = Difficulty::rate(&puzzle)?.value();
         }
 
-        refined.unwrap()
+        range.contains(&difficulty).then_some(puzzle)
+    }
+
+    // Whether the validator can still find the puzzle valid, reporting
+    // the strategy that rejects it otherwise.
+    fn accepts_partial(&self, puzzle: &Puzzle<R>, events: &dyn RefinerEvents<R>) -> bool {
+        let rejected = self.validator.invalidated_by(puzzle);
+        if let Some(strategy) = rejected {
+            events.candidate_rejected(strategy.name());
+        }
+        rejected.is_none()
+    }
+
+    // Whether the validator finds the puzzle valid, reporting the
+    // strategy that rejects it otherwise.
+    fn accepts(&self, puzzle: &Puzzle<R>, events: &dyn RefinerEvents<R>) -> bool {
+        let rejected = self.validator.not_validated_by(puzzle);
+        if let Some(strategy) = rejected {
+            events.candidate_rejected(strategy.name());
+        }
+        rejected.is_none()
+    }
+
+    // Refines puzzles on background threads, delivering them through the
+    // returned stream. The threads stop once the stream is dropped, which
+    // they check before every attempt, so that they do not keep on
+    // refining puzzles that no validator will accept.
+    pub fn spawn_stream<G>(self, config: StreamConfig<G>) -> PuzzleStream<R>
+    where
+        G: Generator<StdRng, R> + Send + Sync + 'static,
+    {
+        let (sender, receiver) = sync_channel(config.buffer);
+        let stopped = Arc::new(AtomicBool::new(false));
+        let refiner = Arc::new(self);
+        let generator = Arc::new(config.generator);
+
+        let threads = (0..config.threads)
+            .map(|_| {
+                let sender = sender.clone();
+                let stopped = Arc::clone(&stopped);
+                let refiner = Arc::clone(&refiner);
+                let generator = Arc::clone(&generator);
+
+                thread::spawn(move || {
+                    let mut rng = StdRng::from_entropy();
+                    let running = || !stopped.load(Ordering::Relaxed);
+
+                    while let Some(puzzle) = refiner.refined_while(&mut rng, &*generator, running) {
+                        if sender.send(puzzle).is_err() {
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        PuzzleStream {
+            receiver,
+            stopped,
+            threads,
+        }
+    }
+
+    // Removes the givens of the puzzle one at a time, keeping out each of
+    // them without which the puzzle still solves and validates. Givens
+    // revealed early often turn out to be redundant once later ones are
+    // revealed, which leaves puzzles easier than they need to be. No
+    // single given can be removed from the result, though removing
+    // several of them at once may still work.
+    pub fn minimized(&self, mut puzzle: Puzzle<R>) -> Puzzle<R> {
+        let mut givens: Vec<(Position, Cell)> = puzzle
+            .board()
+            .cells()
+            .iter()
+            .map(|(position, cell)| (*position, *cell))
+            .collect();
+        givens.sort_by_key(|(position, _cell)| position.coordinates());
+
+        for (position, cell) in givens {
+            puzzle.mut_board().remove(position);
+            if !Solver::new(puzzle.clone()).solve() || !self.validator.is_valid(&puzzle) {
+                puzzle.mut_board().insert(position, cell);
+            }
+        }
+
+        puzzle
+    }
+
+    pub fn refine(&self, solution: Puzzle<R>) -> Option<Puzzle<R>> {
+        self.refine_with(&solution, &mut SolverWorkspace::new())
     }
 
-    pub fn refine(&self, solution: Puzzle) -> Option<Puzzle> {
+    // Like `refine`, but solves with the buffers of the given workspace,
+    // so that successive attempts reuse them instead of allocating their
+    // own. The cleared copy of the solution held by the solver is the
+    // only copy of the board made along the way, with cells revealed to
+    // it directly.
+    pub fn refine_with(
+        &self,
+        solution: &Puzzle<R>,
+        workspace: &mut SolverWorkspace,
+    ) -> Option<Puzzle<R>> {
+        self.refine_observed(solution, workspace, self.events())
+    }
+
+    fn refine_observed(
+        &self,
+        solution: &Puzzle<R>,
+        workspace: &mut SolverWorkspace,
+        events: &dyn RefinerEvents<R>,
+    ) -> Option<Puzzle<R>> {
+        if let Some(width) = self.beam_width {
+            return self.refine_beam(solution, width, events);
+        }
+
         let mut puzzle = solution.clone();
         puzzle.clear();
-        let mut solver = Solver::new(puzzle.clone());
 
-        if !self.validator.is_not_invalid(puzzle.clone()) {
+        if !self.accepts_partial(&puzzle, events) {
             return None;
         }
 
-        while !solver.solve() {
-            self.solve_cell(&solution, &mut puzzle, &mut solver);
-            // if !self.validator.is_not_invalid(puzzle.clone()) {
+        let mut solver = Solver::with_workspace(puzzle, mem::take(workspace));
+        loop {
+            while let Some(technique) = solver.step() {
+                events.pass_taken(technique.name());
+            }
+            if solver.solution().is_solved() {
+                break;
+            }
+
+            if self.strengthen {
+                if let Some((key, kind)) = Self::strengthen_clue(solution, &mut solver) {
+                    events.clue_strengthened(key, kind);
+                    continue;
+                }
+            }
+
+            // Without a cell to reveal, the solution cannot be refined,
+            // and neither can one whose clues the solver cannot compute.
+            let (position, cell) = match self.strategy.select(solution, &solver) {
+                Ok(reveal) => reveal?,
+                Err(_error) => {
+                    events.candidate_rejected("malformed");
+                    return None;
+                }
+            };
+            solver.reveal(position, cell);
+            events.given_added(position, cell);
+            // if !self.validator.is_not_invalid(solver.puzzle()) {
             //     return None;
             // }
         }
 
-        if !self.validator.is_valid(puzzle.clone()) {
+        let (puzzle, reused) = solver.into_parts();
+        *workspace = reused;
+
+        if !self.accepts(&puzzle, events) {
             return None;
         }
 
         Some(puzzle)
     }
 
-    fn lowest_computed_clue(
-        computed_clues: HashMap<(Direction, Distance), Clue>,
-    ) -> Option<((Direction, Distance), Clue)> {
-        computed_clues
-            .iter()
+    // Strengthens the first clue, from those with the fewest remaining
+    // cells, whose line's rule as a stronger kind lets the solver solve
+    // anything more, returning its line and its new kind.
+    fn strengthen_clue(
+        solution: &Puzzle<R>,
+        solver: &mut Solver<R>,
+    ) -> Option<((Direction, Distance), ClueKind)> {
+        let mut computed_clues: Vec<_> = solver
+            .computed_clues()
+            .ok()?
+            .into_iter()
             .filter(|(_key, clue)| !clue.is_empty())
-            .min_by_key(|(_key, clue)| clue.count())
-            .map(|(key, clue)| (*key, *clue))
-    }
-
-    fn find_segment_unsolved_cell_position(
-        solution: &Puzzle,
-        solver: &Solver,
-        direction: Direction,
-        distance: Distance,
-        cell: Cell,
-    ) -> Option<Position> {
-        solution
-            .board()
-            .segment(distance, direction)
-            .unwrap()
-            .find(|(position, found_cell)| {
-                !solver.solution().cells().contains_key(position) && found_cell == &Some(cell)
-            })
-            .map(|(position, _)| position)
+            .collect();
+        computed_clues.sort_by_key(|((direction, distance), clue)| {
+            (clue.count(), direction.position().coordinates(), *distance)
+        });
+
+        for (key, _clue) in computed_clues {
+            let Some((kind, _cells)) = solver.puzzle().clue_rules().get(&key)?.kind() else {
+                continue;
+            };
+            let (direction, distance) = key;
+            let cells: Vec<Cell> = solution
+                .board()
+                .segment(distance, direction)?
+                .filter_map(|(_position, cell)| cell)
+                .collect();
+
+            for stronger in kind.stronger() {
+                let mut guess = solver.clone();
+                guess.mut_puzzle().set_clue_rule(key, stronger.rule(&cells));
+                if guess.step().is_some() {
+                    solver
+                        .mut_puzzle()
+                        .set_clue_rule(key, stronger.rule(&cells));
+                    return Some((key, *stronger));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn refine_beam(
+        &self,
+        solution: &Puzzle<R>,
+        width: usize,
+        events: &dyn RefinerEvents<R>,
+    ) -> Option<Puzzle<R>> {
+        let mut puzzle = solution.clone();
+        puzzle.clear();
+
+        if !self.accepts_partial(&puzzle, events) {
+            return None;
+        }
+
+        let mut beam = vec![Candidate::new(puzzle, events)];
+        let mut finished = vec![];
+
+        while !beam.is_empty() {
+            let mut seen = HashSet::new();
+            let mut expanded = vec![];
+
+            for candidate in beam {
+                if candidate.is_solved() {
+                    finished.push(candidate.puzzle);
+                    continue;
+                }
+
+                let Ok(reveals) = strategy::reveals(solution, &candidate.solver, width) else {
+                    events.candidate_rejected("malformed");
+                    return None;
+                };
+
+                for (position, cell) in reveals {
+                    let mut next = candidate.clone();
+                    next.reveal(position, cell);
+
+                    if seen.insert(next.givens()) {
+                        next.advance(events);
+                        expanded.push(next);
+                    }
+                }
+            }
+
+            expanded.sort_by(|a, b| {
+                b.score(&self.objective)
+                    .total_cmp(&a.score(&self.objective))
+            });
+            expanded.truncate(width);
+            beam = expanded;
+        }
+
+        let best = finished
+            .into_iter()
+            .filter(|puzzle| self.accepts(puzzle, events))
+            .filter_map(|puzzle| Some((self.objective.evaluate(&puzzle)?, puzzle)))
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_score, puzzle)| puzzle)?;
+
+        // The refinements that were not picked added givens too, but
+        // only the givens of the one that was are reported.
+        for (position, cell) in best.board().cells() {
+            events.given_added(*position, *cell);
+        }
+
+        Some(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::puzzle::board::Board;
+    use crate::puzzle::puzzle::GeneratorFn;
+    use crate::puzzle::validator::{MaximumSolvedPositions, ValidatorStrategy};
+
+    struct Unsatisfiable;
+
+    impl ValidatorStrategy for Unsatisfiable {
+        fn name(&self) -> &'static str {
+            "unsatisfiable"
+        }
+
+        fn is_valid(&self, _puzzle: &Puzzle) -> Option<bool> {
+            Some(false)
+        }
+    }
+
+    #[test]
+    fn refine() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let refiner = Refiner::new(Validator::new(vec![]));
+
+        let mut givens = 0;
+        for _ in 0..5 {
+            let solution = Puzzle::with_clues(Board::random(&mut rng, 3).unwrap());
+            let puzzle = refiner.refine(solution.clone()).unwrap();
+
+            // The cells revealed while refining are kept as givens, which
+            // is what lets the puzzle be solved on its own.
+            givens += puzzle.board().cells().len();
+            assert!(puzzle
+                .board()
+                .cells()
+                .iter()
+                .all(|(position, cell)| solution.board().cells().get(position) == Some(cell)));
+            let mut solver = Solver::new(puzzle);
+            assert!(solver.solve());
+            assert_eq!(solution.board().cells(), solver.solution().cells());
+        }
+        assert!(givens > 0);
+    }
+
+    #[test]
+    fn malformed() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let board = Board::random(&mut rng, 3).unwrap();
+        let clues = board
+            .clues()
+            .filter(|(key, _clue)| *key != (Direction::XY, 0));
+        let solution = Puzzle::new(board.clone(), clues);
+
+        for refiner in [
+            Refiner::new(Validator::new(vec![])),
+            Refiner::new(Validator::new(vec![])).with_beam_search(2),
+        ] {
+            let recorder = Arc::new(Recorder::default());
+            let refiner = refiner.with_events(Arc::clone(&recorder) as Arc<dyn RefinerEvents>);
+
+            assert!(refiner.refine(solution.clone()).is_none());
+            assert!(recorder
+                .0
+                .lock()
+                .unwrap()
+                .contains(&"malformed".to_string()));
+        }
+    }
+
+    #[test]
+    fn spawn_stream() {
+        let validator = Validator::new(vec![Box::new(MaximumSolvedPositions(7))]);
+        let generator: GeneratorFn<StdRng> = Board::generator(2).unwrap();
+        let config = StreamConfig::new(generator).with_threads(2).with_buffer(1);
+        let stream = Refiner::new(validator).spawn_stream(config);
+
+        for puzzle in stream.iter().take(3) {
+            assert!(puzzle.board().cells().len() <= 7);
+        }
+        stream.stop();
+    }
+
+    #[derive(Default)]
+    struct Attempts(AtomicUsize);
+
+    impl RefinerEvents for Attempts {
+        fn attempt_started(&self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // Threads refining puzzles that are never accepted stop once the
+    // stream is dropped, rather than spinning forever.
+    #[test]
+    fn spawn_stream_stops() {
+        let attempts = Arc::new(Attempts::default());
+        let refiner = Refiner::new(Validator::new(vec![Box::new(Unsatisfiable)]))
+            .with_events(Arc::clone(&attempts) as Arc<dyn RefinerEvents>);
+        let generator: GeneratorFn<StdRng> = Board::generator(1).unwrap();
+
+        let stream = refiner.spawn_stream(StreamConfig::new(generator).with_threads(2));
+        thread::sleep(Duration::from_millis(20));
+        drop(stream);
+
+        thread::sleep(Duration::from_millis(50));
+        let stopped = attempts.0.load(Ordering::Relaxed);
+        thread::sleep(Duration::from_millis(100));
+        assert!(stopped > 0);
+        assert_eq!(stopped, attempts.0.load(Ordering::Relaxed));
+
+        let refiner = Refiner::new(Validator::new(vec![Box::new(Unsatisfiable)]));
+        let generator: GeneratorFn<StdRng> = Board::generator(1).unwrap();
+        refiner
+            .spawn_stream(StreamConfig::new(generator).with_threads(2))
+            .stop();
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl RefinerEvents for Recorder {
+        fn attempt_started(&self) {
+            self.0.lock().unwrap().push("attempt".to_string());
+        }
+
+        fn candidate_rejected(&self, strategy: &'static str) {
+            self.0.lock().unwrap().push(strategy.to_string());
+        }
+
+        fn given_added(&self, _position: Position, _cell: Cell) {
+            self.0.lock().unwrap().push("given".to_string());
+        }
+
+        fn puzzle_accepted(&self, _puzzle: &Puzzle) {
+            self.0.lock().unwrap().push("accepted".to_string());
+        }
+    }
+
+    #[test]
+    fn events() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let generator: GeneratorFn<StdRng> = Board::generator(2).unwrap();
+        let recorder = Arc::new(Recorder::default());
+
+        let validator = Validator::new(vec![Box::new(Unsatisfiable)]);
+        let refiner = Refiner::new(validator).with_events(recorder.clone());
+        let budget = Budget::unlimited().with_attempts(2);
+        assert!(refiner
+            .refined_within(&mut rng, &generator, &budget)
+            .is_err());
+        assert_eq!(
+            vec!["attempt", "unsatisfiable", "attempt", "unsatisfiable"],
+            *recorder.0.lock().unwrap()
+        );
+
+        recorder.0.lock().unwrap().clear();
+        let validator = Validator::new(vec![Box::new(MaximumSolvedPositions(7))]);
+        let puzzle = Refiner::new(validator)
+            .with_events(recorder.clone())
+            .refined(&mut rng, &generator);
+
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(Some("accepted"), events.last().map(String::as_str));
+        let attempts = events.iter().filter(|event| *event == "attempt").count();
+        let rejections = events
+            .iter()
+            .filter(|event| *event == "maximum-solved-positions")
+            .count();
+        assert_eq!(attempts, rejections + 1);
+
+        let givens = events
+            .iter()
+            .rev()
+            .take_while(|event| *event != "attempt")
+            .filter(|event| *event == "given")
+            .count();
+        assert_eq!(puzzle.board().cells().len(), givens);
+    }
+
+    #[test]
+    fn refined_with_stats() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let generator: GeneratorFn<StdRng> = Board::generator(2).unwrap();
+
+        let validator = Validator::new(vec![Box::new(Unsatisfiable)]);
+        let budget = Budget::unlimited().with_attempts(3);
+        let (refined, stats) =
+            Refiner::new(validator).refined_with_stats(&mut rng, &generator, &budget);
+        assert!(refined.is_err());
+        assert_eq!(3, stats.attempts());
+        assert_eq!(3, stats.rejected_by("unsatisfiable"));
+        assert_eq!(0, stats.givens());
+
+        let validator = Validator::new(vec![Box::new(MaximumSolvedPositions(7))]);
+        let (refined, stats) = Refiner::new(validator).refined_with_stats(
+            &mut rng,
+            &generator,
+            &budget.with_attempts(1000),
+        );
+        let puzzle = refined.unwrap();
+        assert_eq!(
+            stats.attempts(),
+            stats.rejected_by("maximum-solved-positions") + 1
+        );
+        assert!(stats.givens() >= puzzle.board().cells().len());
+        assert!(stats.passes() > 0);
+        assert!(stats
+            .to_string()
+            .starts_with(&format!("{} attempts", stats.attempts())));
     }
 
-    fn solve_cell(&self, solution: &Puzzle, _puzzle: &mut Puzzle, solver: &mut Solver) {
-        let computed_clues = solver.computed_clues();
-        let ((direction, distance), clue) = Self::lowest_computed_clue(computed_clues).unwrap();
+    #[test]
+    fn refined_within() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let generator: GeneratorFn<StdRng> = Board::generator(2).unwrap();
+        let refiner = Refiner::new(Validator::new(vec![Box::new(Unsatisfiable)]));
+
+        let budget = Budget::unlimited().with_attempts(5);
+        assert_eq!(
+            Some(RefineError::Exhausted(5)),
+            refiner.refined_within(&mut rng, &generator, &budget).err()
+        );
+
+        let budget = Budget::unlimited().with_duration(Duration::from_millis(20));
+        assert!(matches!(
+            refiner.refined_within(&mut rng, &generator, &budget),
+            Err(RefineError::Exhausted(_))
+        ));
+
+        let cancelled = Arc::new(AtomicBool::new(true));
+        let budget = Budget::unlimited().with_cancellation(cancelled);
+        assert_eq!(
+            Some(RefineError::Cancelled(0)),
+            refiner.refined_within(&mut rng, &generator, &budget).err()
+        );
+
+        let validator = Validator::new(vec![Box::new(MaximumSolvedPositions(7))]);
+        let budget = Budget::unlimited().with_attempts(1000);
+        let puzzle = Refiner::new(validator)
+            .refined_within(&mut rng, &generator, &budget)
+            .unwrap();
+        assert!(puzzle.board().cells().len() <= 7);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_refined() {
+        let validator = Validator::new(vec![Box::new(MaximumSolvedPositions(7))]);
+        let generator: GeneratorFn<StdRng> = Board::generator(2).unwrap();
+
+        let puzzle = Refiner::new(validator).par_refined(&generator);
+        assert!(puzzle.board().cells().len() <= 7);
+        assert!(Solver::new(puzzle).solve());
+
+        let refiner = Refiner::new(Validator::new(vec![Box::new(Unsatisfiable)]));
+        let budget = Budget::unlimited().with_attempts(5);
+        assert!(matches!(
+            refiner.par_refined_within(&generator, &budget),
+            Err(RefineError::Exhausted(_))
+        ));
+    }
+
+    #[test]
+    fn minimized() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let refiner = Refiner::new(Validator::new(vec![]));
+
+        for _ in 0..5 {
+            let solution = Puzzle::with_clues(Board::random(&mut rng, 3).unwrap());
+            let refined = refiner.refine(solution.clone()).unwrap();
+            let puzzle = refiner.minimized(refined.clone());
+
+            assert!(puzzle.board().cells().len() <= refined.board().cells().len());
+            let mut solver = Solver::new(puzzle.clone());
+            assert!(solver.solve());
+            assert_eq!(solution.board().cells(), solver.solution().cells());
+
+            for position in puzzle.board().cells().keys() {
+                let mut fewer = puzzle.clone();
+                fewer.mut_board().remove(*position);
+                assert!(!Solver::new(fewer).solve());
+            }
+        }
+    }
 
-        let max_cell = clue.max_cell().unwrap();
-        let position = Self::find_segment_unsolved_cell_position(
-            solution, solver, direction, distance, max_cell,
-        )
-        .unwrap();
+    #[test]
+    fn clue_strengthening() {
+        let mut rng = StdRng::seed_from_u64(17);
+        let refiner = Refiner::new(Validator::new(vec![]));
+        let strengthening = Refiner::new(Validator::new(vec![])).with_clue_strengthening();
 
-        // Add that cell to the puzzle
-        solver.mut_puzzle().mut_board().insert(position, max_cell);
-        solver.mut_solution().insert(position, max_cell);
+        let mut strengthened = 0;
+        for _ in 0..5 {
+            let solution = Puzzle::with_clues(Board::random(&mut rng, 3).unwrap());
+            let refined = refiner.refine(solution.clone()).unwrap();
+            let puzzle = strengthening.refine(solution.clone()).unwrap();
+
+            assert!(puzzle.board().cells().len() <= refined.board().cells().len());
+            let mut solver = Solver::new(puzzle.clone());
+            assert!(solver.solve());
+            assert_eq!(solution.board().cells(), solver.solution().cells());
+
+            strengthened += puzzle
+                .clue_rules()
+                .values()
+                .filter(|rule| rule.kind().map(|(kind, _cells)| kind) != Some(ClueKind::Full))
+                .count();
+        }
+        assert!(strengthened > 0);
+    }
+
+    #[test]
+    fn difficulty() {
+        let mut rng = StdRng::seed_from_u64(19);
+        let generator: GeneratorFn<StdRng> = Board::generator(3).unwrap();
+        let budget = Budget::unlimited().with_attempts(1000);
+
+        for range in [0.0..4.0, 8.0..12.0] {
+            let refiner = Refiner::new(Validator::new(vec![])).with_difficulty(range.clone());
+            let (refined, stats) = refiner.refined_with_stats(&mut rng, &generator, &budget);
+
+            let difficulty = Difficulty::rate(&refined.unwrap()).unwrap().value();
+            assert!(range.contains(&difficulty));
+            assert_eq!(stats.attempts(), stats.rejected_by("difficulty") + 1);
+        }
+    }
+
+    #[test]
+    fn refined_with_difficulty() {
+        let mut rng = StdRng::seed_from_u64(19);
+        let generator: GeneratorFn<StdRng> = Board::generator(3).unwrap();
+        let refiner = Refiner::new(Validator::new(vec![]));
+
+        let range = 8.0..12.0;
+        let refined = refiner.refined_with_difficulty(&mut rng, &generator, range.clone());
+        assert!(range.contains(&Difficulty::rate(&refined).unwrap().value()));
+    }
+
+    #[test]
+    fn refined_best() {
+        let generator: GeneratorFn<StdRng> = Board::generator(3).unwrap();
+        let refiner = Refiner::new(Validator::new(vec![]));
+        let fewest_givens = |puzzle: &Puzzle| -(puzzle.board().cells().len() as f64);
+
+        let mut rng = StdRng::seed_from_u64(23);
+        let best = refiner.refined_best(&mut rng, &generator, 5, fewest_givens);
+
+        let mut rng = StdRng::seed_from_u64(23);
+        let fewest = (0..5)
+            .map(|_| refiner.refined(&mut rng, &generator).board().cells().len())
+            .min()
+            .unwrap();
+        assert_eq!(fewest, best.board().cells().len());
+    }
+
+    #[test]
+    fn beam_search() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let refiner = Refiner::new(Validator::new(vec![])).with_beam_search(4);
+
+        for _ in 0..5 {
+            let solution = Puzzle::with_clues(Board::random(&mut rng, 3).unwrap());
+            let puzzle = refiner.refine(solution.clone()).unwrap();
+
+            let mut solver = Solver::new(puzzle.clone());
+            assert!(solver.solve());
+            assert_eq!(solution.board().cells(), solver.solution().cells());
+        }
     }
 }
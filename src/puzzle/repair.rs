@@ -0,0 +1,255 @@
+use crate::grid::{Direction, Distance, Position};
+use crate::i18n;
+
+use super::diagnosis::{Constraint, Diagnoser};
+use super::puzzle::Puzzle;
+use super::rules::{ClueRule, FullClue};
+use super::solver::Solver;
+use super::{Cell, Clue, Count};
+
+// A change to a puzzle proposed by the repairer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Fix {
+    // Replaces the clue of a line with a full clue of other counts.
+    SetClue(Direction, Distance, Clue),
+    RemoveGiven(Position),
+    AddGiven(Position, Cell),
+}
+
+impl Fix {
+    // How much of the puzzle the fix changes: the number of cells a
+    // clue's counts are off by, or one for each given.
+    pub fn cost(&self, puzzle: &Puzzle) -> u32 {
+        match self {
+            Fix::SetClue(direction, distance, clue) => {
+                let old = puzzle
+                    .clues()
+                    .get(&(*direction, *distance))
+                    .copied()
+                    .unwrap_or(Clue::zero());
+                Cell::all()
+                    .into_iter()
+                    .map(|cell| old.cell(cell).abs_diff(clue.cell(cell)))
+                    .sum::<u32>()
+                    / 2
+            }
+            Fix::RemoveGiven(..) | Fix::AddGiven(..) => 1,
+        }
+    }
+
+    pub fn apply(&self, puzzle: &mut Puzzle) {
+        match self {
+            Fix::SetClue(direction, distance, clue) => {
+                puzzle.set_clue((*direction, *distance), *clue)
+            }
+            Fix::RemoveGiven(position) => {
+                puzzle.mut_board().remove(*position);
+            }
+            Fix::AddGiven(position, cell) => puzzle.mut_board().insert(*position, *cell),
+        }
+    }
+
+    // Describes the fix, with the counts of clues in the colors of the
+    // puzzle's palette.
+    pub fn describe(&self, puzzle: &Puzzle) -> String {
+        match self {
+            Fix::SetClue(direction, distance, clue) => i18n::message(
+                "report.fix.set-clue",
+                &[
+                    ("clue", &Constraint::Clue(*direction, *distance)),
+                    (
+                        "counts",
+                        &FullClue::from(*clue).describe(puzzle.palette().colors()),
+                    ),
+                ],
+            ),
+            Fix::RemoveGiven(position) => i18n::message(
+                "report.fix.remove-given",
+                &[
+                    ("x", &position.x()),
+                    ("y", &position.y()),
+                    ("z", &position.z()),
+                ],
+            ),
+            Fix::AddGiven(position, cell) => i18n::message(
+                "report.fix.add-given",
+                &[("given", &Constraint::Given(*position, *cell))],
+            ),
+        }
+    }
+}
+
+// Proposes small changes that make a broken puzzle solvable and unique,
+// such as puzzles imported from other sources or edited by hand. First,
+// conflicts found by the diagnoser are resolved one at a time, by the
+// cheapest change to one of their clues or givens. Then, givens are
+// added from a solution of the puzzle until it can be solved logically,
+// which also makes its solution unique.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Repairer {
+    diagnoser: Diagnoser,
+}
+
+impl Repairer {
+    pub fn new(diagnoser: Diagnoser) -> Self {
+        Repairer { diagnoser }
+    }
+
+    // Returns the fixes and the repaired puzzle, or `None` if no repair
+    // was found. Puzzles that are already fine need no fixes.
+    pub fn repair(&self, puzzle: &Puzzle) -> Option<(Vec<Fix>, Puzzle)> {
+        let mut puzzle = puzzle.clone();
+        let mut fixes = vec![];
+
+        let limit = puzzle.clue_rules().len() + puzzle.board().cells().len();
+        for _ in 0..limit {
+            let Some(conflict) = self.diagnoser.diagnose(&puzzle) else {
+                break;
+            };
+
+            // Prefer fixes that leave the puzzle consistent, and then
+            // those that change the least.
+            let fix = conflict
+                .constraints()
+                .iter()
+                .flat_map(|constraint| alternatives(&puzzle, *constraint))
+                .min_by_key(|fix| {
+                    let mut fixed = puzzle.clone();
+                    fix.apply(&mut fixed);
+                    (!self.diagnoser.is_consistent(&fixed), fix.cost(&puzzle))
+                })?;
+
+            fix.apply(&mut puzzle);
+            fixes.push(fix);
+        }
+
+        let solution = Solver::new(puzzle.clone()).search()?;
+
+        loop {
+            let mut solver = Solver::new(puzzle.clone());
+            if solver.solve() {
+                return Some((fixes, puzzle));
+            }
+
+            // Give the cell that lets the solver get the furthest.
+            let fix = puzzle
+                .board()
+                .hexagon()
+                .into_iter()
+                .filter(|position| !solver.solution().cells().contains_key(position))
+                .map(|position| Fix::AddGiven(position, solution.cells()[&position]))
+                .max_by_key(|fix| {
+                    let mut fixed = puzzle.clone();
+                    fix.apply(&mut fixed);
+                    let mut solver = Solver::new(fixed);
+                    solver.solve();
+                    solver.solution().cells().len()
+                })?;
+
+            fix.apply(&mut puzzle);
+            fixes.push(fix);
+        }
+    }
+}
+
+// The changes to a constraint that could resolve a conflict it takes
+// part in: other counts for a clue, among the colors of the puzzle's
+// palette and adding up to the length of its line, or leaving out a
+// given.
+fn alternatives(puzzle: &Puzzle, constraint: Constraint) -> Vec<Fix> {
+    match constraint {
+        Constraint::Clue(direction, distance) => {
+            let Some(segment) = puzzle.board().hexagon().segment(distance, direction) else {
+                return vec![];
+            };
+            let length = segment.into_iter().count() as Count;
+            let current = puzzle.clues().get(&(direction, distance)).copied();
+
+            splits(length, puzzle.palette().colors())
+                .into_iter()
+                .map(|counts| Clue::from_counts(&counts))
+                .filter(|clue| Some(*clue) != current)
+                .map(|clue| Fix::SetClue(direction, distance, clue))
+                .collect()
+        }
+        Constraint::Given(position, _cell) => vec![Fix::RemoveGiven(position)],
+    }
+}
+
+// Every way of splitting the length of a line among the given number of
+// colors, as the counts of each of them.
+fn splits(length: Count, colors: usize) -> Vec<Vec<Count>> {
+    let mut splits = vec![vec![]];
+    for _ in 1..colors {
+        splits = splits
+            .into_iter()
+            .flat_map(|counts: Vec<Count>| {
+                let left = length - counts.iter().sum::<Count>();
+                (0..=left).map(move |count| [counts.as_slice(), &[count]].concat())
+            })
+            .collect();
+    }
+
+    for counts in &mut splits {
+        counts.push(length - counts.iter().sum::<Count>());
+    }
+    splits
+}
+
+#[cfg(all(test, feature = "generate"))]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::puzzle::board::Board;
+    use crate::puzzle::Palette;
+
+    #[test]
+    fn repair() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for colors in [3, 5] {
+            let palette = Palette::new(colors).unwrap();
+            let board = Board::random_with_palette(&mut rng, 2, palette).unwrap();
+            let mut puzzle = Puzzle::with_clues(board.clone()).with_palette(palette);
+            puzzle.clear();
+
+            // Moving a cell of a clue from one color to another breaks the
+            // puzzle, which the repairer fixes by moving it back or
+            // adjusting the crossing lines.
+            let key = (Direction::XY, 0);
+            let clue = puzzle.clues()[&key];
+            let from = clue.max_cell().unwrap();
+            let to = *palette.cells().iter().find(|cell| **cell != from).unwrap();
+            let mut counts = clue.counts();
+            counts[from.index()] -= 1;
+            counts[to.index()] += 1;
+            puzzle.set_clue(key, Clue::from_counts(&counts));
+            assert!(Diagnoser::new().diagnose(&puzzle).is_some());
+
+            let (fixes, repaired) = Repairer::default().repair(&puzzle).unwrap();
+            assert!(fixes
+                .iter()
+                .any(|fix| matches!(fix, Fix::SetClue(_direction, _distance, _clue))));
+            assert!(Diagnoser::new().diagnose(&repaired).is_none());
+
+            let mut solver = Solver::new(repaired);
+            assert!(solver.solve());
+            assert!(solver.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn splits() {
+        assert_eq!(
+            vec![vec![0, 2], vec![1, 1], vec![2, 0]],
+            super::splits(2, 2)
+        );
+        assert_eq!(21, super::splits(5, 3).len());
+        assert_eq!(126, super::splits(5, 5).len());
+        assert!(super::splits(5, 5)
+            .iter()
+            .all(|counts| counts.len() == 5 && counts.iter().sum::<Count>() == 5));
+    }
+}
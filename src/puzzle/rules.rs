@@ -0,0 +1,576 @@
+use std::fmt::Debug;
+
+use super::{Cell, Clue, ClueKind, Count, Hint, COLORS};
+
+// How a clue constrains the cells of its line. The cells and hints of
+// a line are given in the order of its segment, with the clue shown
+// after its last position, and the cells hold those solved so far.
+pub trait ClueRule: Debug + Send + Sync {
+    // A short description of the clue, as shown next to its line, with
+    // the counts of the given number of colors of the puzzle's palette.
+    fn describe(&self, colors: usize) -> String;
+
+    // The colors each position of the line could take, given the cells
+    // solved so far and the colors allowed by every other line. Solved
+    // positions keep their own color, unless the clue contradicts it.
+    fn project(&self, cells: &[Option<Cell>], hints: &[Hint]) -> Vec<Hint>;
+
+    // The cells of the unsolved positions that must be of a color as
+    // all of its remaining cells are needed to fill the positions that
+    // allow it.
+    fn exhaust(&self, cells: &[Option<Cell>], hints: &[Hint]) -> Vec<Option<Cell>>;
+
+    // Whether the cells of a complete line satisfy the clue.
+    fn is_satisfied(&self, cells: &[Cell]) -> bool;
+
+    // The built-in kind of the clue and the colors it reveals beyond its
+    // counts, for writing it out. Other rules cannot be written out.
+    fn kind(&self) -> Option<(ClueKind, Vec<Cell>)> {
+        None
+    }
+}
+
+// The number of cells of each color.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FullClue(Clue);
+
+// The numbers of cells of each color, without saying which color each
+// of the numbers belongs to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TotalsClue([Count; COLORS]);
+
+// The number of cells of each color, listed in the order in which the
+// colors first appear, reading the line from its clue's end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderedClue {
+    clue: Clue,
+    order: Vec<Cell>,
+}
+
+// The number of cells of each color, and which of the colors have all
+// of their cells in a single contiguous run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContiguousClue {
+    clue: Clue,
+    contiguous: Vec<Cell>,
+}
+
+impl FullClue {
+    pub fn new(cells: &[Cell]) -> Self {
+        FullClue(Clue::from_cells(cells.iter().copied()))
+    }
+}
+
+impl From<Clue> for FullClue {
+    fn from(clue: Clue) -> Self {
+        FullClue(clue)
+    }
+}
+
+impl From<Clue> for TotalsClue {
+    fn from(clue: Clue) -> Self {
+        TotalsClue(clue.totals())
+    }
+}
+
+impl TotalsClue {
+    pub fn new(cells: &[Cell]) -> Self {
+        TotalsClue::from(Clue::from_cells(cells.iter().copied()))
+    }
+
+    // The clues that could be behind the totals, one for each distinct
+    // way of assigning the totals to the colors the line allows.
+    fn candidates(&self, allowed: Hint) -> Vec<Clue> {
+        let totals: Vec<Count> = self.0.into_iter().filter(|total| *total > 0).collect();
+        let mut candidates = vec![];
+        assign(&totals, Clue::zero(), allowed, &mut candidates);
+        candidates
+    }
+
+    // The remaining clues of the unsolved positions under each of the
+    // candidates, leaving out those that need more cells of a color
+    // than the positions allow for.
+    fn remaining(&self, cells: &[Option<Cell>], hints: &[Hint]) -> Vec<Clue> {
+        let capacity = capacity(cells, hints);
+        let allowed = hints.iter().fold(
+            Hint::from_cells(cells.iter().flatten().copied()),
+            |allowed, hint| allowed | *hint,
+        );
+
+        self.candidates(allowed)
+            .into_iter()
+            .filter_map(|candidate| remaining(candidate, cells))
+            .filter(|remaining| capacity.checked_sub(*remaining).is_some())
+            .collect()
+    }
+}
+
+impl OrderedClue {
+    pub fn new(cells: &[Cell]) -> Self {
+        OrderedClue {
+            clue: Clue::from_cells(cells.iter().copied()),
+            order: order(cells),
+        }
+    }
+
+    pub fn from_parts(clue: Clue, order: Vec<Cell>) -> Self {
+        OrderedClue { clue, order }
+    }
+
+    pub fn order(&self) -> &[Cell] {
+        &self.order
+    }
+}
+
+impl ContiguousClue {
+    pub fn new(cells: &[Cell]) -> Self {
+        ContiguousClue {
+            clue: Clue::from_cells(cells.iter().copied()),
+            contiguous: contiguous(cells),
+        }
+    }
+
+    pub fn from_parts(clue: Clue, contiguous: Vec<Cell>) -> Self {
+        ContiguousClue { clue, contiguous }
+    }
+
+    pub fn contiguous(&self) -> &[Cell] {
+        &self.contiguous
+    }
+}
+
+impl ClueRule for FullClue {
+    fn describe(&self, colors: usize) -> String {
+        let counts: Vec<String> = self.0.counts()[..colors]
+            .iter()
+            .map(Count::to_string)
+            .collect();
+        format!("({})", counts.join(" "))
+    }
+
+    fn project(&self, cells: &[Option<Cell>], _hints: &[Hint]) -> Vec<Hint> {
+        spread(remaining(self.0, cells).map(|clue| clue.hint()), cells)
+    }
+
+    fn exhaust(&self, cells: &[Option<Cell>], hints: &[Hint]) -> Vec<Option<Cell>> {
+        exhaust(remaining(self.0, cells).into_iter(), cells, hints)
+    }
+
+    fn is_satisfied(&self, cells: &[Cell]) -> bool {
+        Clue::from_cells(cells.iter().copied()) == self.0
+    }
+
+    fn kind(&self) -> Option<(ClueKind, Vec<Cell>)> {
+        Some((ClueKind::Full, vec![]))
+    }
+}
+
+impl ClueRule for TotalsClue {
+    // Totals are shown in brackets, as they are unordered.
+    fn describe(&self, colors: usize) -> String {
+        let totals: Vec<String> = self.0[..colors].iter().map(Count::to_string).collect();
+        format!("[{}]", totals.join(" "))
+    }
+
+    fn project(&self, cells: &[Option<Cell>], hints: &[Hint]) -> Vec<Hint> {
+        let remaining = self.remaining(cells, hints);
+        let hint = (!remaining.is_empty()).then(|| {
+            remaining
+                .into_iter()
+                .fold(Hint::none(), |hint, clue| hint | clue.hint())
+        });
+        spread(hint, cells)
+    }
+
+    fn exhaust(&self, cells: &[Option<Cell>], hints: &[Hint]) -> Vec<Option<Cell>> {
+        exhaust(self.remaining(cells, hints).into_iter(), cells, hints)
+    }
+
+    fn is_satisfied(&self, cells: &[Cell]) -> bool {
+        Clue::from_cells(cells.iter().copied()).totals() == self.0
+    }
+
+    fn kind(&self) -> Option<(ClueKind, Vec<Cell>)> {
+        Some((ClueKind::Totals, vec![]))
+    }
+}
+
+impl ClueRule for OrderedClue {
+    // Ordered clues are shown in braces, in the order of their colors,
+    // which leaves out the colors the line does not have.
+    fn describe(&self, _colors: usize) -> String {
+        let counts: Vec<String> = self
+            .order
+            .iter()
+            .map(|cell| format!("{}{}", cell.letter(), self.clue.cell(*cell)))
+            .collect();
+        format!("{{{}}}", counts.join(" "))
+    }
+
+    // No position can be of a color before that color's first
+    // appearance, which comes after the first appearance of the colors
+    // listed before it, and before the first appearance of the colors
+    // listed after it. A color whose first appearance can only be at a
+    // single position must be at that position.
+    fn project(&self, cells: &[Option<Cell>], hints: &[Hint]) -> Vec<Hint> {
+        let order = &self.order;
+        let cells: Vec<Option<Cell>> = cells.iter().rev().copied().collect();
+
+        let mut earliest = vec![0; order.len()];
+        for index in 1..order.len() {
+            earliest[index] = (earliest[index - 1] + 1..cells.len())
+                .find(|position| cells[*position].is_none_or(|cell| cell == order[index]))
+                .unwrap_or(cells.len());
+        }
+
+        let mut latest: Vec<usize> = vec![0; order.len()];
+        for index in (1..order.len()).rev() {
+            let mut position = cells
+                .iter()
+                .position(|cell| *cell == Some(order[index]))
+                .unwrap_or(cells.len() - 1);
+
+            if index + 1 < order.len() {
+                position = position.min(latest[index + 1].saturating_sub(1));
+            }
+
+            latest[index] = position;
+        }
+
+        let mut projected: Vec<Hint> = (0..cells.len())
+            .map(|position| {
+                let forced = (0..order.len())
+                    .find(|index| earliest[*index] == position && latest[*index] == position);
+
+                match forced {
+                    Some(index) => Hint::from_cells([order[index]].into_iter()),
+                    None => Hint::from_cells(
+                        (0..order.len())
+                            .filter(|index| earliest[*index] <= position)
+                            .map(|index| order[index]),
+                    ),
+                }
+            })
+            .collect();
+        projected.reverse();
+
+        FullClue(self.clue)
+            .project(&cells, hints)
+            .into_iter()
+            .rev()
+            .zip(projected)
+            .map(|(counts, hint)| counts & hint)
+            .collect()
+    }
+
+    fn exhaust(&self, cells: &[Option<Cell>], hints: &[Hint]) -> Vec<Option<Cell>> {
+        FullClue(self.clue).exhaust(cells, hints)
+    }
+
+    fn is_satisfied(&self, cells: &[Cell]) -> bool {
+        FullClue(self.clue).is_satisfied(cells) && order(cells) == self.order
+    }
+
+    fn kind(&self) -> Option<(ClueKind, Vec<Cell>)> {
+        Some((ClueKind::Ordered, self.order.clone()))
+    }
+}
+
+impl ClueRule for ContiguousClue {
+    // Counts of contiguous colors are shown in braces, and of other
+    // colors between dashes.
+    fn describe(&self, colors: usize) -> String {
+        let counts: Vec<String> = Cell::all()[..colors]
+            .iter()
+            .map(|cell| {
+                let count = self.clue.cell(*cell);
+                if count == 0 {
+                    count.to_string()
+                } else if self.contiguous.contains(cell) {
+                    format!("{{{count}}}")
+                } else {
+                    format!("-{count}-")
+                }
+            })
+            .collect();
+        format!("({})", counts.join(" "))
+    }
+
+    // A run can only be placed where it covers every solved cell of its
+    // color and no solved cell of another color: positions outside of
+    // every placement cannot be of its color, while positions within
+    // all of them must be.
+    fn project(&self, cells: &[Option<Cell>], hints: &[Hint]) -> Vec<Hint> {
+        let mut projected = FullClue(self.clue).project(cells, hints);
+
+        for cell in &self.contiguous {
+            let count = self.clue.cell(*cell) as usize;
+            if count == 0 {
+                continue;
+            }
+
+            let placements: Vec<usize> = (0..=cells.len() - count)
+                .filter(|start| {
+                    let run = *start..start + count;
+                    run.clone()
+                        .all(|index| cells[index].is_none_or(|other| other == *cell))
+                        && cells
+                            .iter()
+                            .enumerate()
+                            .all(|(index, other)| *other != Some(*cell) || run.contains(&index))
+                })
+                .collect();
+
+            let others = Hint::from_cells(Cell::all().into_iter().filter(|other| other != cell));
+
+            for (index, hint) in projected.iter_mut().enumerate() {
+                let covering = placements
+                    .iter()
+                    .filter(|start| (**start..**start + count).contains(&index))
+                    .count();
+
+                if covering == 0 {
+                    *hint = *hint & others;
+                } else if covering == placements.len() {
+                    *hint = *hint & Hint::from_cells([*cell].into_iter());
+                }
+            }
+        }
+
+        projected
+    }
+
+    fn exhaust(&self, cells: &[Option<Cell>], hints: &[Hint]) -> Vec<Option<Cell>> {
+        FullClue(self.clue).exhaust(cells, hints)
+    }
+
+    fn is_satisfied(&self, cells: &[Cell]) -> bool {
+        FullClue(self.clue).is_satisfied(cells) && contiguous(cells) == self.contiguous
+    }
+
+    fn kind(&self) -> Option<(ClueKind, Vec<Cell>)> {
+        Some((ClueKind::Contiguous, self.contiguous.clone()))
+    }
+}
+
+// The clue of the unsolved positions of a line, unless the solved ones
+// already have more cells of a color than the clue.
+fn remaining(clue: Clue, cells: &[Option<Cell>]) -> Option<Clue> {
+    clue.checked_sub(Clue::from_cells(cells.iter().flatten().copied()))
+}
+
+// The hint of every position of a line, given the colors its unsolved
+// positions could take, if the clue allows for its solved cells at all.
+fn spread(unsolved: Option<Hint>, cells: &[Option<Cell>]) -> Vec<Hint> {
+    cells
+        .iter()
+        .map(|cell| match (unsolved, cell) {
+            (None, _cell) => Hint::none(),
+            (Some(_hint), Some(cell)) => Hint::from_cells([*cell].into_iter()),
+            (Some(hint), None) => hint,
+        })
+        .collect()
+}
+
+// The number of unsolved positions of a line that allow each color.
+fn capacity(cells: &[Option<Cell>], hints: &[Hint]) -> Clue {
+    cells
+        .iter()
+        .zip(hints)
+        .filter(|(cell, _hint)| cell.is_none())
+        .fold(Clue::zero(), |capacity, (_cell, hint)| {
+            capacity + hint.clue()
+        })
+}
+
+// The cells that are certain under every one of the possible remaining
+// clues of the unsolved positions, that is, the positions that allow a
+// color whose remaining cells are needed to fill all of them.
+fn exhaust(
+    remaining: impl Iterator<Item = Clue>,
+    cells: &[Option<Cell>],
+    hints: &[Hint],
+) -> Vec<Option<Cell>> {
+    let capacity = capacity(cells, hints);
+    let mut deductions: Option<Vec<Option<Cell>>> = None;
+
+    for remaining in remaining {
+        if capacity.checked_sub(remaining).is_none() {
+            continue;
+        }
+
+        let mut found = vec![None; cells.len()];
+        for cell in Cell::all() {
+            if capacity.cell(cell) == remaining.cell(cell) {
+                for (index, (solved, hint)) in cells.iter().zip(hints).enumerate() {
+                    if solved.is_none() && hint.cell(cell) {
+                        found[index] = Some(cell);
+                    }
+                }
+            }
+        }
+
+        deductions = Some(match deductions {
+            None => found,
+            Some(deductions) => deductions
+                .into_iter()
+                .zip(found)
+                .map(|(deduction, found)| deduction.filter(|cell| Some(*cell) == found))
+                .collect(),
+        });
+    }
+
+    deductions.unwrap_or_else(|| vec![None; cells.len()])
+}
+
+// The colors of a line in the order in which they first appear,
+// reading the line backwards, from the end where its clue is shown.
+fn order(cells: &[Cell]) -> Vec<Cell> {
+    let mut order = vec![];
+
+    for cell in cells.iter().rev() {
+        if !order.contains(cell) {
+            order.push(*cell);
+        }
+    }
+
+    order
+}
+
+// The colors of a line whose cells form a single contiguous run.
+fn contiguous(cells: &[Cell]) -> Vec<Cell> {
+    Cell::all()
+        .into_iter()
+        .filter(|cell| {
+            let runs = cells
+                .iter()
+                .enumerate()
+                .filter(|(index, other)| {
+                    *other == cell && (*index == 0 || cells[index - 1] != *cell)
+                })
+                .count();
+            runs == 1
+        })
+        .collect()
+}
+
+// Adds every distinct way of assigning the totals to different colors
+// among those allowed to the candidates.
+fn assign(totals: &[Count], clue: Clue, allowed: Hint, candidates: &mut Vec<Clue>) {
+    let Some((total, rest)) = totals.split_first() else {
+        if !candidates.contains(&clue) {
+            candidates.push(clue);
+        }
+        return;
+    };
+
+    for cell in allowed.cells().filter(|cell| clue.cell(*cell) == 0) {
+        let mut counts = clue.counts();
+        counts[cell.index()] = *total;
+        assign(rest, Clue::from_counts(&counts), allowed, candidates);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::Palette;
+    use Cell::*;
+
+    #[test]
+    fn ordered() {
+        // Reading from the end: green, green, red, blue, red.
+        let line = [Red, Blue, Red, Green, Green];
+        let rule = OrderedClue::new(&line);
+
+        assert_eq!(&[Green, Red, Blue], rule.order());
+        assert_eq!("{G2 R2 B1}", rule.describe(3));
+        assert!(rule.is_satisfied(&line));
+        assert!(!rule.is_satisfied(&[Red, Red, Blue, Green, Green]));
+
+        let mut cells = [None; 5];
+        let hints = [Palette::default().hint(); 5];
+        let projected = rule.project(&cells, &hints);
+
+        assert_eq!(Hint::new(false, true, false), projected[4]);
+        assert_eq!(Hint::new(true, true, false), projected[3]);
+        assert_eq!(Palette::default().hint(), projected[0]);
+
+        // Once the blue cell is known, red must first appear right
+        // after the first green cell.
+        cells[2] = Some(Blue);
+        let projected = rule.project(&cells, &hints);
+
+        assert_eq!(Hint::new(true, false, false), projected[3]);
+    }
+
+    #[test]
+    fn contiguous() {
+        let line = [Green, Red, Red, Red, Green];
+        let rule = ContiguousClue::new(&line);
+
+        assert_eq!(&[Red], rule.contiguous());
+        assert_eq!("({3} -2- 0)", rule.describe(3));
+        assert!(rule.is_satisfied(&line));
+        assert!(!rule.is_satisfied(&[Red, Green, Red, Red, Green]));
+
+        // A run of three red cells always covers the middle position.
+        let mut cells = [None; 5];
+        let hints = [Palette::default().hint(); 5];
+        let projected = rule.project(&cells, &hints);
+
+        assert_eq!(Hint::new(true, false, false), projected[2]);
+        assert_eq!(Hint::new(true, true, false), projected[0]);
+
+        // Once a red cell is known next to the end, the run cannot reach
+        // the other end.
+        cells[1] = Some(Red);
+        let projected = rule.project(&cells, &hints);
+
+        assert_eq!(Hint::new(false, true, false), projected[4]);
+        assert_eq!(Hint::new(true, false, false), projected[2]);
+    }
+
+    #[test]
+    fn totals() {
+        let line = [Red, Red, Green, Blue, Red];
+        let rule = TotalsClue::new(&line);
+
+        assert_eq!("[3 1 1]", rule.describe(3));
+        assert!(rule.is_satisfied(&[Blue, Green, Blue, Blue, Red]));
+        assert!(!rule.is_satisfied(&[Blue, Green, Blue, Green, Red]));
+
+        // Only one position allows blue, so blue cannot be the color of
+        // the three cells, which leaves red and green to choose from.
+        let cells = [None, None, None, None, Some(Red)];
+        let mut hints = [Hint::new(true, true, false); 5];
+        hints[0] = Palette::default().hint();
+        let projected = rule.project(&cells, &hints);
+
+        assert_eq!(2, rule.remaining(&cells, &hints).len());
+        assert_eq!(Palette::default().hint(), projected[0]);
+        assert_eq!(
+            vec![Some(Blue), None, None, None, None],
+            rule.exhaust(&cells, &hints)
+        );
+    }
+
+    #[test]
+    fn more_colors() {
+        let line = [Red, Orange, Orange, Purple, Red];
+        assert_eq!("(2 0 0 2 1)", FullClue::new(&line).describe(5));
+        assert_eq!("(1 0 0 2 0)", FullClue::new(&line[..3]).describe(5));
+        assert_eq!("(-2- 0 0 {2} {1})", ContiguousClue::new(&line).describe(5));
+        assert_eq!("(1 0)", FullClue::new(&[Red]).describe(2));
+
+        // Totals are assigned to the colors the line allows, so three
+        // colors give three ways of placing the single purple cell.
+        let rule = TotalsClue::new(&line);
+        assert_eq!("[2 2 1 0 0]", rule.describe(5));
+        let cells = [None; 5];
+        let hints = [Palette::default().hint(); 5];
+        assert_eq!(3, rule.remaining(&cells, &hints).len());
+        let hints = [Palette::new(5).unwrap().hint(); 5];
+        assert_eq!(30, rule.remaining(&cells, &hints).len());
+    }
+}
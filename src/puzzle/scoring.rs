@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use super::difficulty::Difficulty;
+use super::session::Session;
+
+// Converts the outcome of a play session into a score. The formula is
+// public so that every front-end ranks sessions the same way:
+//
+//     par = seconds_per_point * difficulty
+//     speed = min(par / elapsed, max_speed_bonus)
+//     penalty = (1 - mistake_penalty) ^ mistakes * (1 - hint_penalty) ^ hints
+//     score = round(points_per_difficulty * difficulty * speed * penalty)
+//
+// Solving a puzzle in exactly its par time, without mistakes or hints,
+// scores `points_per_difficulty` points per point of difficulty.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ScoringModel {
+    pub points_per_difficulty: f64,
+    pub seconds_per_point: f64,
+    pub max_speed_bonus: f64,
+    pub mistake_penalty: f64,
+    pub hint_penalty: f64,
+}
+
+const DEFAULT: ScoringModel = ScoringModel {
+    points_per_difficulty: 100.0,
+    seconds_per_point: 30.0,
+    max_speed_bonus: 2.0,
+    mistake_penalty: 0.1,
+    hint_penalty: 0.2,
+};
+
+impl Default for ScoringModel {
+    fn default() -> Self {
+        DEFAULT
+    }
+}
+
+impl ScoringModel {
+    pub fn score(
+        &self,
+        difficulty: Difficulty,
+        elapsed: Duration,
+        mistakes: u32,
+        hints: u32,
+    ) -> u32 {
+        let difficulty = difficulty.value().max(0.0);
+        let par = self.seconds_per_point * difficulty;
+        let elapsed = elapsed.as_secs_f64().max(1.0);
+        let speed = (par / elapsed).min(self.max_speed_bonus);
+        let penalty = (1.0 - self.mistake_penalty).powi(mistakes as i32)
+            * (1.0 - self.hint_penalty).powi(hints as i32);
+
+        (self.points_per_difficulty * difficulty * speed * penalty)
+            .round()
+            .max(0.0) as u32
+    }
+
+    // Scores a session, rating its puzzle's difficulty. Returns `None`
+    // if the session is not finished or its puzzle cannot be rated.
+    pub fn score_session(&self, session: &Session) -> Option<u32> {
+        if !session.is_finished() {
+            return None;
+        }
+
+        let difficulty = Difficulty::rate(session.puzzle())?;
+
+        Some(self.score(
+            difficulty,
+            session.elapsed(),
+            session.mistakes(),
+            session.hints(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score() {
+        let model = ScoringModel::default();
+        let difficulty = Difficulty::new(4.0);
+        let par = Duration::from_secs(120);
+
+        assert_eq!(400, model.score(difficulty, par, 0, 0));
+        assert_eq!(800, model.score(difficulty, par / 10, 0, 0));
+        assert_eq!(200, model.score(difficulty, par * 2, 0, 0));
+        assert_eq!(360, model.score(difficulty, par, 1, 0));
+        assert_eq!(320, model.score(difficulty, par, 0, 1));
+        assert_eq!(800, model.score(Difficulty::new(8.0), par * 2, 0, 0));
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::grid::Position;
+
+use super::board::Board;
+use super::puzzle::Puzzle;
+use super::solver::{Solver, WhatIf};
+use super::Cell;
+
+// A player's attempt at solving a puzzle, keeping track of the cells
+// they placed, the time they took, and how often they made mistakes or
+// asked for hints. Cells they are not yet sure about are pencilled in
+// as tentative cells, apart from their board, and are not checked
+// against the solution.
+#[derive(Debug, Clone)]
+pub struct Session {
+    puzzle: Puzzle,
+    solution: Board,
+    board: Board,
+    tentative: HashMap<Position, Cell>,
+    mistakes: u32,
+    hints: u32,
+    started: Instant,
+    finished: Option<Instant>,
+}
+
+impl Session {
+    pub fn new(puzzle: Puzzle, solution: Board) -> Self {
+        let board = puzzle.board().clone();
+
+        Session {
+            puzzle,
+            solution,
+            board,
+            tentative: HashMap::new(),
+            mistakes: 0,
+            hints: 0,
+            started: Instant::now(),
+            finished: None,
+        }
+    }
+
+    // Starts a session for a puzzle that can be solved logically, using
+    // the solver to find its solution.
+    pub fn from_puzzle(puzzle: Puzzle) -> Option<Self> {
+        let mut solver = Solver::new(puzzle.clone());
+
+        if solver.solve() {
+            Some(Self::new(puzzle, solver.solution().clone()))
+        } else {
+            None
+        }
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn tentative(&self) -> &HashMap<Position, Cell> {
+        &self.tentative
+    }
+
+    pub fn mistakes(&self) -> u32 {
+        self.mistakes
+    }
+
+    pub fn hints(&self) -> u32 {
+        self.hints
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.is_some()
+    }
+
+    // The time spent on the puzzle, up to the moment it was finished.
+    pub fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(Instant::now) - self.started
+    }
+
+    // Places a cell on the player's board if it matches the solution,
+    // counting a mistake otherwise. Returns whether the cell was placed.
+    pub fn place(&mut self, position: Position, cell: Cell) -> bool {
+        if self.is_finished() || self.board.cells().contains_key(&position) {
+            return false;
+        }
+
+        if self.solution.cells().get(&position) != Some(&cell) {
+            self.mistakes += 1;
+            return false;
+        }
+
+        self.tentative.remove(&position);
+        self.board.insert(position, cell);
+        self.check_finished();
+        true
+    }
+
+    // Pencils in a tentative cell on a position the player has not
+    // placed yet, replacing any earlier tentative cell there. Returns
+    // whether the cell was pencilled in.
+    pub fn pencil(&mut self, position: Position, cell: Cell) -> bool {
+        if self.is_finished()
+            || !self.board.hexagon().contains(position)
+            || self.board.cells().contains_key(&position)
+        {
+            return false;
+        }
+
+        self.tentative.insert(position, cell);
+        true
+    }
+
+    pub fn erase(&mut self, position: Position) -> Option<Cell> {
+        self.tentative.remove(&position)
+    }
+
+    pub fn erase_all(&mut self) {
+        self.tentative.clear();
+    }
+
+    // Explores what follows from the player's board if the tentative
+    // cells are right, without revealing anything of the solution
+    // beyond what the player could deduce themselves.
+    pub fn what_if(&self) -> WhatIf {
+        let mut puzzle = self.puzzle.clone();
+        *puzzle.mut_board() = self.board.clone();
+
+        Solver::new(puzzle).what_if(
+            self.tentative
+                .iter()
+                .map(|(position, cell)| (*position, *cell)),
+        )
+    }
+
+    // Reveals a cell of the solution, preferring one that can be
+    // deduced from the player's board.
+    pub fn hint(&mut self) -> Option<(Position, Cell)> {
+        if self.is_finished() {
+            return None;
+        }
+
+        let (position, cell) = Solver::new(self.puzzle.clone())
+            .next_move(&self.board)
+            .map(|step| (step.position(), step.cell()))
+            .or_else(|| {
+                self.solution
+                    .cells()
+                    .iter()
+                    .find(|(position, _cell)| !self.board.cells().contains_key(position))
+                    .map(|(position, cell)| (*position, *cell))
+            })?;
+
+        self.hints += 1;
+        self.tentative.remove(&position);
+        self.board.insert(position, cell);
+        self.check_finished();
+        Some((position, cell))
+    }
+
+    fn check_finished(&mut self) {
+        if self.board.is_solved() {
+            self.finished = Some(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Direction;
+
+    fn session() -> Session {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+        Session::new(puzzle, board)
+    }
+
+    #[test]
+    fn place() {
+        let mut session = session();
+
+        assert!(!session.place(Position::zero(), Cell::Blue));
+        assert!(!session.place(Direction::XY.position() * 5, Cell::Red));
+        assert_eq!(2, session.mistakes());
+
+        assert!(session.place(Position::zero(), Cell::Red));
+        assert!(!session.place(Position::zero(), Cell::Red));
+        assert_eq!(2, session.mistakes());
+        assert!(!session.is_finished());
+
+        let solution = session.solution.clone();
+        for (position, cell) in solution.cells() {
+            session.place(*position, *cell);
+        }
+        assert!(session.is_finished());
+        assert_eq!(solution.cells(), session.board().cells());
+
+        // Nothing changes once the puzzle is finished, not even the time
+        // spent on it.
+        let elapsed = session.elapsed();
+        assert!(!session.pencil(Position::zero(), Cell::Red));
+        assert_eq!(None, session.hint());
+        assert_eq!(elapsed, session.elapsed());
+    }
+
+    #[test]
+    fn pencil() {
+        let mut session = session();
+        let position = Direction::XY.position();
+
+        assert!(session.pencil(position, Cell::Red));
+        assert!(session.pencil(position, Cell::Green));
+        assert!(!session.pencil(position * 5, Cell::Green));
+        assert_eq!(Some(&Cell::Green), session.tentative().get(&position));
+        assert_eq!(0, session.mistakes());
+
+        // Tentative cells are not checked until the position is placed,
+        // which replaces them.
+        match session.what_if() {
+            WhatIf::Follows(follows) => assert!(follows.iter().all(|(position, cell)| session
+                .solution
+                .cells()
+                .get(position)
+                == Some(cell))),
+            WhatIf::Contradiction(position) => panic!("contradiction at {position:?}"),
+        }
+        assert!(session.place(position, Cell::Green));
+        assert!(session.tentative().is_empty());
+        assert!(!session.pencil(position, Cell::Blue));
+
+        session.pencil(Position::zero(), Cell::Blue);
+        session.pencil(Direction::YZ.position(), Cell::Green);
+        assert_eq!(Some(Cell::Blue), session.erase(Position::zero()));
+        session.erase_all();
+        assert!(session.tentative().is_empty());
+    }
+
+    #[test]
+    fn hint() {
+        let mut session = session();
+        session.pencil(Position::zero(), Cell::Blue);
+
+        let mut hints = 0;
+        while let Some((position, cell)) = session.hint() {
+            hints += 1;
+            assert_eq!(Some(&cell), session.solution.cells().get(&position));
+            assert_eq!(Some(&cell), session.board().cells().get(&position));
+            assert!(!session.tentative().contains_key(&position));
+        }
+
+        assert_eq!(19, hints);
+        assert_eq!(19, session.hints());
+        assert_eq!(0, session.mistakes());
+        assert!(session.is_finished());
+    }
+}
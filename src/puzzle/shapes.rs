@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use rand::Rng;
+
+use crate::grid::hexagon::{Hexagon, HexagonError};
+use crate::grid::{Distance, Position};
+
+use super::board::Board;
+use super::puzzle::{Generator, Puzzle};
+use super::svg;
+use super::Hint;
+
+// A picture that can be drawn onto a hexagon of any radius, described
+// either by the outline of an SVG path, which is scaled to fit the
+// hexagon, or by the rings at the border of the hexagon.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Outline {
+    Path(&'static str),
+    Border,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Shape {
+    name: &'static str,
+    outline: Outline,
+}
+
+// Paths are kept clear of the corners of the hexagon, which would
+// otherwise cut off the parts of the picture that reach into them.
+const PATH_SCALE: f64 = 0.85;
+
+const HEART: Shape = Shape {
+    name: "heart",
+    outline: Outline::Path("M0 3 L-3 0 V-1.5 L-2 -2.5 H-1 L0 -1.5 L1 -2.5 H2 L3 -1.5 V0 Z"),
+};
+
+const STAR: Shape = Shape {
+    name: "star",
+    outline: Outline::Path(
+        "M0 -1 L0.294 -0.405 L0.951 -0.309 L0.476 0.155 L0.588 0.809 L0 0.5 \
+         L-0.588 0.809 L-0.476 0.155 L-0.951 -0.309 L-0.294 -0.405 Z",
+    ),
+};
+
+const OUTLINE: Shape = Shape {
+    name: "outline",
+    outline: Outline::Border,
+};
+
+// A round face, with the eyes and the mouth cut out of it.
+const SMILEY: Shape = Shape {
+    name: "smiley",
+    outline: Outline::Path(
+        "M1 0 L0.866 0.5 L0.5 0.866 L0 1 L-0.5 0.866 L-0.866 0.5 L-1 0 \
+         L-0.866 -0.5 L-0.5 -0.866 L0 -1 L0.5 -0.866 L0.866 -0.5 Z \
+         M-0.55 -0.6 h0.35 v0.5 h-0.35 Z M0.2 -0.6 h0.35 v0.5 h-0.35 Z \
+         M-0.75 0.1 L-0.4 0.65 H0.4 L0.75 0.1 L0.45 0.05 L0.2 0.35 H-0.2 L-0.45 0.05 Z",
+    ),
+};
+
+const SHAPES: [Shape; 4] = [HEART, STAR, OUTLINE, SMILEY];
+
+impl Shape {
+    pub fn all() -> [Shape; 4] {
+        SHAPES
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        SHAPES.into_iter().find(|shape| shape.name == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    // The positions of the hexagon that the shape covers.
+    pub fn mask(&self, hexagon: Hexagon) -> HashSet<Position> {
+        match self.outline {
+            Outline::Path(path) => {
+                svg::rasterize_scaled(&svg::parse_path(path).unwrap(), hexagon, PATH_SCALE)
+            }
+            Outline::Border => {
+                // Thicker borders keep the outline visible on larger hexagons.
+                let thickness = (hexagon.radius() + 3) / 4;
+
+                hexagon
+                    .into_iter()
+                    .filter(|position| {
+                        hexagon.origin().distance_to(*position) > hexagon.radius() - thickness
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl FromStr for Shape {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::by_name(value).ok_or_else(|| format!("unknown shape `{value}`"))
+    }
+}
+
+// Generates solutions that draw a shape, by picking cells of the inside
+// hint's colors for the positions the shape covers, and of the outside
+// hint's colors for every other position.
+#[derive(Debug, Clone)]
+pub struct ShapeGenerator {
+    shape: Shape,
+    radius: Distance,
+    inside: Hint,
+    outside: Hint,
+}
+
+impl ShapeGenerator {
+    // Shapes cannot be drawn on a single cell, so the hexagon must have
+    // at least one ring.
+    pub fn new(shape: Shape, radius: Distance) -> Result<Self, HexagonError> {
+        if radius < 1 {
+            return Err(HexagonError::InsufficientRadius(radius));
+        }
+
+        Ok(ShapeGenerator {
+            shape,
+            radius,
+            inside: Hint::new(true, false, false),
+            outside: Hint::new(false, true, true),
+        })
+    }
+
+    pub fn with_colors(mut self, inside: Hint, outside: Hint) -> Self {
+        self.inside = inside;
+        self.outside = outside;
+        self
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    pub fn hints(&self) -> HashMap<Position, Hint> {
+        let hexagon = Hexagon::zero(self.radius).unwrap();
+        let mask = self.shape.mask(hexagon);
+
+        hexagon
+            .into_iter()
+            .map(|position| {
+                let hint = if mask.contains(&position) {
+                    self.inside
+                } else {
+                    self.outside
+                };
+                (position, hint)
+            })
+            .collect()
+    }
+}
+
+impl<T: Rng> Generator<T> for ShapeGenerator {
+    fn generate(&self, rng: &mut T) -> Puzzle {
+        let board = Board::random_from_hints(rng, self.radius, self.hints().into_iter());
+        Puzzle::with_clues(board.unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::puzzle::Cell;
+
+    #[test]
+    fn shapes() {
+        for shape in Shape::all() {
+            assert_eq!(Ok(shape), shape.name().parse());
+
+            for radius in [3, 5, 8] {
+                let hexagon = Hexagon::zero(radius).unwrap();
+                let mask = shape.mask(hexagon);
+                let count = hexagon.into_iter().count();
+
+                assert!(!mask.is_empty(), "{} at radius {radius}", shape.name());
+                assert!(mask.len() < count, "{} at radius {radius}", shape.name());
+            }
+        }
+
+        assert!("square".parse::<Shape>().is_err());
+
+        let hexagon = Hexagon::zero(5).unwrap();
+        assert!(HEART.mask(hexagon).contains(&Position::zero()));
+        assert!(STAR.mask(hexagon).contains(&Position::zero()));
+        assert!(!OUTLINE.mask(hexagon).contains(&Position::zero()));
+        assert!(OUTLINE
+            .mask(hexagon)
+            .contains(&Position::new((5, -5, 0)).unwrap()));
+        assert!(!SMILEY
+            .mask(hexagon)
+            .contains(&Position::new((5, -5, 0)).unwrap()));
+    }
+
+    #[test]
+    fn generate() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let generator = ShapeGenerator::new(HEART, 5)
+            .unwrap()
+            .with_colors(Hint::new(false, false, true), Hint::new(true, true, false));
+        let mask = HEART.mask(Hexagon::zero(5).unwrap());
+        let puzzle = generator.generate(&mut rng);
+
+        assert!(puzzle.board().is_solved());
+        for (position, cell) in puzzle.board().cells() {
+            assert_eq!(mask.contains(position), *cell == Cell::Blue);
+        }
+
+        assert!(ShapeGenerator::new(HEART, 0).is_err());
+    }
+}
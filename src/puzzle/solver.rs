@@ -1,162 +1,1288 @@
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+use std::mem;
+use std::sync::Arc;
 
+use crate::grid::hexagon::Hexagon;
+use crate::grid::region::Region;
 use crate::grid::{Direction, Distance, Position};
 
+use super::bitboard::BitSet;
 use super::board::Board;
 use super::puzzle::Puzzle;
-use super::{Cell, Clue, Hint};
+use super::rules::ClueRule;
+use super::technique::{self, Technique};
+use super::trace::SolveStep;
+use super::{Cell, Clue, Hint, COLORS};
 
-pub struct Solver {
-    puzzle: Puzzle,
-    solution: Board,
+// The outcome of tentatively placing cells: either a position at which
+// they contradict the puzzle, or the cells that follow from them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WhatIf {
+    Contradiction(Position),
+    Follows(HashMap<Position, Cell>),
 }
 
-impl Solver {
-    pub fn new(puzzle: Puzzle) -> Self {
+// Why a puzzle could not be solved.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    // The puzzle has no clue for a line of its board.
+    MissingClue(Direction, Distance),
+    // The solution contradicts the puzzle at the position.
+    Contradiction(Position),
+    // The clues leave the given number of positions unsolved.
+    IncompleteClues(usize),
+    // A board has more cells of a color on the line than its clue.
+    ExceededClue(Direction, Distance),
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::MissingClue(direction, distance) => {
+                write!(f, "missing clue for line {direction:?} {distance}")
+            }
+            SolveError::Contradiction(position) => {
+                write!(f, "contradiction at {:?}", position.coordinates())
+            }
+            SolveError::IncompleteClues(unsolved) => {
+                write!(f, "the clues leave {unsolved} positions unsolved")
+            }
+            SolveError::ExceededClue(direction, distance) => {
+                write!(
+                    f,
+                    "too many cells for the clue of line {direction:?} {distance}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+// The rule of a line, with its positions and solved cells.
+pub(super) type Line<'a> = (&'a dyn ClueRule, Vec<Position>, Vec<Option<Cell>>);
+
+// The index of a position found by a pass of the solver, with its cell
+// and the line whose clue needs it, if it was not found by hints.
+type Pending = (usize, Cell, Option<(Direction, Distance)>);
+
+// The buffers a solver reuses between its passes, holding hints and
+// cells by the index of their position in the region rather than in
+// maps, so that passes do not allocate them anew. A workspace can be
+// handed on to the solver of the next puzzle, which keeps its layout of
+// lines when the puzzle is on the same region.
+#[derive(Debug, Clone, Default)]
+pub struct SolverWorkspace {
+    region: Option<Arc<dyn Any + Send + Sync>>,
+    positions: Vec<Position>,
+    indices: HashMap<Position, usize>,
+    lines: Vec<((Direction, Distance), Vec<usize>)>,
+    // The indices of the lines through each position, one for each of
+    // the normalized directions.
+    through: Vec<[usize; 3]>,
+    // The positions of each line as a set of their indices, for
+    // counting clues from bit sets of the cells of each color.
+    masks: Vec<BitSet>,
+    cells: Vec<Option<Cell>>,
+    hints: Vec<Hint>,
+    line_cells: Vec<Option<Cell>>,
+    line_hints: Vec<Hint>,
+    queue: VecDeque<usize>,
+    queued: Vec<bool>,
+    pending: Vec<Pending>,
+}
+
+impl SolverWorkspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Whether the positions and lines of the region are laid out.
+    fn is_laid_out<R: Region>(&self, region: &R) -> bool {
+        self.region
+            .as_ref()
+            .and_then(|laid_out| laid_out.downcast_ref::<R>())
+            == Some(region)
+    }
+
+    // Lays out the positions and lines of the region, unless they are
+    // laid out already.
+    fn layout<R: Region>(&mut self, region: &R) {
+        if self.is_laid_out(region) {
+            return;
+        }
+
+        self.region = Some(Arc::new(region.clone()));
+        self.positions = region.clone().into_iter().collect();
+        self.indices = self
+            .positions
+            .iter()
+            .enumerate()
+            .map(|(index, position)| (*position, index))
+            .collect();
+        self.lines = Direction::normalized()
+            .into_iter()
+            .flat_map(|direction| {
+                region
+                    .segments(direction)
+                    .map(move |(distance, segment)| ((direction, distance), segment))
+            })
+            .map(|(key, segment)| {
+                (
+                    key,
+                    segment
+                        .into_iter()
+                        .map(|position| self.indices[&position])
+                        .collect(),
+                )
+            })
+            .collect();
+
+        self.through = vec![[0; 3]; self.positions.len()];
+        self.masks = vec![];
+        for (line, ((direction, _distance), indices)) in self.lines.iter().enumerate() {
+            let axis = Direction::normalized()
+                .into_iter()
+                .position(|other| other == *direction)
+                .unwrap();
+            let mut mask = BitSet::new(self.positions.len());
+            for index in indices {
+                self.through[*index][axis] = line;
+                mask.insert(*index);
+            }
+            self.masks.push(mask);
+        }
+    }
+
+    // Subtracts the cells of the solution from the clues of the puzzle,
+    // line by line, counting them from bit sets of the solution when
+    // the workspace is laid out for its region.
+    fn count_remaining<R: Region>(
+        &self,
+        puzzle: &Puzzle<R>,
+        solution: &Board<R>,
+    ) -> Result<HashMap<(Direction, Distance), Clue>, SolveError> {
+        let counted: Vec<((Direction, Distance), Clue)> =
+            match self.is_laid_out(puzzle.board().region()) {
+                true => {
+                    let mut colors: [BitSet; COLORS] =
+                        std::array::from_fn(|_| BitSet::new(self.positions.len()));
+                    for (position, cell) in solution.cells() {
+                        if let Some(index) = self.indices.get(position) {
+                            colors[cell.index()].insert(*index);
+                        }
+                    }
+
+                    self.lines
+                        .iter()
+                        .zip(&self.masks)
+                        .map(|((key, _indices), mask)| {
+                            let counts = colors.each_ref().map(|bits| bits.intersection_len(mask));
+                            (*key, Clue::from_counts(&counts))
+                        })
+                        .collect()
+                }
+                false => solution.clues().collect(),
+            };
+        let mut clues = puzzle.clues().clone();
+
+        for (key, solution_clue) in counted {
+            let (direction, distance) = key;
+            let puzzle_clue = clues
+                .get(&key)
+                .copied()
+                .ok_or(SolveError::MissingClue(direction, distance))?;
+            let remaining = puzzle_clue
+                .checked_sub(solution_clue)
+                .ok_or(SolveError::ExceededClue(direction, distance))?;
+            clues.insert(key, remaining);
+        }
+
+        Ok(clues)
+    }
+
+    // Projects the rule of every line onto the hints of its positions,
+    // until no more colors are ruled out.
+    fn project<R: Region>(&mut self, puzzle: &Puzzle<R>, solution: &Board<R>) {
+        self.layout(puzzle.board().region());
+        self.cells.clear();
+        self.cells.extend(
+            self.positions
+                .iter()
+                .map(|position| solution.cells().get(position).copied()),
+        );
+        self.hints.clear();
+        self.hints.resize(self.cells.len(), puzzle.palette().hint());
+
+        self.queue.clear();
+        self.queue.extend(0..self.lines.len());
+        self.queued.clear();
+        self.queued.resize(self.lines.len(), true);
+
+        self.propagate(puzzle);
+    }
+
+    // Projects the lines through the given positions, solved since the
+    // hints were last projected, onto the hints. As solving positions
+    // only ever rules out more colors, the hints are narrowed down from
+    // where they were instead of being projected anew.
+    fn project_solved<R: Region>(
+        &mut self,
+        puzzle: &Puzzle<R>,
+        solution: &Board<R>,
+        solved: &[Position],
+    ) {
+        for position in solved {
+            let index = self.indices[position];
+            self.cells[index] = solution.cells().get(position).copied();
+
+            for line in self.through[index] {
+                if !self.queued[line] {
+                    self.queued[line] = true;
+                    self.queue.push_back(line);
+                }
+            }
+        }
+
+        self.propagate(puzzle);
+    }
+
+    // Projects the queued lines, queueing the lines through every
+    // position whose hint is narrowed down again, until none are left.
+    fn propagate<R: Region>(&mut self, puzzle: &Puzzle<R>) {
+        let SolverWorkspace {
+            lines,
+            through,
+            cells,
+            hints,
+            line_cells,
+            line_hints,
+            queue,
+            queued,
+            ..
+        } = self;
+
+        while let Some(line) = queue.pop_front() {
+            queued[line] = false;
+            let (key, indices) = &lines[line];
+            let Some(rule) = puzzle.clue_rules().get(key) else {
+                continue;
+            };
+
+            line_cells.clear();
+            line_cells.extend(indices.iter().map(|index| cells[*index]));
+            line_hints.clear();
+            line_hints.extend(indices.iter().map(|index| hints[*index]));
+
+            for (index, projected) in indices.iter().zip(rule.project(line_cells, line_hints)) {
+                let hint = hints[*index] & projected;
+                if hint != hints[*index] {
+                    hints[*index] = hint;
+                    for other in through[*index] {
+                        if !queued[other] {
+                            queued[other] = true;
+                            queue.push_back(other);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Solver<R: Region = Hexagon> {
+    puzzle: Puzzle<R>,
+    solution: Board<R>,
+    workspace: SolverWorkspace,
+    techniques: Arc<[Arc<dyn Technique<R>>]>,
+    trace: Vec<SolveStep>,
+    // The positions solved since the hints of the workspace were last
+    // projected, or none when they must be projected anew.
+    solved: Option<Vec<Position>>,
+    // The clues of the unsolved positions of each line, kept up to date
+    // as cells are placed, or none when they must be computed anew.
+    remaining: Option<HashMap<(Direction, Distance), Clue>>,
+}
+
+impl<R: Region> Solver<R> {
+    pub fn new(puzzle: Puzzle<R>) -> Self {
+        Self::with_workspace(puzzle, SolverWorkspace::new())
+    }
+
+    // Solves the puzzle with the buffers of an earlier solver.
+    pub fn with_workspace(puzzle: Puzzle<R>, workspace: SolverWorkspace) -> Self {
         let solution = puzzle.board().clone();
-        Solver { puzzle, solution }
+        let mut workspace = workspace;
+        workspace.layout(puzzle.board().region());
+        let remaining = workspace.count_remaining(&puzzle, &solution).ok();
+        Solver {
+            puzzle,
+            solution,
+            workspace,
+            techniques: technique::default_techniques().into(),
+            trace: vec![],
+            solved: None,
+            remaining,
+        }
     }
 
-    pub fn puzzle(&self) -> &Puzzle {
+    // Solves on from a player's partial board, holding the cells placed
+    // so far on top of the puzzle's givens. The board must keep the
+    // givens, and have no more cells of a color on any line than the
+    // puzzle's clue for it.
+    pub fn from_state(puzzle: Puzzle<R>, partial: Board<R>) -> Result<Self, SolveError> {
+        let region = puzzle.board().region();
+        for (position, cell) in puzzle.board().cells() {
+            if partial.cells().get(position) != Some(cell) {
+                return Err(SolveError::Contradiction(*position));
+            }
+        }
+        if let Some(position) = partial
+            .cells()
+            .keys()
+            .find(|position| !region.contains(**position))
+        {
+            return Err(SolveError::Contradiction(*position));
+        }
+
+        for (key, placed) in partial.clues() {
+            let (direction, distance) = key;
+            let clue = puzzle
+                .clues()
+                .get(&key)
+                .ok_or(SolveError::MissingClue(direction, distance))?;
+            if clue.checked_sub(placed).is_none() {
+                return Err(SolveError::ExceededClue(direction, distance));
+            }
+        }
+
+        let mut solver = Solver::new(puzzle);
+        for (position, cell) in partial.cells() {
+            solver.insert(*position, *cell);
+        }
+        Ok(solver)
+    }
+
+    // Solves with the given techniques, in order, instead of the default
+    // ones.
+    pub fn with_techniques(mut self, techniques: Vec<Arc<dyn Technique<R>>>) -> Self {
+        self.techniques = techniques.into();
+        self
+    }
+
+    pub fn techniques(&self) -> &[Arc<dyn Technique<R>>] {
+        &self.techniques
+    }
+
+    pub fn into_workspace(self) -> SolverWorkspace {
+        self.workspace
+    }
+
+    // The puzzle being solved, with any cells revealed to it, and the
+    // buffers of the solver.
+    pub fn into_parts(self) -> (Puzzle<R>, SolverWorkspace) {
+        (self.puzzle, self.workspace)
+    }
+
+    pub fn puzzle(&self) -> &Puzzle<R> {
         &self.puzzle
     }
 
-    pub fn solution(&self) -> &Board {
+    pub fn solution(&self) -> &Board<R> {
         &self.solution
     }
 
-    pub fn mut_puzzle(&mut self) -> &mut Puzzle {
+    // The puzzle can be changed in any way, so the solver keeps nothing
+    // it worked out from it before.
+    pub fn mut_puzzle(&mut self) -> &mut Puzzle<R> {
+        self.solved = None;
+        self.remaining = None;
         &mut self.puzzle
     }
 
-    pub fn mut_solution(&mut self) -> &mut Board {
+    // Like changing the puzzle, changing the solution directly makes the
+    // solver work everything out anew. Placing cells with `insert` keeps
+    // what it worked out up to date instead.
+    pub fn mut_solution(&mut self) -> &mut Board<R> {
+        self.solved = None;
+        self.remaining = None;
         &mut self.solution
     }
 
+    // Places the cell in the solution, updating the remaining clues of
+    // the lines through it and noting it for the next projection.
+    pub fn insert(&mut self, position: Position, cell: Cell) {
+        let previous = self.solution.cells().get(&position).copied();
+        if previous == Some(cell) {
+            return;
+        }
+        self.solution.insert(position, cell);
+
+        let Some(index) = self.workspace.indices.get(&position).copied() else {
+            self.solved = None;
+            self.remaining = None;
+            return;
+        };
+        let keys = self.workspace.through[index].map(|line| self.workspace.lines[line].0);
+
+        match (&mut self.solved, previous) {
+            (Some(solved), None) => solved.push(position),
+            // A changed cell may allow colors that were ruled out.
+            (solved, _previous) => *solved = None,
+        }
+
+        if let Some(remaining) = &mut self.remaining {
+            let placed = Clue::from_cells([cell].into_iter());
+            let lifted = Clue::from_cells(previous.into_iter());
+            for key in keys {
+                match remaining
+                    .get(&key)
+                    .and_then(|clue| (*clue + lifted).checked_sub(placed))
+                {
+                    Some(clue) => {
+                        remaining.insert(key, clue);
+                    }
+                    None => {
+                        self.remaining = None;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    // Reveals the cell to the puzzle as a given, and places it in the
+    // solution.
+    pub fn reveal(&mut self, position: Position, cell: Cell) {
+        self.puzzle.mut_board().insert(position, cell);
+        self.insert(position, cell);
+    }
+
+    // The positions solved so far, in the order they were solved, with
+    // the reasoning behind each of them.
+    pub fn trace(&self) -> &[SolveStep] {
+        &self.trace
+    }
+
+    // Places the cell of the step, recording the step unless its
+    // position was solved already.
+    pub fn place(&mut self, step: SolveStep) {
+        let (position, cell) = (step.position(), step.cell());
+        if !self.solution.cells().contains_key(&position) {
+            self.trace.push(step);
+        }
+        self.insert(position, cell);
+    }
+
+    // Applies the first of the solver's techniques that solves anything,
+    // and returns it.
+    pub fn step(&mut self) -> Option<Arc<dyn Technique<R>>> {
+        let techniques = self.techniques.clone();
+        techniques
+            .iter()
+            .find(|technique| technique.apply(self))
+            .cloned()
+    }
+
+    // Brings the hints of the workspace up to date with the solution.
+    fn project(&mut self) {
+        match self.solved.as_mut() {
+            Some(solved) => {
+                self.workspace
+                    .project_solved(&self.puzzle, &self.solution, solved);
+                solved.clear();
+            }
+            None => {
+                self.workspace.project(&self.puzzle, &self.solution);
+                self.solved = Some(vec![]);
+            }
+        }
+    }
+
     pub fn solve_hints(&mut self) -> bool {
-        let mut did_solve: bool = false;
-        for (position, hint) in self.computed_hints() {
+        self.project();
+
+        let SolverWorkspace {
+            cells,
+            hints,
+            pending,
+            ..
+        } = &mut self.workspace;
+
+        pending.clear();
+        for (index, hint) in hints.iter().enumerate() {
             if let Some(cell) = hint.solution() {
-                if !self.solution.cells().contains_key(&position) {
-                    self.solution.insert(position, cell);
-                    did_solve = true;
+                if cells[index].is_none() {
+                    pending.push((index, cell, None));
                 }
             }
         }
 
-        did_solve
+        self.place_pending()
     }
 
+    // Solves the positions of each line that its rule finds must be of
+    // a color, as all of its remaining cells are needed to fill the
+    // positions that allow it.
     pub fn solve_clues(&mut self) -> bool {
-        let mut did_solve: bool = false;
+        self.project();
 
-        let hints = self.computed_hints();
-        let mut new: HashMap<Position, Cell> = HashMap::new();
+        let SolverWorkspace {
+            lines,
+            cells,
+            hints,
+            line_cells,
+            line_hints,
+            pending,
+            ..
+        } = &mut self.workspace;
 
-        for ((direction, distance), computed_clue) in self.computed_clues() {
-            let segment = self
-                .puzzle
-                .board()
-                .hexagon()
-                .segment(distance, direction)
-                .unwrap();
+        pending.clear();
+        for (key, indices) in lines.iter() {
+            let Some(rule) = self.puzzle.clue_rules().get(key) else {
+                continue;
+            };
 
-            let mut hinted_clue = Clue::zero();
+            line_cells.clear();
+            line_cells.extend(indices.iter().map(|index| cells[*index]));
+            line_hints.clear();
+            line_hints.extend(indices.iter().map(|index| hints[*index]));
 
-            for position in segment {
-                if self.solution.cells().contains_key(&position) {
-                    continue;
+            for (index, cell) in indices.iter().zip(rule.exhaust(line_cells, line_hints)) {
+                if let Some(cell) = cell {
+                    pending.push((*index, cell, Some(*key)));
                 }
-
-                hinted_clue = hinted_clue + hints.get(&position).unwrap().clue()
             }
+        }
 
-            for cell in Cell::all() {
-                if hinted_clue.cell(cell) == computed_clue.cell(cell) {
-                    for position in segment {
-                        if self.solution.cells().contains_key(&position) {
-                            continue;
-                        }
+        self.place_pending()
+    }
 
-                        if hints.get(&position).unwrap().cell(cell) {
-                            new.insert(position, cell);
-                            did_solve = true;
-                        }
+    // Places the cells found by a pass of hints or clues, recording a
+    // step for each position, with the line whose clue needs it when
+    // there is one.
+    fn place_pending(&mut self) -> bool {
+        let pending = mem::take(&mut self.workspace.pending);
+
+        for (index, cell, key) in pending.iter() {
+            let position = self.workspace.positions[*index];
+            if !self.solution.cells().contains_key(&position) {
+                let step = match key {
+                    Some(key) => {
+                        let rule = self.puzzle.clue_rules()[key].clone();
+                        let colors = self.puzzle.palette().colors();
+                        SolveStep::new("clues", position, *cell).with_line(*key, rule, colors)
                     }
-                }
+                    None => SolveStep::new("hints", position, *cell),
+                };
+                self.trace.push(step);
             }
+            self.insert(position, *cell);
         }
 
-        for (position, cell) in new {
-            self.solution.insert(position, cell);
-        }
-
-        did_solve
+        let placed = !pending.is_empty();
+        self.workspace.pending = pending;
+        placed
     }
 
     pub fn solve(&mut self) -> bool {
-        while self.solve_hints() || self.solve_clues() {}
+        while self.step().is_some() {}
 
         self.solution.is_solved()
     }
 
-    pub fn computed_hints(&self) -> HashMap<Position, Hint> {
-        let mut hints = HashMap::new();
+    // Solves the puzzle one position at a time, as the steps are taken
+    // from the iterator, for stepping through or animating a solution.
+    // Each pass of the solver runs once the steps of the one before are
+    // used up, and the steps end when a pass solves nothing more.
+    pub fn steps(&mut self) -> Steps<'_, R> {
+        Steps {
+            next: self.trace.len(),
+            solver: self,
+        }
+    }
 
-        for ((direction, distance), clue) in self.computed_clues() {
-            let clue_hint = clue.hint();
-            let segment = self
-                .puzzle
-                .board()
-                .hexagon()
-                .segment(distance, direction)
-                .unwrap();
+    // Solves the puzzle as far as its clues allow, telling why when they
+    // do not lead to a solution that satisfies all of them.
+    pub fn try_solve(&mut self) -> Result<&Board<R>, SolveError> {
+        loop {
+            if let Some(position) = self.contradiction() {
+                return Err(SolveError::Contradiction(position));
+            }
 
-            for position in segment {
-                let hint = hints.get(&position).cloned().unwrap_or(Hint::any());
-                hints.insert(position, hint & clue_hint);
+            if self.step().is_none() {
+                break;
             }
         }
 
-        hints
+        let area = self.puzzle.board().region().clone().into_iter().count();
+        let unsolved = area - self.solution.cells().len();
+        if unsolved > 0 {
+            return Err(SolveError::IncompleteClues(unsolved));
+        }
+
+        Ok(&self.solution)
+    }
+
+    // Whether the solution is complete and satisfies the rule of every
+    // line of the puzzle.
+    pub fn is_satisfied(&self) -> bool {
+        self.solution.is_solved()
+            && self.lines().into_iter().all(|(rule, _positions, cells)| {
+                let cells: Vec<Cell> = cells.into_iter().flatten().collect();
+                rule.is_satisfied(&cells)
+            })
+    }
+
+    // A position at which the solution contradicts the puzzle: a solved
+    // position whose color no line allows, an unsolved position that no
+    // color is left for, or the first position of a line whose rule the
+    // complete solution does not satisfy.
+    pub fn contradiction(&self) -> Option<Position> {
+        let hints = self.computed_hints();
+
+        let conflict = hints
+            .iter()
+            .find(
+                |(position, hint)| match self.solution.cells().get(position) {
+                    Some(cell) => !hint.cell(*cell),
+                    None => hint.is_empty(),
+                },
+            )
+            .map(|(position, _hint)| *position);
+
+        if conflict.is_some() || !self.solution.is_solved() {
+            return conflict;
+        }
+
+        self.lines()
+            .into_iter()
+            .find(|(rule, _positions, cells)| {
+                let cells: Vec<Cell> = cells.iter().flatten().copied().collect();
+                !rule.is_satisfied(&cells)
+            })
+            .and_then(|(_rule, positions, _cells)| positions.first().copied())
     }
 
-    pub fn computed_clues(&self) -> HashMap<(Direction, Distance), Clue> {
-        let mut clues = self.puzzle.clues().clone();
+    // Finds a solution satisfying every rule of the puzzle, guessing the
+    // colors of positions with the fewest colors left whenever solving
+    // gets stuck, and backtracking on contradictions.
+    pub fn search(&self) -> Option<Board<R>> {
+        self.solutions().next()
+    }
 
-        for (key, solution_clue) in self.solution.clues() {
-            let puzzle_clue = clues.get(&key).cloned().unwrap();
-            let clue = puzzle_clue - solution_clue;
-            clues.insert(key, clue);
+    // Every solution satisfying every rule of the puzzle, found lazily
+    // by guessing and backtracking.
+    pub fn solutions(&self) -> Solutions<R> {
+        Solutions {
+            pending: vec![self.clone()],
         }
+    }
 
-        clues
+    // A position that follows from a player's partial board, with the
+    // reasoning behind it, as a hint towards their next move. The board
+    // holds the cells placed so far on top of the puzzle's givens. There
+    // is no next move when the board contradicts the puzzle, or when
+    // nothing more follows from it.
+    pub fn next_move(&self, current: &Board<R>) -> Option<SolveStep> {
+        let mut solver = Solver::with_workspace(self.puzzle.clone(), self.workspace.clone());
+        for (position, cell) in current.cells() {
+            solver.insert(*position, *cell);
+        }
+
+        if solver.contradiction().is_some() {
+            return None;
+        }
+
+        solver.steps().next()
+    }
+
+    // Explores what follows from tentatively placing the given cells,
+    // solving as far as possible without changing this solver.
+    pub fn what_if(&self, guesses: impl Iterator<Item = (Position, Cell)>) -> WhatIf {
+        let mut solver = self.clone();
+
+        for (position, cell) in guesses {
+            match solver.solution.cells().get(&position) {
+                Some(solved) if *solved != cell => return WhatIf::Contradiction(position),
+                Some(_solved) => {}
+                None => solver.insert(position, cell),
+            }
+        }
+        let guessed = solver.solution.clone();
+
+        loop {
+            if let Some(position) = solver.contradiction() {
+                return WhatIf::Contradiction(position);
+            }
+
+            if solver.step().is_none() {
+                break;
+            }
+        }
+
+        WhatIf::Follows(
+            solver
+                .solution
+                .cells()
+                .iter()
+                .filter(|(position, _cell)| !guessed.cells().contains_key(position))
+                .map(|(position, cell)| (*position, *cell))
+                .collect(),
+        )
+    }
+
+    // The colors each position could take, as projected by the rules of
+    // the lines through it. As rules may take into account the colors
+    // allowed by other lines, projecting is repeated until no more
+    // colors are ruled out.
+    pub fn computed_hints(&self) -> HashMap<Position, Hint> {
+        let hints = |workspace: &SolverWorkspace| {
+            workspace
+                .positions
+                .iter()
+                .copied()
+                .zip(workspace.hints.iter().copied())
+                .collect()
+        };
+
+        let mut workspace = match self.solved.as_deref() {
+            Some([]) => return hints(&self.workspace),
+            _ => self.workspace.clone(),
+        };
+        match self.solved.as_deref() {
+            Some(solved) => workspace.project_solved(&self.puzzle, &self.solution, solved),
+            None => workspace.project(&self.puzzle, &self.solution),
+        }
+        hints(&workspace)
+    }
+
+    // The number of lines with unsolved positions whose rule alone, not
+    // taking other lines into account, leaves every one of them a single
+    // color.
+    pub fn solved_clues(&self) -> usize {
+        self.lines()
+            .into_iter()
+            .filter(|(rule, _positions, cells)| {
+                let hints = vec![self.puzzle.palette().hint(); cells.len()];
+                cells.iter().any(Option::is_none)
+                    && cells
+                        .iter()
+                        .zip(rule.project(cells, &hints))
+                        .filter(|(cell, _hint)| cell.is_none())
+                        .all(|(_cell, hint)| hint.solution().is_some())
+            })
+            .count()
+    }
+
+    // The rule, positions and solved cells of each line, in the order
+    // of its segment.
+    pub(super) fn lines(&self) -> Vec<Line<'_>> {
+        self.puzzle
+            .clue_rules()
+            .iter()
+            .filter_map(|((direction, distance), rule)| {
+                let positions: Vec<Position> = self
+                    .puzzle
+                    .board()
+                    .region()
+                    .segment(*distance, *direction)?
+                    .into_iter()
+                    .collect();
+                let cells = positions
+                    .iter()
+                    .map(|position| self.solution.cells().get(position).copied())
+                    .collect();
+
+                Some((rule.as_ref(), positions, cells))
+            })
+            .collect()
+    }
+
+    // The clues of the unsolved positions of each line, as they are
+    // known to the puzzle's author.
+    pub fn computed_clues(&self) -> Result<HashMap<(Direction, Distance), Clue>, SolveError> {
+        match &self.remaining {
+            Some(remaining) => Ok(remaining.clone()),
+            None => self.workspace.count_remaining(&self.puzzle, &self.solution),
+        }
+    }
+}
+
+// Iterates over the steps of a solver as it solves its puzzle.
+pub struct Steps<'a, R: Region = Hexagon> {
+    solver: &'a mut Solver<R>,
+    next: usize,
+}
+
+impl<R: Region> Steps<'_, R> {
+    // The solution as far as the steps taken so far and the rest of
+    // their pass go.
+    pub fn solution(&self) -> &Board<R> {
+        &self.solver.solution
+    }
+}
+
+impl<R: Region> Iterator for Steps<'_, R> {
+    type Item = SolveStep;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next == self.solver.trace.len() {
+            self.solver.step()?;
+        }
+
+        self.next += 1;
+        Some(self.solver.trace[self.next - 1].clone())
+    }
+}
+
+// Iterates over the solutions of a puzzle, solving as far as possible
+// before each guess, and trying the colors of the guessed position in
+// order.
+pub struct Solutions<R: Region = Hexagon> {
+    pending: Vec<Solver<R>>,
+}
+
+impl<R: Region> Iterator for Solutions<R> {
+    type Item = Board<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(mut solver) = self.pending.pop() {
+            let contradicts = loop {
+                if solver.contradiction().is_some() {
+                    break true;
+                }
+
+                if solver.step().is_none() {
+                    break false;
+                }
+            };
+            if contradicts {
+                continue;
+            }
+
+            if solver.solution.is_solved() {
+                return Some(solver.solution);
+            }
+
+            let hints = solver.computed_hints();
+            let Some(position) = solver
+                .puzzle
+                .board()
+                .region()
+                .clone()
+                .into_iter()
+                .filter(|position| !solver.solution.cells().contains_key(position))
+                .min_by_key(|position| hints[position].clue().count())
+            else {
+                continue;
+            };
+
+            let guesses = Cell::all()
+                .into_iter()
+                .rev()
+                .filter(|cell| hints[&position].cell(*cell))
+                .map(|cell| {
+                    let mut guess = solver.clone();
+                    guess.insert(position, cell);
+                    guess
+                });
+            self.pending.extend(guesses);
+        }
+
+        None
     }
 }
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "generate")]
+    use rand::{rngs::StdRng, SeedableRng};
+
     use super::*;
-    use crate::{grid::ring::Ring, puzzle::board::Board};
+    #[cfg(feature = "generate")]
+    use crate::grid::{parallelogram::Parallelogram, triangle::Triangle};
+    use crate::puzzle::board::Board;
+    #[cfg(feature = "generate")]
+    use crate::puzzle::{refiner::Refiner, validator::Validator, ClueKind, ClueMix};
 
     #[test]
     fn test_solver() {
-        let mut board = Board::new(2).unwrap();
+        let mut puzzle = Puzzle::with_clues(Board::rings_fixture());
+        puzzle.clear();
+        assert!(puzzle.board().cells().is_empty());
+        let mut solver = Solver::new(puzzle);
+        assert!(solver.solve());
+    }
+
+    #[test]
+    fn try_solve() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+        let mut solver = Solver::new(puzzle.clone());
+        assert_eq!(board.cells(), solver.try_solve().unwrap().cells());
+
+        let mut solver = Solver::new(puzzle.clone());
+        solver.mut_solution().insert(Position::zero(), Cell::Blue);
+        assert!(matches!(
+            solver.try_solve(),
+            Err(SolveError::Contradiction(_))
+        ));
+
+        let mut unclued = puzzle.clone();
+        for key in puzzle.clue_rules().keys() {
+            unclued.remove_clue_rule(*key);
+        }
+        assert!(matches!(
+            Solver::new(unclued).try_solve(),
+            Err(SolveError::IncompleteClues(19))
+        ));
+
+        let mut solver = Solver::new(Puzzle::new(Board::new(2).unwrap(), std::iter::empty()));
+        solver.mut_solution().insert(Position::zero(), Cell::Red);
+        assert!(matches!(
+            solver.computed_clues(),
+            Err(SolveError::MissingClue(..))
+        ));
+    }
+
+    #[test]
+    fn trace() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+        let mut solver = Solver::new(puzzle);
+        assert!(solver.trace().is_empty());
+        assert!(solver.solve());
+
+        // Every position is solved once, to its color in the solution.
+        let trace = solver.trace();
+        assert_eq!(board.cells().len(), trace.len());
+        for step in trace {
+            assert_eq!(Some(&step.cell()), board.cells().get(&step.position()));
+            assert_eq!(step.technique() == "clues", step.line().is_some());
+            assert!(step.to_string().contains(&format!(
+                "({}, {}, {})",
+                step.position().x(),
+                step.position().y(),
+                step.position().z()
+            )));
+        }
+    }
+
+    #[test]
+    fn steps() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+        let mut solver = Solver::new(puzzle);
+
+        // Taking a single step solves no further than its pass.
+        let mut steps = solver.steps();
+        let first = steps.next().unwrap();
+        assert!(steps.solution().cells().contains_key(&first.position()));
+        assert!(!steps.solution().is_solved());
+
+        let rest: Vec<SolveStep> = steps.collect();
+        assert_eq!(board.cells().len(), rest.len() + 1);
+        assert!(solver.solution().is_solved());
+        assert_eq!(rest.len() + 1, solver.trace().len());
+        assert!(solver.steps().next().is_none());
+    }
+
+    #[test]
+    fn next_move() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+        let solver = Solver::new(puzzle);
+
+        let mut current = Board::new(2).unwrap();
+        let step = solver.next_move(&current).unwrap();
+        assert_eq!(Some(&step.cell()), board.cells().get(&step.position()));
+        assert!(solver.trace().is_empty());
+
+        // The move is one the player has not made yet.
+        current.insert(step.position(), step.cell());
+        let next = solver.next_move(&current).unwrap();
+        assert!(!current.cells().contains_key(&next.position()));
+
+        assert!(solver.next_move(&board).is_none());
+        current.insert(Position::zero(), Cell::Blue);
+        assert!(solver.next_move(&current).is_none());
+    }
+
+    #[test]
+    fn from_state() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+
+        let mut partial = Board::new(2).unwrap();
+        partial.insert(Position::zero(), Cell::Red);
+        let mut solver = Solver::from_state(puzzle.clone(), partial.clone()).unwrap();
+        assert_eq!(
+            Some(&Cell::Red),
+            solver.solution().cells().get(&Position::zero())
+        );
+        assert!(solver.solve());
+        assert_eq!(board.cells(), solver.solution().cells());
+
+        // The middle row has a single red cell.
+        partial.insert(Direction::XY.position(), Cell::Red);
+        assert!(matches!(
+            Solver::from_state(puzzle.clone(), partial),
+            Err(SolveError::ExceededClue(_, _))
+        ));
+
+        puzzle.mut_board().insert(Position::zero(), Cell::Red);
+        assert_eq!(
+            Some(SolveError::Contradiction(Position::zero())),
+            Solver::from_state(puzzle, Board::new(2).unwrap()).err()
+        );
+    }
+
+    #[test]
+    fn what_if() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+        let solver = Solver::new(puzzle);
+        assert_eq!(None, solver.contradiction());
+
+        // The right guess leads to the rest of the solution, while the
+        // solver itself is left untouched.
+        match solver.what_if([(Position::zero(), Cell::Red)].into_iter()) {
+            WhatIf::Follows(cells) => {
+                assert_eq!(board.cells().len() - 1, cells.len());
+                for (position, cell) in cells {
+                    assert_eq!(board.cells()[&position], cell);
+                }
+            }
+            WhatIf::Contradiction(position) => panic!("contradiction at {position:?}"),
+        }
+        assert!(solver.solution().cells().is_empty());
+
+        assert!(matches!(
+            solver.what_if([(Position::zero(), Cell::Blue)].into_iter()),
+            WhatIf::Contradiction(_)
+        ));
+    }
+
+    #[test]
+    fn solutions() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+        let solutions: Vec<Board> = Solver::new(puzzle.clone()).solutions().collect();
+        assert_eq!(1, solutions.len());
+        assert_eq!(board.cells(), solutions[0].cells());
+
+        // Without any clues, the two positions left open can take any
+        // pair of colors.
+        for (key, _clue) in board.clues() {
+            puzzle.remove_clue_rule(key);
+        }
+        let mut solver = Solver::new(puzzle);
+        let open = [Position::zero(), Direction::XY.position()];
+        for (position, cell) in board.cells() {
+            if !open.contains(position) {
+                solver.mut_solution().insert(*position, *cell);
+            }
+        }
+
+        let solutions: Vec<Board> = solver.solutions().collect();
+        assert_eq!(9, solutions.len());
+        for (index, solution) in solutions.iter().enumerate() {
+            assert!(solution.is_solved());
+            assert!(solutions[..index]
+                .iter()
+                .all(|other| other.cells() != solution.cells()));
+        }
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn clue_mix() {
+        let mut rng = StdRng::seed_from_u64(17);
+        let mix: ClueMix = "full=2, ordered=1,contiguous=0".parse().unwrap();
 
-        board.insert(Position::zero(), Cell::Red);
+        assert_eq!(
+            vec![ClueKind::Full, ClueKind::Ordered],
+            mix.kinds().collect::<Vec<_>>()
+        );
+        assert!("full=x".parse::<ClueMix>().is_err());
+        assert!("diagonal".parse::<ClueMix>().is_err());
 
-        for position in Ring::zero(1).unwrap() {
-            board.insert(position, Cell::Green);
+        // Lines only get the kinds of clue the mix gives weight to, and the
+        // solution still satisfies every one of them.
+        let board = Board::random(&mut rng, 3).unwrap();
+        let puzzle = Puzzle::with_clues(board.clone())
+            .with_clue_mix(&mut rng, &mix)
+            .unwrap();
+        let kinds: Vec<ClueKind> = puzzle
+            .clue_rules()
+            .values()
+            .filter_map(|rule| Some(rule.kind()?.0))
+            .collect();
+
+        assert_eq!(puzzle.clue_rules().len(), kinds.len());
+        assert!(kinds.contains(&ClueKind::Full) && kinds.contains(&ClueKind::Ordered));
+        assert!(!kinds.contains(&ClueKind::Contiguous));
+        assert!(Solver::new(puzzle.clone()).is_satisfied());
+
+        let mut cleared = puzzle.clone();
+        cleared.clear();
+        let mut solver = Solver::new(cleared);
+        solver.solve();
+        assert!(solver
+            .solution()
+            .cells()
+            .iter()
+            .all(|(position, cell)| board.cells().get(position) == Some(cell)));
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn clue_kinds() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let refiner = Refiner::new(Validator::new(vec![]));
+
+        let mix = ClueMix::new(ClueKind::all().map(|kind| (kind, 1.0)).to_vec());
+
+        for kind in ClueKind::all().repeat(4) {
+            let board = Board::random(&mut rng, 3).unwrap();
+            let solution = Puzzle::with_clues(board.clone())
+                .with_clue_kind(kind)
+                .unwrap();
+
+            assert!(Solver::new(solution.clone()).is_satisfied());
+
+            for solution in [
+                solution,
+                Puzzle::with_clues(board)
+                    .with_clue_mix(&mut rng, &mix)
+                    .unwrap(),
+            ] {
+                let puzzle = refiner.refine(solution.clone()).unwrap();
+                let mut solver = Solver::new(puzzle);
+                assert!(solver.solve());
+                assert_eq!(solution.board().cells(), solver.solution().cells());
+            }
         }
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn workspace() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let refiner = Refiner::new(Validator::new(vec![]));
+        let mut workspace = SolverWorkspace::new();
+
+        for radius in [2, 3, 3, 2] {
+            let puzzle = refiner.refined(&mut rng, Board::generator(radius).unwrap());
+
+            let mut solver = Solver::with_workspace(puzzle.clone(), workspace);
+            let hints = solver.computed_hints();
+            assert_eq!(Solver::new(puzzle.clone()).computed_hints(), hints);
+            assert!(solver.solve());
+            assert!(solver.is_satisfied());
+
+            workspace = solver.into_workspace();
+        }
+    }
 
-        for position in Ring::zero(2).unwrap() {
-            board.insert(position, Cell::Blue);
+    // Refines a random board of the region into a puzzle, and solves it
+    // with the buffers of the given workspace.
+    #[cfg(feature = "generate")]
+    fn refined_on<R: Region>(
+        rng: &mut StdRng,
+        region: R,
+        workspace: SolverWorkspace,
+    ) -> SolverWorkspace {
+        let board = Board::random_in(rng, region);
+        let puzzle = Refiner::new(Validator::new(vec![]))
+            .refine(Puzzle::with_clues_in(board.clone()).unwrap())
+            .unwrap();
+
+        let mut solver = Solver::with_workspace(puzzle, workspace);
+        assert!(solver.solve());
+        assert!(solver.is_satisfied());
+        assert_eq!(board.cells(), solver.solution().cells());
+        solver.into_workspace()
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn regions() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let mut workspace = SolverWorkspace::new();
+
+        // Puzzles on other regions than hexagons are refined and solved
+        // alike, with the workspace laid out anew whenever the region
+        // changes.
+        for _ in 0..3 {
+            workspace = refined_on(&mut rng, Parallelogram::zero(4, 3).unwrap(), workspace);
+            workspace = refined_on(&mut rng, Triangle::zero(5).unwrap(), workspace);
+            workspace = refined_on(&mut rng, Hexagon::zero(2).unwrap(), workspace);
         }
+    }
 
-        let mut puzzle = Puzzle::with_clues(board);
+    #[cfg(feature = "generate")]
+    #[test]
+    fn incremental() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let board = Board::random(&mut rng, 4).unwrap();
+        let mut puzzle = Puzzle::with_clues(board.clone());
         puzzle.clear();
+
+        // Whatever the solver keeps up to date as it places cells is the
+        // same as what a new solver works out for the same solution.
+        let mut solver = Solver::new(puzzle);
+        let unsolved: Vec<Position> = board.hexagon().into_iter().collect();
+        for position in unsolved {
+            while solver.step().is_some() {}
+            if solver.solution().cells().contains_key(&position) {
+                continue;
+            }
+            solver.reveal(position, board.cells()[&position]);
+
+            let mut fresh = Solver::new(solver.puzzle().clone());
+            *fresh.mut_solution() = solver.solution().clone();
+            assert_eq!(fresh.computed_hints(), solver.computed_hints());
+            assert_eq!(fresh.computed_clues(), solver.computed_clues());
+        }
+        assert!(solver.is_satisfied());
+
+        // Changing a cell directly makes the solver work it out anew.
+        let position = Position::zero();
+        let cell = Cell::all()
+            .into_iter()
+            .find(|cell| *cell != board.cells()[&position])
+            .unwrap();
+        solver.mut_solution().insert(position, cell);
+        assert!(solver.computed_clues().is_err());
+        assert!(solver.contradiction().is_some());
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn single_cell() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let refiner = Refiner::new(Validator::new(vec![]));
+        let puzzle = refiner.refined(&mut rng, Board::generator(0).unwrap());
+
         assert!(puzzle.board().cells().is_empty());
+        assert_eq!(3, puzzle.clue_rules().len());
+        assert_eq!(3, puzzle.to_string().matches("- ").count());
+
         let mut solver = Solver::new(puzzle);
         assert!(solver.solve());
+        assert!(solver.is_satisfied());
+        assert_eq!(1, solver.solution().cells().len());
     }
 }
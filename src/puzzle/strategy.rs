@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::grid::hexagon::Hexagon;
+use crate::grid::region::Region;
+use crate::grid::{Direction, Distance, Position};
+
+use super::puzzle::Puzzle;
+use super::solver::{SolveError, Solver};
+use super::{Cell, Clue};
+
+// Picks the cell of the solution that a refiner reveals whenever the
+// solver stalls. Which cells are revealed shapes how the refined puzzles
+// play, from where their givens are to how much is left to deduce.
+pub trait RefineStrategy<R: Region = Hexagon>: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // A position the solver has not solved, with its cell in the
+    // solution, or nothing if there is no cell to reveal. Fails when the
+    // solver cannot tell what is left of the puzzle's clues.
+    fn select(
+        &self,
+        solution: &Puzzle<R>,
+        solver: &Solver<R>,
+    ) -> Result<Option<(Position, Cell)>, SolveError>;
+}
+
+// Reveals the most common remaining cell of the computed clue with the
+// fewest remaining cells. This is the strategy refiners use by default.
+pub struct LowestClue;
+
+impl<R: Region> RefineStrategy<R> for LowestClue {
+    fn name(&self) -> &'static str {
+        "lowest-clue"
+    }
+
+    fn select(
+        &self,
+        solution: &Puzzle<R>,
+        solver: &Solver<R>,
+    ) -> Result<Option<(Position, Cell)>, SolveError> {
+        let computed_clues = solver.computed_clues()?;
+
+        Ok(
+            lowest_computed_clue(computed_clues).and_then(|((direction, distance), clue)| {
+                let cell = clue.max_cell()?;
+                let position = unsolved_position(solution, solver, direction, distance, cell)?;
+                Some((position, cell))
+            }),
+        )
+    }
+}
+
+// Reveals an unsolved position picked at random.
+pub struct RandomCell(Mutex<StdRng>);
+
+impl RandomCell {
+    pub fn new(seed: u64) -> Self {
+        RandomCell(Mutex::new(StdRng::seed_from_u64(seed)))
+    }
+
+    pub fn from_entropy() -> Self {
+        RandomCell(Mutex::new(StdRng::from_entropy()))
+    }
+}
+
+impl<R: Region> RefineStrategy<R> for RandomCell {
+    fn name(&self) -> &'static str {
+        "random-cell"
+    }
+
+    fn select(
+        &self,
+        solution: &Puzzle<R>,
+        solver: &Solver<R>,
+    ) -> Result<Option<(Position, Cell)>, SolveError> {
+        let mut unsolved: Vec<(Position, Cell)> = solution
+            .board()
+            .cells()
+            .iter()
+            .filter(|(position, _cell)| !solver.solution().cells().contains_key(position))
+            .map(|(position, cell)| (*position, *cell))
+            .collect();
+        unsolved.sort_by_key(|(position, _cell)| position.coordinates());
+
+        Ok(unsolved.choose(&mut *self.0.lock().unwrap()).copied())
+    }
+}
+
+// Reveals the unsolved position that the computed hints leave the
+// fewest colors for, which gives away the least that the solver could
+// not nearly deduce on its own.
+pub struct MostConstrained;
+
+impl<R: Region> RefineStrategy<R> for MostConstrained {
+    fn name(&self) -> &'static str {
+        "most-constrained"
+    }
+
+    fn select(
+        &self,
+        solution: &Puzzle<R>,
+        solver: &Solver<R>,
+    ) -> Result<Option<(Position, Cell)>, SolveError> {
+        Ok(solver
+            .computed_hints()
+            .into_iter()
+            .filter(|(position, _hint)| !solver.solution().cells().contains_key(position))
+            .filter_map(|(position, hint)| {
+                let colors = Cell::all()
+                    .into_iter()
+                    .filter(|cell| hint.cell(*cell))
+                    .count();
+                Some((colors, position, *solution.board().cells().get(&position)?))
+            })
+            .min_by_key(|(colors, position, _cell)| (*colors, position.coordinates()))
+            .map(|(_colors, position, cell)| (position, cell)))
+    }
+}
+
+// Tries revealing each of the given number of cells that `LowestClue`
+// would consider, and reveals the one after which the solver places the
+// fewest cells, leaving as much as possible for the player to deduce.
+// This makes for more difficult puzzles at the cost of solving the
+// partial puzzle once for every cell tried.
+pub struct Lookahead(pub usize);
+
+impl<R: Region> RefineStrategy<R> for Lookahead {
+    fn name(&self) -> &'static str {
+        "lookahead"
+    }
+
+    fn select(
+        &self,
+        solution: &Puzzle<R>,
+        solver: &Solver<R>,
+    ) -> Result<Option<(Position, Cell)>, SolveError> {
+        Ok(reveals(solution, solver, self.0.max(1))?
+            .into_iter()
+            .min_by_key(|(position, cell)| {
+                let mut guess = solver.clone();
+                guess.reveal(*position, *cell);
+                guess.solve();
+                guess.solution().cells().len()
+            }))
+    }
+}
+
+// The cells that could be revealed to make progress: the most common
+// remaining cell of each of the given number of computed clues with the
+// fewest remaining cells.
+pub(super) fn reveals<R: Region>(
+    solution: &Puzzle<R>,
+    solver: &Solver<R>,
+    count: usize,
+) -> Result<Vec<(Position, Cell)>, SolveError> {
+    let mut computed_clues: Vec<((Direction, Distance), Clue)> = solver
+        .computed_clues()?
+        .into_iter()
+        .filter(|(_key, clue)| !clue.is_empty())
+        .collect();
+    computed_clues.sort_by_key(|(_key, clue)| clue.count());
+
+    let mut reveals = vec![];
+    for ((direction, distance), clue) in computed_clues {
+        let cell = clue.max_cell().unwrap();
+        if let Some(position) = unsolved_position(solution, solver, direction, distance, cell) {
+            if !reveals.contains(&(position, cell)) {
+                reveals.push((position, cell));
+            }
+        }
+
+        if reveals.len() >= count {
+            break;
+        }
+    }
+
+    Ok(reveals)
+}
+
+fn lowest_computed_clue(
+    computed_clues: HashMap<(Direction, Distance), Clue>,
+) -> Option<((Direction, Distance), Clue)> {
+    computed_clues
+        .iter()
+        .filter(|(_key, clue)| !clue.is_empty())
+        .min_by_key(|(_key, clue)| clue.count())
+        .map(|(key, clue)| (*key, *clue))
+}
+
+// The first position of the line that the solver has not solved and
+// that has the given cell in the solution.
+fn unsolved_position<R: Region>(
+    solution: &Puzzle<R>,
+    solver: &Solver<R>,
+    direction: Direction,
+    distance: Distance,
+    cell: Cell,
+) -> Option<Position> {
+    solution
+        .board()
+        .segment(distance, direction)?
+        .find(|(position, found_cell)| {
+            !solver.solution().cells().contains_key(position) && found_cell == &Some(cell)
+        })
+        .map(|(position, _)| position)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::puzzle::board::Board;
+    use crate::puzzle::refiner::Refiner;
+    use crate::puzzle::validator::Validator;
+
+    #[test]
+    fn strategies() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let strategies: Vec<Arc<dyn RefineStrategy>> = vec![
+            Arc::new(LowestClue),
+            Arc::new(RandomCell::new(11)),
+            Arc::new(MostConstrained),
+            Arc::new(Lookahead(4)),
+        ];
+
+        for _ in 0..3 {
+            let solution = Puzzle::with_clues(Board::random(&mut rng, 3).unwrap());
+
+            for strategy in &strategies {
+                let refiner =
+                    Refiner::new(Validator::new(vec![])).with_strategy(Arc::clone(strategy));
+                let puzzle = refiner.refine(solution.clone()).unwrap();
+
+                let mut solver = Solver::new(puzzle.clone());
+                assert!(solver.solve(), "{}", strategy.name());
+                assert_eq!(solution.board().cells(), solver.solution().cells());
+                assert!(puzzle
+                    .board()
+                    .cells()
+                    .iter()
+                    .all(|(position, cell)| solution.board().cells().get(position) == Some(cell)));
+            }
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+use crate::grid::hexagon::Hexagon;
+use crate::grid::layout::{Layout, Orientation, Point};
+use crate::grid::Position;
+
+use super::Hint;
+
+// A closed polygon in an arbitrary coordinate space, where `y` grows
+// downwards, as in SVG documents.
+pub type Polygon = Vec<Point>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathError {
+    UnsupportedCommand(char),
+    InvalidNumber(String),
+    MissingCoordinates(char),
+    MissingCommand,
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathError::UnsupportedCommand(command) => {
+                write!(f, "unsupported path command `{command}`")
+            }
+            PathError::InvalidNumber(number) => write!(f, "invalid number `{number}`"),
+            PathError::MissingCoordinates(command) => {
+                write!(f, "missing coordinates for path command `{command}`")
+            }
+            PathError::MissingCommand => write!(f, "missing path command"),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+enum Token {
+    Command(char),
+    Number(f64),
+}
+
+fn tokenize(path: &str) -> Result<Vec<Token>, PathError> {
+    let mut tokens = vec![];
+    let characters: Vec<char> = path.chars().collect();
+    let mut index = 0;
+
+    while index < characters.len() {
+        let character = characters[index];
+
+        if character.is_whitespace() || character == ',' {
+            index += 1;
+        } else if character.is_ascii_alphabetic() && character != 'e' && character != 'E' {
+            tokens.push(Token::Command(character));
+            index += 1;
+        } else {
+            // Numbers may follow each other without separators, as long
+            // as the next one starts with a sign or a second period.
+            let start = index;
+            let mut seen_period = false;
+            let mut seen_exponent = false;
+
+            if characters[index] == '-' || characters[index] == '+' {
+                index += 1;
+            }
+
+            while index < characters.len() {
+                match characters[index] {
+                    '0'..='9' => {}
+                    '.' if !seen_period && !seen_exponent => seen_period = true,
+                    'e' | 'E' if !seen_exponent => {
+                        seen_exponent = true;
+                        if index + 1 < characters.len()
+                            && (characters[index + 1] == '-' || characters[index + 1] == '+')
+                        {
+                            index += 1;
+                        }
+                    }
+                    _ => break,
+                }
+                index += 1;
+            }
+
+            let number: String = characters[start..index].iter().collect();
+            if index == start {
+                return Err(PathError::InvalidNumber(character.to_string()));
+            }
+            tokens.push(Token::Number(
+                number
+                    .parse()
+                    .map_err(|_| PathError::InvalidNumber(number))?,
+            ));
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Parses the outline of an SVG path made of straight lines, that is,
+// using only the `M`, `L`, `H`, `V` and `Z` commands, in their absolute
+// or relative forms. Every subpath is returned as a separate polygon.
+pub fn parse_path(path: &str) -> Result<Vec<Polygon>, PathError> {
+    let tokens = tokenize(path)?;
+    let mut polygons: Vec<Polygon> = vec![];
+    let mut current: Polygon = vec![];
+    let mut point = (0.0, 0.0);
+    let mut command = None;
+    let mut index = 0;
+
+    let number = |index: &mut usize, command: char| -> Result<f64, PathError> {
+        match tokens.get(*index) {
+            Some(Token::Number(number)) => {
+                *index += 1;
+                Ok(*number)
+            }
+            _ => Err(PathError::MissingCoordinates(command)),
+        }
+    };
+
+    while index < tokens.len() {
+        if let Token::Command(character) = tokens[index] {
+            command = Some(character);
+            index += 1;
+
+            if character == 'Z' || character == 'z' {
+                if !current.is_empty() {
+                    point = current[0];
+                    polygons.push(std::mem::take(&mut current));
+                }
+                continue;
+            }
+        }
+
+        let character = command.ok_or(PathError::MissingCommand)?;
+        let relative = character.is_ascii_lowercase();
+        let origin = if relative { point } else { (0.0, 0.0) };
+
+        point = match character.to_ascii_uppercase() {
+            'M' | 'L' => {
+                let x = number(&mut index, character)?;
+                let y = number(&mut index, character)?;
+                (origin.0 + x, origin.1 + y)
+            }
+            'H' => (origin.0 + number(&mut index, character)?, point.1),
+            'V' => (point.0, origin.1 + number(&mut index, character)?),
+            _ => return Err(PathError::UnsupportedCommand(character)),
+        };
+
+        if character.eq_ignore_ascii_case(&'M') {
+            if current.len() > 1 {
+                polygons.push(std::mem::take(&mut current));
+            }
+            current.clear();
+            // Coordinates following a move are treated as lines.
+            command = Some(if relative { 'l' } else { 'L' });
+        }
+
+        current.push(point);
+    }
+
+    if current.len() > 1 {
+        polygons.push(current);
+    }
+
+    Ok(polygons)
+}
+
+// Whether the point is inside the polygons, following the even-odd rule,
+// such that polygons within polygons become holes.
+fn is_inside(polygons: &[Polygon], point: Point) -> bool {
+    let (x, y) = point;
+    let mut inside = false;
+
+    for polygon in polygons {
+        for index in 0..polygon.len() {
+            let (ax, ay) = polygon[index];
+            let (bx, by) = polygon[(index + 1) % polygon.len()];
+
+            if (ay > y) != (by > y) && x < ax + (y - ay) * (bx - ax) / (by - ay) {
+                inside = !inside;
+            }
+        }
+    }
+
+    inside
+}
+
+// The positions of the hexagon whose cell centers lie inside the
+// polygons, once the polygons are scaled and centered to fit the
+// hexagon as snugly as possible.
+pub fn rasterize(polygons: &[Polygon], hexagon: Hexagon) -> HashSet<Position> {
+    rasterize_scaled(polygons, hexagon, 1.0)
+}
+
+// Like `rasterize`, but scales the fitted polygons around the center of
+// the hexagon by the given factor, such that factors below one leave a
+// margin around them.
+pub fn rasterize_scaled(polygons: &[Polygon], hexagon: Hexagon, factor: f64) -> HashSet<Position> {
+    let points = polygons.iter().flatten();
+    let (min_x, min_y, max_x, max_y) = points.fold(
+        (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+        |(min_x, min_y, max_x, max_y), (x, y)| {
+            (min_x.min(*x), min_y.min(*y), max_x.max(*x), max_y.max(*y))
+        },
+    );
+
+    if min_x > max_x {
+        return HashSet::new();
+    }
+
+    let layout = Layout::new(Orientation::PointyTop, 1.0, (0.0, 0.0));
+    let centers: Vec<(Position, Point)> = hexagon
+        .into_iter()
+        .map(|position| (position, layout.position_to_pixel(position)))
+        .collect();
+    let (left, top, right, bottom) = centers.iter().fold(
+        (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+        |(left, top, right, bottom), (_position, (x, y))| {
+            (left.min(*x), top.min(*y), right.max(*x), bottom.max(*y))
+        },
+    );
+
+    let scale = ((right - left) / (max_x - min_x).max(f64::EPSILON))
+        .min((bottom - top) / (max_y - min_y).max(f64::EPSILON))
+        * factor;
+    let (center_x, center_y) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+    let (hexagon_x, hexagon_y) = ((left + right) / 2.0, (top + bottom) / 2.0);
+
+    centers
+        .into_iter()
+        .filter(|(_position, (x, y))| {
+            let point = (
+                center_x + (x - hexagon_x) / scale,
+                center_y + (y - hexagon_y) / scale,
+            );
+            is_inside(polygons, point)
+        })
+        .map(|(position, _point)| position)
+        .collect()
+}
+
+// Builds a hint mask for the hexagon, giving positions inside the
+// polygons the first hint and every other position the second one.
+pub fn hint_mask(
+    polygons: &[Polygon],
+    hexagon: Hexagon,
+    inside: Hint,
+    outside: Hint,
+) -> HashMap<Position, Hint> {
+    let rasterized = rasterize(polygons, hexagon);
+
+    hexagon
+        .into_iter()
+        .map(|position| {
+            let hint = if rasterized.contains(&position) {
+                inside
+            } else {
+                outside
+            };
+            (position, hint)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Ok(vec![vec![(0.0, 0.0), (10.0, 0.0), (10.0, 5.0), (0.0, 5.0)]]),
+            parse_path("M0,0 L10 0 V5 H0 Z")
+        );
+        assert_eq!(
+            Ok(vec![
+                vec![(1.0, 1.0), (3.0, 1.0), (3.0, -0.5)],
+                vec![(5.0, 5.0), (6.0, 5.0), (6.0, 6.0)]
+            ]),
+            parse_path("m1 1 2 0 0-1.5z M5,5 h1 v1")
+        );
+        assert_eq!(
+            Ok(vec![vec![(0.5, 0.5), (1e2, -2.0)]]),
+            parse_path("M.5.5L1e2-2")
+        );
+
+        assert_eq!(
+            Err(PathError::UnsupportedCommand('C')),
+            parse_path("M0 0 C1 1 2 2 3 3")
+        );
+        assert_eq!(
+            Err(PathError::MissingCoordinates('L')),
+            parse_path("M0 0 L1")
+        );
+        assert_eq!(Err(PathError::MissingCommand), parse_path("1 2"));
+    }
+
+    #[test]
+    fn rasterize_polygons() {
+        let hexagon = Hexagon::zero(4).unwrap();
+
+        // A square around the center, with a square hole in its middle.
+        let polygons = parse_path("M-1 -1 H1 V1 H-1 Z M-0.1 -0.1 H0.1 V0.1 H-0.1 Z").unwrap();
+        let rasterized = rasterize(&polygons, hexagon);
+
+        assert!(!rasterized.contains(&Position::zero()));
+        assert!(rasterized.contains(&Position::new((1, -2, 1)).unwrap()));
+        assert!(!rasterized.contains(&Position::new((4, -4, 0)).unwrap()));
+
+        // A triangle pointing upwards, scaled to cover the hexagon.
+        let polygons = vec![vec![(0.0, 0.0), (1.0, 2.0), (-1.0, 2.0)]];
+        let rasterized = rasterize(&polygons, hexagon);
+
+        assert!(rasterized.contains(&Position::zero()));
+        assert!(rasterized.contains(&Position::new((1, 1, -2)).unwrap()));
+        assert!(!rasterized.contains(&Position::new((0, -4, 4)).unwrap()));
+        assert!(!rasterized.contains(&Position::new((4, -4, 0)).unwrap()));
+
+        let scaled = rasterize_scaled(&polygons, hexagon, 0.5);
+        assert!(scaled.len() < rasterized.len());
+        assert!(scaled.is_subset(&rasterized));
+
+        let mask = hint_mask(
+            &polygons,
+            hexagon,
+            Hint::new(true, false, false),
+            Hint::new(false, true, true),
+        );
+        assert_eq!(hexagon.into_iter().count(), mask.len());
+        assert_eq!(
+            rasterized.len(),
+            mask.values()
+                .filter(|hint| **hint == Hint::new(true, false, false))
+                .count()
+        );
+    }
+}
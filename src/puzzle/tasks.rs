@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tokio::task::{self, JoinError};
+
+use super::puzzle::{Generator, Puzzle};
+use super::refiner::{Budget, Refiner};
+use super::solver::Solver;
+
+// Signals a blocking task to stop when the future waiting on it is
+// dropped before the task finishes.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+// Refines a puzzle on a blocking thread. Dropping the returned future
+// cancels the refinement before its next attempt.
+pub async fn generate_async<G>(refiner: Arc<Refiner>, generator: G) -> Result<Puzzle, JoinError>
+where
+    G: Generator<StdRng> + Send + 'static,
+{
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let _guard = CancelOnDrop(Arc::clone(&cancelled));
+
+    let puzzle = task::spawn_blocking(move || {
+        let mut rng = StdRng::from_entropy();
+        let budget = Budget::unlimited().with_cancellation(cancelled);
+        refiner.refined_within(&mut rng, generator, &budget)
+    })
+    .await?;
+
+    // The refinement can only be cancelled by dropping this future, in
+    // which case its result is never observed.
+    Ok(puzzle.unwrap())
+}
+
+// Solves a puzzle on a blocking thread, returning the solver with the
+// solution it reached. Dropping the returned future discards the result,
+// but solving a puzzle always finishes in a bounded number of passes.
+pub async fn solve_async(puzzle: Puzzle) -> Result<(bool, Solver), JoinError> {
+    task::spawn_blocking(move || {
+        let mut solver = Solver::new(puzzle);
+        let solved = solver.solve();
+        (solved, solver)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::runtime::Builder;
+
+    use super::*;
+    use crate::puzzle::board::Board;
+    use crate::puzzle::puzzle::GeneratorFn;
+    use crate::puzzle::validator::{MaximumSolvedPositions, Validator};
+
+    #[test]
+    fn generate_and_solve() {
+        let runtime = Builder::new_current_thread().build().unwrap();
+        let validator = Validator::new(vec![Box::new(MaximumSolvedPositions(7))]);
+        let refiner = Arc::new(Refiner::new(validator));
+        let generator: GeneratorFn<StdRng> = Board::generator(2).unwrap();
+
+        runtime.block_on(async {
+            let puzzle = generate_async(refiner, generator).await.unwrap();
+            assert!(puzzle.board().cells().len() <= 7);
+
+            let (solved, solver) = solve_async(puzzle).await.unwrap();
+            assert!(solved);
+            assert!(solver.solution().is_solved());
+        });
+    }
+}
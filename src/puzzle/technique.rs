@@ -0,0 +1,496 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::grid::hexagon::Hexagon;
+use crate::grid::region::Region;
+use crate::grid::{Direction, Position};
+
+use super::solver::{Line, Solver, WhatIf};
+use super::trace::SolveStep;
+use super::{Cell, Clue, ClueKind, Count, Hint};
+
+// A way for the solver to deduce the colors of positions from the rules
+// of the puzzle and the cells solved so far. Solvers try their
+// techniques in order, starting over from the first one whenever one of
+// them solves something, so cheaper techniques should come first.
+pub trait Technique<R: Region = Hexagon>: Debug + Send + Sync {
+    // A short name of the technique, which the steps it takes are
+    // explained by as the `step.<name>` message.
+    fn name(&self) -> &'static str;
+
+    // How much each pass of the technique adds to the difficulty of a
+    // puzzle.
+    fn cost(&self) -> f64;
+
+    // Solves the positions it can, placing them with `Solver::place`,
+    // and tells whether it solved any.
+    fn apply(&self, solver: &mut Solver<R>) -> bool;
+}
+
+// Solves the positions that the rules of the lines through them leave a
+// single color, as `Solver::solve_hints` does.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct HintsTechnique;
+
+impl<R: Region> Technique<R> for HintsTechnique {
+    fn name(&self) -> &'static str {
+        "hints"
+    }
+
+    fn cost(&self) -> f64 {
+        1.0
+    }
+
+    fn apply(&self, solver: &mut Solver<R>) -> bool {
+        solver.solve_hints()
+    }
+}
+
+// Solves the positions that the rule of a line needs to be of a color,
+// as `Solver::solve_clues` does.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct CluesTechnique;
+
+impl<R: Region> Technique<R> for CluesTechnique {
+    fn name(&self) -> &'static str {
+        "clues"
+    }
+
+    fn cost(&self) -> f64 {
+        2.0
+    }
+
+    fn apply(&self, solver: &mut Solver<R>) -> bool {
+        solver.solve_clues()
+    }
+}
+
+// Solves the positions for which all colors but one would leave one of
+// the lines through them without a way to fill its other positions,
+// given the colors those positions are left. Trying a color on the lines
+// through a position catches what projecting each line onto all of its
+// positions at once misses. It is not one of the default techniques.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct IntersectionTechnique;
+
+impl IntersectionTechnique {
+    // Whether the line can still be filled with the cell at the index.
+    fn fits(line: &Line, hints: &HashMap<Position, Hint>, index: usize, cell: Cell) -> bool {
+        let (rule, positions, cells) = line;
+        let mut cells = cells.clone();
+        cells[index] = Some(cell);
+        let mut line_hints: Vec<Hint> = positions.iter().map(|position| hints[position]).collect();
+        line_hints[index] = Hint::from_cells([cell].into_iter());
+
+        rule.project(&cells, &line_hints)
+            .into_iter()
+            .zip(line_hints)
+            .all(|(projected, hint)| !(projected & hint).is_empty())
+    }
+}
+
+impl<R: Region> Technique<R> for IntersectionTechnique {
+    fn name(&self) -> &'static str {
+        "intersection"
+    }
+
+    fn cost(&self) -> f64 {
+        1.5
+    }
+
+    fn apply(&self, solver: &mut Solver<R>) -> bool {
+        let hints = solver.computed_hints();
+        let lines = solver.lines();
+
+        let mut crossing: HashMap<Position, Vec<(&Line, usize)>> = HashMap::new();
+        for line in &lines {
+            for (index, position) in line.1.iter().enumerate() {
+                crossing.entry(*position).or_default().push((line, index));
+            }
+        }
+
+        let mut steps = vec![];
+        for (position, hint) in &hints {
+            if solver.solution().cells().contains_key(position) {
+                continue;
+            }
+
+            let fitting: Vec<Cell> = Cell::all()
+                .into_iter()
+                .filter(|cell| hint.cell(*cell))
+                .filter(|cell| {
+                    crossing
+                        .get(position)
+                        .into_iter()
+                        .flatten()
+                        .all(|(line, index)| Self::fits(line, &hints, *index, *cell))
+                })
+                .collect();
+
+            if let [cell] = fitting[..] {
+                steps.push(SolveStep::new(Technique::<R>::name(self), *position, cell));
+            }
+        }
+
+        let solved = !steps.is_empty();
+        for step in steps {
+            solver.place(step);
+        }
+        solved
+    }
+}
+
+// Solves positions by counting a color across lines in two directions.
+// When the remaining cells of a color on one or two lines in a direction
+// can only be at positions that the lines of another direction cross,
+// and those lines have no more of the color left than the first ones,
+// all of their cells of the color are at these crossings, so none of
+// their other positions can take it. Only lines whose clues show the
+// count of each color are counted. It is not one of the default
+// techniques.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct CountingTechnique;
+
+// A line whose clue shows how many cells of each color it has left.
+struct CountedLine {
+    positions: Vec<Position>,
+    remaining: Clue,
+}
+
+impl CountingTechnique {
+    // The lines of the solver in the given direction whose clues show
+    // their counts.
+    fn counted_lines<R: Region>(solver: &Solver<R>, direction: Direction) -> Vec<CountedLine> {
+        let puzzle = solver.puzzle();
+
+        puzzle
+            .board()
+            .region()
+            .segments(direction)
+            .filter_map(|(distance, segment)| {
+                let key = (direction, distance);
+                let (kind, _revealed) = puzzle.clue_rules().get(&key)?.kind()?;
+                if kind == ClueKind::Totals {
+                    return None;
+                }
+
+                let positions: Vec<Position> = segment.into_iter().collect();
+                let solved = positions
+                    .iter()
+                    .filter_map(|position| solver.solution().cells().get(position).copied());
+                let remaining = puzzle
+                    .clues()
+                    .get(&key)?
+                    .checked_sub(Clue::from_cells(solved))?;
+
+                Some(CountedLine {
+                    positions,
+                    remaining,
+                })
+            })
+            .collect()
+    }
+}
+
+impl<R: Region> Technique<R> for CountingTechnique {
+    fn name(&self) -> &'static str {
+        "counting"
+    }
+
+    fn cost(&self) -> f64 {
+        2.5
+    }
+
+    fn apply(&self, solver: &mut Solver<R>) -> bool {
+        let mut hints = solver.computed_hints();
+        let unsolved: Vec<Position> = hints
+            .keys()
+            .copied()
+            .filter(|position| !solver.solution().cells().contains_key(position))
+            .collect();
+
+        let lines: HashMap<Direction, Vec<CountedLine>> = Direction::normalized()
+            .into_iter()
+            .map(|direction| (direction, Self::counted_lines(solver, direction)))
+            .collect();
+        // The index of the counted line through each position, in each
+        // of the directions.
+        let through: HashMap<Direction, HashMap<Position, usize>> = lines
+            .iter()
+            .map(|(direction, lines)| {
+                let indices = lines.iter().enumerate().flat_map(|(index, line)| {
+                    line.positions
+                        .iter()
+                        .map(move |position| (*position, index))
+                });
+                (*direction, indices.collect())
+            })
+            .collect();
+
+        for cell in solver.puzzle().palette().cells().iter().copied() {
+            let others = Hint::from_cells(Cell::all().into_iter().filter(|other| *other != cell));
+
+            for (direction, across) in Direction::normalized()
+                .into_iter()
+                .flat_map(|direction| Direction::normalized().map(|across| (direction, across)))
+                .filter(|(direction, across)| direction != across)
+            {
+                let (lines, crossing) = (&lines[&direction], &lines[&across]);
+
+                for first in 0..lines.len() {
+                    for second in first..lines.len() {
+                        let counted: Vec<&CountedLine> = match first == second {
+                            true => vec![&lines[first]],
+                            false => vec![&lines[first], &lines[second]],
+                        };
+                        let count: Count =
+                            counted.iter().map(|line| line.remaining.cell(cell)).sum();
+                        let candidates: Vec<Position> = counted
+                            .iter()
+                            .flat_map(|line| line.positions.iter().copied())
+                            .filter(|position| {
+                                unsolved.contains(position) && hints[position].cell(cell)
+                            })
+                            .collect();
+                        if count == 0 || candidates.is_empty() {
+                            continue;
+                        }
+
+                        let covering: Option<Vec<usize>> = candidates
+                            .iter()
+                            .map(|position| through[&across].get(position).copied())
+                            .collect();
+                        let Some(mut covering) = covering else {
+                            continue;
+                        };
+                        covering.sort_unstable();
+                        covering.dedup();
+                        let covered: Count = covering
+                            .iter()
+                            .map(|index| crossing[*index].remaining.cell(cell))
+                            .sum();
+                        if covered != count {
+                            continue;
+                        }
+
+                        for index in covering {
+                            for position in &crossing[index].positions {
+                                if unsolved.contains(position) && !candidates.contains(position) {
+                                    let hint = hints[position] & others;
+                                    hints.insert(*position, hint);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let steps: Vec<SolveStep> = unsolved
+            .into_iter()
+            .filter_map(|position| {
+                let cell = hints[&position].solution()?;
+                Some(SolveStep::new(Technique::<R>::name(self), position, cell))
+            })
+            .collect();
+
+        let solved = !steps.is_empty();
+        for step in steps {
+            solver.place(step);
+        }
+        solved
+    }
+}
+
+// Solves positions by trial and error, one position at a time: a color
+// is ruled out for a position when placing it there and solving on with
+// hints alone leads to a contradiction. Guesses are never nested. It is
+// not one of the default techniques.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct TrialTechnique;
+
+impl<R: Region> Technique<R> for TrialTechnique {
+    fn name(&self) -> &'static str {
+        "trial"
+    }
+
+    fn cost(&self) -> f64 {
+        5.0
+    }
+
+    fn apply(&self, solver: &mut Solver<R>) -> bool {
+        let hints = solver.computed_hints();
+        let propagating = solver
+            .clone()
+            .with_techniques(vec![Arc::new(HintsTechnique)]);
+
+        let mut steps = vec![];
+        for (position, hint) in &hints {
+            if solver.solution().cells().contains_key(position) {
+                continue;
+            }
+
+            let possible: Vec<Cell> = Cell::all()
+                .into_iter()
+                .filter(|cell| hint.cell(*cell))
+                .filter(|cell| {
+                    !matches!(
+                        propagating.what_if([(*position, *cell)].into_iter()),
+                        WhatIf::Contradiction(_)
+                    )
+                })
+                .collect();
+
+            if let [cell] = possible[..] {
+                steps.push(SolveStep::new(Technique::<R>::name(self), *position, cell));
+            }
+        }
+
+        let solved = !steps.is_empty();
+        for step in steps {
+            solver.place(step);
+        }
+        solved
+    }
+}
+
+// The techniques of a solver unless it is given others.
+pub fn default_techniques<R: Region>() -> Vec<Arc<dyn Technique<R>>> {
+    vec![Arc::new(HintsTechnique), Arc::new(CluesTechnique)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::board::Board;
+    use crate::puzzle::puzzle::Puzzle;
+
+    // Reveals a position of the solution at a time.
+    #[derive(Debug)]
+    struct Reveal(Board);
+
+    impl Technique for Reveal {
+        fn name(&self) -> &'static str {
+            "reveal"
+        }
+
+        fn cost(&self) -> f64 {
+            10.0
+        }
+
+        fn apply(&self, solver: &mut Solver) -> bool {
+            let unsolved = self
+                .0
+                .cells()
+                .iter()
+                .find(|(position, _cell)| !solver.solution().cells().contains_key(position));
+
+            match unsolved {
+                Some((position, cell)) => {
+                    solver.place(SolveStep::new(self.name(), *position, *cell));
+                    true
+                }
+                None => false,
+            }
+        }
+    }
+
+    #[test]
+    fn techniques() {
+        let board = Board::rings_fixture();
+
+        // Without clues, only revealing cells makes progress.
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+        for (key, _clue) in board.clues() {
+            puzzle.remove_clue_rule(key);
+        }
+
+        let mut solver = Solver::new(puzzle.clone());
+        assert_eq!(2, solver.techniques().len());
+        assert!(!solver.solve());
+
+        let mut techniques = default_techniques();
+        techniques.push(Arc::new(Reveal(board.clone())));
+        let mut solver = Solver::new(puzzle).with_techniques(techniques);
+        assert!(solver.solve());
+        assert_eq!(board.cells(), solver.solution().cells());
+        assert_eq!(board.cells().len(), solver.trace().len());
+        assert!(solver
+            .trace()
+            .iter()
+            .all(|step| step.technique() == "reveal"));
+    }
+
+    #[test]
+    fn intersection() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+
+        // Trying colors on all the lines through a position at once
+        // solves whatever projecting them one at a time does.
+        let techniques: Vec<Arc<dyn Technique>> = vec![Arc::new(IntersectionTechnique)];
+        let mut solver = Solver::new(puzzle).with_techniques(techniques);
+        assert!(solver.solve());
+        assert_eq!(board.cells(), solver.solution().cells());
+        for step in solver.trace() {
+            assert_eq!("intersection", step.technique());
+            assert_eq!(board.cells()[&step.position()], step.cell());
+        }
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn counting() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(140);
+        let board = Board::random(&mut rng, 3).unwrap();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+
+        let mut solver = Solver::new(puzzle.clone());
+        assert!(!solver.solve());
+
+        // Counting colors across lines gets further than the default
+        // techniques alone, and only to cells of the solution.
+        let mut techniques = default_techniques();
+        techniques.push(Arc::new(CountingTechnique));
+        let mut counting = Solver::new(puzzle).with_techniques(techniques);
+        counting.solve();
+        assert!(counting.solution().cells().len() > solver.solution().cells().len());
+        assert!(counting
+            .trace()
+            .iter()
+            .any(|step| step.technique() == "counting"));
+        for (position, cell) in counting.solution().cells() {
+            assert_eq!(board.cells()[position], *cell);
+        }
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn trial() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(140);
+        let board = Board::random(&mut rng, 3).unwrap();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+
+        let mut solver = Solver::new(puzzle.clone());
+        assert!(!solver.solve());
+
+        let mut techniques = default_techniques();
+        techniques.push(Arc::new(TrialTechnique));
+        let mut trial = Solver::new(puzzle).with_techniques(techniques);
+        trial.solve();
+        assert!(trial.solution().cells().len() > solver.solution().cells().len());
+        assert!(trial.trace().iter().any(|step| step.technique() == "trial"));
+        for (position, cell) in trial.solution().cells() {
+            assert_eq!(board.cells()[position], *cell);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::fmt::Display;
+use std::sync::Arc;
+
+use crate::grid::{Direction, Distance, Position};
+use crate::i18n;
+
+use super::diagnosis::{self, Constraint};
+use super::rules::ClueRule;
+use super::Cell;
+
+// A position solved by the solver, with the technique and the line
+// behind it.
+#[derive(Debug, Clone)]
+pub struct SolveStep {
+    technique: &'static str,
+    position: Position,
+    cell: Cell,
+    line: Option<((Direction, Distance), Arc<dyn ClueRule>)>,
+    colors: usize,
+}
+
+impl SolveStep {
+    // A step of the technique of the given name.
+    pub fn new(technique: &'static str, position: Position, cell: Cell) -> Self {
+        SolveStep {
+            technique,
+            position,
+            cell,
+            line: None,
+            colors: 0,
+        }
+    }
+
+    // The step follows from the rule of a single line, described with
+    // the given number of colors.
+    pub fn with_line(
+        mut self,
+        key: (Direction, Distance),
+        rule: Arc<dyn ClueRule>,
+        colors: usize,
+    ) -> Self {
+        self.line = Some((key, rule));
+        self.colors = colors;
+        self
+    }
+
+    pub fn technique(&self) -> &'static str {
+        self.technique
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn cell(&self) -> Cell {
+        self.cell
+    }
+
+    // The line whose clue solved the position, for steps that follow
+    // from a single clue.
+    pub fn line(&self) -> Option<(Direction, Distance)> {
+        self.line.as_ref().map(|(key, _rule)| *key)
+    }
+}
+
+impl Display for SolveStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cell = diagnosis::cell_name(self.cell);
+        let (x, y, z) = self.position.coordinates();
+
+        let (clue, rule) = match &self.line {
+            Some(((direction, distance), rule)) => (
+                Constraint::Clue(*direction, *distance).to_string(),
+                rule.describe(self.colors),
+            ),
+            None => (String::new(), String::new()),
+        };
+
+        f.write_str(&i18n::message(
+            &format!("step.{}", self.technique),
+            &[
+                ("cell", &cell),
+                ("x", &x),
+                ("y", &y),
+                ("z", &z),
+                ("clue", &clue),
+                ("rule", &rule),
+            ],
+        ))
+    }
+}
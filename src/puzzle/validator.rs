@@ -1,16 +1,60 @@
-use super::{puzzle::Puzzle, solver::Solver};
+use std::fmt::Display;
+use std::sync::Arc;
 
-pub trait ValidatorStrategy {
-    fn is_valid(&self, puzzle: Puzzle) -> Option<bool>;
+use super::{
+    diagnosis::cell_name, difficulty::Difficulty, puzzle::Puzzle, solver::Solver,
+    technique::Technique, Cell, Clue,
+};
+use crate::grid::hexagon::Hexagon;
+use crate::grid::isometry::Isometry;
+use crate::grid::region::Region;
+use crate::grid::Direction;
+use crate::i18n;
+
+// Tells whether a puzzle is valid, invalid, or cannot be told yet as
+// revealing more of its cells may still make it valid. Strategies borrow
+// the puzzle, and only copy it when they need a solver of their own.
+pub trait ValidatorStrategy<R: Region = Hexagon>: Send + Sync {
+    // A short name for the strategy, for telling which of them rejected
+    // a puzzle.
+    fn name(&self) -> &'static str;
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool>;
+
+    // A short explanation of the verdict on the puzzle, for telling why
+    // puzzles are rejected. Nothing by default.
+    fn reason(&self, _puzzle: &Puzzle<R>) -> String {
+        String::new()
+    }
+}
+
+// Explains whether a puzzle has to be solved with a kind of pass, from
+// the verdict of a strategy that requires it or requires it not to be.
+fn solving_reason(verdict: Option<bool>, required: bool, kind: &str) -> String {
+    match verdict {
+        Some(valid) if valid == required => {
+            i18n::message(&format!("validation.{kind}-solving.needed"), &[])
+        }
+        Some(_) => i18n::message(&format!("validation.{kind}-solving.not-needed"), &[]),
+        None => i18n::message("validation.unsolvable", &[]),
+    }
 }
 
 // Check that the puzzle requires (or does not require) solving through
 // clue-wide constraints in order to be solved.
 pub struct RequireClueSolving(pub bool);
 
-impl ValidatorStrategy for RequireClueSolving {
-    fn is_valid(&self, puzzle: Puzzle) -> Option<bool> {
-        let mut solver = Solver::new(puzzle);
+impl<R: Region> ValidatorStrategy<R> for RequireClueSolving {
+    fn name(&self) -> &'static str {
+        "require-clue-solving"
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        solving_reason(self.is_valid(puzzle), self.0, "clue")
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        let mut solver = Solver::new(puzzle.clone());
         while !solver.solution().is_solved() {
             if solver.solve_hints() {
                 continue;
@@ -31,9 +75,17 @@ impl ValidatorStrategy for RequireClueSolving {
 // overlapping hints in order to be solved.
 pub struct RequireHintSolving(pub bool);
 
-impl ValidatorStrategy for RequireHintSolving {
-    fn is_valid(&self, puzzle: Puzzle) -> Option<bool> {
-        let mut solver = Solver::new(puzzle);
+impl<R: Region> ValidatorStrategy<R> for RequireHintSolving {
+    fn name(&self) -> &'static str {
+        "require-hint-solving"
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        solving_reason(self.is_valid(puzzle), self.0, "hint")
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        let mut solver = Solver::new(puzzle.clone());
         while !solver.solution().is_solved() {
             if solver.solve_clues() {
                 continue;
@@ -49,49 +101,1001 @@ impl ValidatorStrategy for RequireHintSolving {
     }
 }
 
-// Check that at most the given number of computed clues (the clues after
-// factoring in the already placed cells) have less than two colors.
+// Check that the puzzle has exactly one solution, counting its solutions
+// by backtracking rather than deduction alone. Puzzles with several of
+// them are undecided, as revealing more cells may rule out all but one.
+pub struct RequireUniqueSolution;
+
+impl RequireUniqueSolution {
+    // The number of solutions of the puzzle, up to two.
+    fn solutions<R: Region>(puzzle: &Puzzle<R>) -> usize {
+        Solver::new(puzzle.clone()).solutions().take(2).count()
+    }
+}
+
+impl<R: Region> ValidatorStrategy<R> for RequireUniqueSolution {
+    fn name(&self) -> &'static str {
+        "require-unique-solution"
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        match Self::solutions(puzzle) {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        match Self::solutions(puzzle) {
+            0 => i18n::message("validation.solutions.none", &[]),
+            1 => i18n::message("validation.solutions.unique", &[]),
+            _ => i18n::message("validation.solutions.several", &[]),
+        }
+    }
+}
+
+// The names of the techniques of the passes a solver with the given
+// techniques takes to solve the puzzle, in the order it first takes
+// them, or none if it cannot solve the puzzle.
+fn techniques_used<R: Region>(
+    techniques: &[Arc<dyn Technique<R>>],
+    puzzle: &Puzzle<R>,
+) -> Option<Vec<&'static str>> {
+    let mut solver = Solver::new(puzzle.clone()).with_techniques(techniques.to_vec());
+    let mut used = vec![];
+
+    while let Some(technique) = solver.step() {
+        if !used.contains(&technique.name()) {
+            used.push(technique.name());
+        }
+    }
+
+    solver.solution().is_solved().then_some(used)
+}
+
+fn techniques_reason<R: Region>(
+    techniques: &[Arc<dyn Technique<R>>],
+    puzzle: &Puzzle<R>,
+) -> String {
+    match techniques_used(techniques, puzzle) {
+        Some(used) => i18n::message("validation.techniques", &[("techniques", &used.join(", "))]),
+        None => i18n::message("validation.unsolvable", &[]),
+    }
+}
+
+// Check that solving the puzzle with the given techniques, each pass
+// taken with the first of them that solves anything, takes a pass of
+// the named technique. Puzzles that cannot be solved yet are undecided.
+pub struct RequireTechnique<R: Region = Hexagon> {
+    techniques: Vec<Arc<dyn Technique<R>>>,
+    name: &'static str,
+}
+
+impl<R: Region> RequireTechnique<R> {
+    pub fn new(techniques: Vec<Arc<dyn Technique<R>>>, name: &'static str) -> Self {
+        RequireTechnique { techniques, name }
+    }
+}
+
+impl<R: Region> ValidatorStrategy<R> for RequireTechnique<R> {
+    fn name(&self) -> &'static str {
+        "require-technique"
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        techniques_used(&self.techniques, puzzle).map(|used| used.contains(&self.name))
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        techniques_reason(&self.techniques, puzzle)
+    }
+}
+
+// Check that solving the puzzle with the given techniques, as with
+// `RequireTechnique`, never takes a pass of the named technique. Puzzles
+// that need it are undecided, as revealing more cells may avoid it.
+pub struct ForbidTechnique<R: Region = Hexagon> {
+    techniques: Vec<Arc<dyn Technique<R>>>,
+    name: &'static str,
+}
+
+impl<R: Region> ForbidTechnique<R> {
+    pub fn new(techniques: Vec<Arc<dyn Technique<R>>>, name: &'static str) -> Self {
+        ForbidTechnique { techniques, name }
+    }
+}
+
+impl<R: Region> ValidatorStrategy<R> for ForbidTechnique<R> {
+    fn name(&self) -> &'static str {
+        "forbid-technique"
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        techniques_used(&self.techniques, puzzle)
+            .filter(|used| !used.contains(&self.name))
+            .map(|_used| true)
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        techniques_reason(&self.techniques, puzzle)
+    }
+}
+
+// Check that at most the given number of clues leave their unsolved
+// positions a single color on their own (after factoring in the already
+// placed cells).
 pub struct MaximumSolvedClues(pub usize);
 
-impl ValidatorStrategy for MaximumSolvedClues {
-    fn is_valid(&self, puzzle: Puzzle) -> Option<bool> {
-        let solver = Solver::new(puzzle);
-        Some(
-            solver
-                .computed_clues()
-                .into_iter()
-                .filter(|(_key, clue)| clue.is_solved())
-                .count()
-                <= self.0,
+impl<R: Region> ValidatorStrategy<R> for MaximumSolvedClues {
+    fn name(&self) -> &'static str {
+        "maximum-solved-clues"
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        let solver = Solver::new(puzzle.clone());
+        i18n::message(
+            "validation.solved-clues",
+            &[("count", &solver.solved_clues()), ("maximum", &self.0)],
         )
     }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        let solver = Solver::new(puzzle.clone());
+        Some(solver.solved_clues() <= self.0)
+    }
 }
 
 // Check that at most the given number of positions are already solved.
 pub struct MaximumSolvedPositions(pub usize);
 
-impl ValidatorStrategy for MaximumSolvedPositions {
-    fn is_valid(&self, puzzle: Puzzle) -> Option<bool> {
+impl<R: Region> ValidatorStrategy<R> for MaximumSolvedPositions {
+    fn name(&self) -> &'static str {
+        "maximum-solved-positions"
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        i18n::message(
+            "validation.givens",
+            &[
+                ("count", &puzzle.board().cells().len()),
+                ("maximum", &self.0),
+            ],
+        )
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
         Some(puzzle.board().cells().len() <= self.0)
     }
 }
 
-pub struct Validator(Vec<Box<dyn ValidatorStrategy>>);
+// Check that at least the given number of positions are already solved.
+// Puzzles with fewer are undecided, as refining them reveals more.
+pub struct MinimumGivens(pub usize);
+
+impl<R: Region> ValidatorStrategy<R> for MinimumGivens {
+    fn name(&self) -> &'static str {
+        "minimum-givens"
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        (puzzle.board().cells().len() >= self.0).then_some(true)
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        i18n::message(
+            "validation.givens-minimum",
+            &[
+                ("count", &puzzle.board().cells().len()),
+                ("minimum", &self.0),
+            ],
+        )
+    }
+}
+
+// Check that the share of the board's positions that are already solved
+// is at least the first value and at most the second, as fractions from
+// zero to one. Puzzles with too few givens are undecided, as refining
+// them reveals more.
+pub struct GivenShare(pub f64, pub f64);
+
+impl GivenShare {
+    fn share<R: Region>(puzzle: &Puzzle<R>) -> f64 {
+        let area = puzzle.board().region().clone().into_iter().count();
+        puzzle.board().cells().len() as f64 / area as f64
+    }
+}
+
+impl<R: Region> ValidatorStrategy<R> for GivenShare {
+    fn name(&self) -> &'static str {
+        "given-share"
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        let share = Self::share(puzzle);
+
+        if share > self.1 {
+            Some(false)
+        } else if share >= self.0 {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        let percent = |share: f64| format!("{:.0}%", share * 100.0);
+        i18n::message(
+            "validation.given-share",
+            &[
+                ("share", &percent(Self::share(puzzle))),
+                ("minimum", &percent(self.0)),
+                ("maximum", &percent(self.1)),
+            ],
+        )
+    }
+}
+
+// Check that no line is solved by its clue alone, such as a line with
+// cells of a single color or a corner line of a single cell. Unlike
+// `MaximumSolvedClues(0)`, the givens of the puzzle are left out, so
+// lines that its givens leave to their clues, or that are given whole,
+// do not count, while lines that are given away by their clue however
+// many givens there are always do.
+pub struct NoTriviallySolvedSegments;
+
+impl NoTriviallySolvedSegments {
+    // The number of lines whose clue alone solves all of their cells.
+    fn trivial<R: Region>(puzzle: &Puzzle<R>) -> usize {
+        let region = puzzle.board().region();
+
+        puzzle
+            .clue_rules()
+            .iter()
+            .filter(|((direction, distance), rule)| {
+                let Some(segment) = region.segment(*distance, *direction) else {
+                    return false;
+                };
+                let length = segment.length() as usize;
+                let cells = vec![None; length];
+                let hints = vec![puzzle.palette().hint(); length];
+
+                rule.project(&cells, &hints)
+                    .iter()
+                    .all(|hint| hint.solution().is_some())
+            })
+            .count()
+    }
+}
+
+impl<R: Region> ValidatorStrategy<R> for NoTriviallySolvedSegments {
+    fn name(&self) -> &'static str {
+        "no-trivially-solved-segments"
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        Some(Self::trivial(puzzle) == 0)
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        i18n::message(
+            "validation.trivial-segments",
+            &[("count", &Self::trivial(puzzle))],
+        )
+    }
+}
+
+// Check that the colors of the solution are evenly spread, with the
+// share of the most common color at most `max_skew` above that of the
+// least common one, as fractions from zero to one. The solution is
+// counted from the clues of the lines in one direction, which cover
+// every position once, so the puzzle's cells need not be solved.
+pub struct ColorBalance {
+    pub max_skew: f64,
+}
+
+impl ColorBalance {
+    fn colors<R: Region>(puzzle: &Puzzle<R>) -> Clue {
+        puzzle
+            .clues()
+            .iter()
+            .filter(|((direction, _distance), _clue)| *direction == Direction::XY)
+            .fold(Clue::zero(), |colors, (_key, clue)| colors + *clue)
+    }
+
+    // The skew between the colors of the puzzle's palette.
+    fn skew<R: Region>(puzzle: &Puzzle<R>, colors: Clue) -> f64 {
+        let shares: Vec<f64> = puzzle
+            .palette()
+            .cells()
+            .iter()
+            .map(|cell| colors.cell(*cell) as f64 / colors.count().max(1) as f64)
+            .collect();
+        let most = shares.iter().copied().fold(0.0, f64::max);
+        let least = shares.iter().copied().fold(1.0, f64::min);
+        most - least
+    }
+}
+
+impl<R: Region> ValidatorStrategy<R> for ColorBalance {
+    fn name(&self) -> &'static str {
+        "color-balance"
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        Some(Self::skew(puzzle, Self::colors(puzzle)) <= self.max_skew)
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        let colors = Self::colors(puzzle);
+        let counts: Vec<String> = puzzle
+            .palette()
+            .cells()
+            .iter()
+            .map(|cell| {
+                i18n::message(
+                    "validation.color-count",
+                    &[("count", &colors.cell(*cell)), ("color", &cell_name(*cell))],
+                )
+            })
+            .collect();
+        i18n::message(
+            "validation.color-balance",
+            &[
+                ("counts", &counts.join(", ")),
+                ("skew", &format!("{:.2}", Self::skew(puzzle, colors))),
+                ("maximum", &self.max_skew),
+            ],
+        )
+    }
+}
+
+// Check that the positions of the givens are symmetric under the given
+// rotation or reflection of the board, every given being mapped onto
+// another given, whatever their colors. Puzzles that are not yet are
+// undecided, as revealing more cells may make them so.
+pub struct SymmetricGivens(pub Isometry);
+
+impl SymmetricGivens {
+    // The number of givens whose image is not a given.
+    fn unmatched<R: Region>(&self, puzzle: &Puzzle<R>) -> usize {
+        let cells = puzzle.board().cells();
+        cells
+            .keys()
+            .filter(|position| !cells.contains_key(&self.0.apply(**position)))
+            .count()
+    }
+}
+
+impl<R: Region> ValidatorStrategy<R> for SymmetricGivens {
+    fn name(&self) -> &'static str {
+        "symmetric-givens"
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        (self.unmatched(puzzle) == 0).then_some(true)
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        i18n::message(
+            "validation.symmetric-givens",
+            &[("count", &self.unmatched(puzzle))],
+        )
+    }
+}
+
+// Check that the already solved positions include at least the given
+// number of distinct colors, unless there are none of them.
+pub struct GivenColorDiversity(pub usize);
+
+impl<R: Region> ValidatorStrategy<R> for GivenColorDiversity {
+    fn name(&self) -> &'static str {
+        "given-color-diversity"
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        let cells = puzzle.board().cells();
+        let colors = Cell::all()
+            .into_iter()
+            .filter(|cell| cells.values().any(|given| given == cell))
+            .count();
+
+        i18n::message(
+            "validation.given-colors",
+            &[("count", &colors), ("minimum", &self.0)],
+        )
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        let cells = puzzle.board().cells();
+        let colors = Cell::all()
+            .into_iter()
+            .filter(|cell| cells.values().any(|given| given == cell))
+            .count();
+
+        Some(cells.is_empty() || colors >= self.0)
+    }
+}
+
+// Check that the difficulty rating of the puzzle is at least the first
+// value and below the second. Revealing cells makes puzzles easier, so
+// puzzles that are too easy cannot be refined into valid ones, while
+// puzzles that are too difficult may yet be.
+pub struct DifficultyRange(pub f64, pub f64);
+
+impl<R: Region> ValidatorStrategy<R> for DifficultyRange {
+    fn name(&self) -> &'static str {
+        "difficulty-range"
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        match Difficulty::rate(puzzle) {
+            Some(difficulty) => i18n::message(
+                "validation.difficulty",
+                &[
+                    ("value", &difficulty.value()),
+                    ("minimum", &self.0),
+                    ("maximum", &self.1),
+                ],
+            ),
+            None => i18n::message("validation.unsolvable", &[]),
+        }
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        let difficulty = Difficulty::rate(puzzle)?.value();
+
+        if difficulty < self.0 {
+            Some(false)
+        } else if difficulty < self.1 {
+            Some(true)
+        } else {
+            None
+        }
+    }
+}
+
+// Check that the number of passes the solver takes to solve the puzzle
+// is at least the first value and below the second. As with
+// `DifficultyRange`, puzzles that take too many passes may yet be
+// refined into valid ones, while those that take too few cannot.
+pub struct SolveStepsRange(pub usize, pub usize);
+
+impl SolveStepsRange {
+    // The number of passes the solver takes, or none if it cannot solve
+    // the puzzle.
+    fn passes<R: Region>(puzzle: &Puzzle<R>) -> Option<usize> {
+        let mut solver = Solver::new(puzzle.clone());
+        let mut passes = 0;
+
+        while !solver.solution().is_solved() {
+            solver.step()?;
+            passes += 1;
+        }
+
+        Some(passes)
+    }
+}
+
+impl<R: Region> ValidatorStrategy<R> for SolveStepsRange {
+    fn name(&self) -> &'static str {
+        "solve-steps-range"
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        let passes = Self::passes(puzzle)?;
+
+        if passes < self.0 {
+            Some(false)
+        } else if passes < self.1 {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        match Self::passes(puzzle) {
+            Some(passes) => i18n::message(
+                "validation.solve-steps",
+                &[
+                    ("count", &passes),
+                    ("minimum", &self.0),
+                    ("maximum", &self.1),
+                ],
+            ),
+            None => i18n::message("validation.unsolvable", &[]),
+        }
+    }
+}
+
+// Finds puzzles valid where the strategy finds them invalid, and the
+// other way around. Puzzles the strategy cannot tell about yet stay
+// undecided. Refiners give up on puzzles that are found invalid before
+// their cells are revealed, so negating a strategy like
+// `MaximumSolvedPositions`, whose verdict changes as cells are revealed,
+// rejects puzzles that could have become valid.
+pub struct Not<R: Region = Hexagon>(pub Box<dyn ValidatorStrategy<R>>);
+
+impl<R: Region> ValidatorStrategy<R> for Not<R> {
+    fn name(&self) -> &'static str {
+        "not"
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        i18n::message("validation.not", &[("reason", &self.0.reason(puzzle))])
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        self.0.is_valid(puzzle).map(|valid| !valid)
+    }
+}
+
+// Finds puzzles valid when any of the strategies does, and invalid when
+// all of them do.
+pub struct AnyOf<R: Region = Hexagon>(pub Vec<Box<dyn ValidatorStrategy<R>>>);
+
+impl<R: Region> ValidatorStrategy<R> for AnyOf<R> {
+    fn name(&self) -> &'static str {
+        "any-of"
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        i18n::message(
+            "validation.any-of",
+            &[("reasons", &reasons(&self.0, puzzle))],
+        )
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        let mut verdict = Some(false);
+        for strategy in &self.0 {
+            match strategy.is_valid(puzzle) {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => verdict = None,
+            }
+        }
+        verdict
+    }
+}
+
+// Finds puzzles valid when all of the strategies do, and invalid when
+// any of them does, as a validator does with its own strategies.
+pub struct AllOf<R: Region = Hexagon>(pub Vec<Box<dyn ValidatorStrategy<R>>>);
+
+impl<R: Region> ValidatorStrategy<R> for AllOf<R> {
+    fn name(&self) -> &'static str {
+        "all-of"
+    }
+
+    fn reason(&self, puzzle: &Puzzle<R>) -> String {
+        i18n::message(
+            "validation.all-of",
+            &[("reasons", &reasons(&self.0, puzzle))],
+        )
+    }
+
+    fn is_valid(&self, puzzle: &Puzzle<R>) -> Option<bool> {
+        let mut verdict = Some(true);
+        for strategy in &self.0 {
+            match strategy.is_valid(puzzle) {
+                Some(true) => {}
+                Some(false) => return Some(false),
+                None => verdict = None,
+            }
+        }
+        verdict
+    }
+}
+
+// The reasons of each of the strategies, one after the other.
+fn reasons<R: Region>(strategies: &[Box<dyn ValidatorStrategy<R>>], puzzle: &Puzzle<R>) -> String {
+    strategies
+        .iter()
+        .map(|strategy| strategy.reason(puzzle))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// The verdict of each strategy of a validator on a puzzle, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationReport(Vec<(&'static str, Option<bool>, String)>);
+
+impl ValidationReport {
+    // The name, verdict and reason of each strategy, in the order of the
+    // validator's strategies.
+    pub fn entries(&self) -> &[(&'static str, Option<bool>, String)] {
+        &self.0
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.0
+            .iter()
+            .all(|(_name, verdict, _reason)| *verdict == Some(true))
+    }
+
+    pub fn is_not_invalid(&self) -> bool {
+        self.0
+            .iter()
+            .all(|(_name, verdict, _reason)| *verdict != Some(false))
+    }
+
+    // The names of the strategies that found the puzzle invalid.
+    pub fn rejected_by(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.0
+            .iter()
+            .filter(|(_name, verdict, _reason)| *verdict == Some(false))
+            .map(|(name, _verdict, _reason)| *name)
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, verdict, reason) in &self.0 {
+            let verdict = match verdict {
+                Some(true) => i18n::message("validation.valid", &[]),
+                Some(false) => i18n::message("validation.invalid", &[]),
+                None => i18n::message("validation.undecided", &[]),
+            };
+
+            match reason.is_empty() {
+                true => writeln!(f, "{name}: {verdict}")?,
+                false => writeln!(f, "{name}: {verdict} ({reason})")?,
+            }
+        }
 
-impl Validator {
-    pub fn new(strategies: Vec<Box<dyn ValidatorStrategy>>) -> Self {
+        Ok(())
+    }
+}
+
+pub struct Validator<R: Region = Hexagon>(Vec<Box<dyn ValidatorStrategy<R>>>);
+
+impl<R: Region> Validator<R> {
+    pub fn new(strategies: Vec<Box<dyn ValidatorStrategy<R>>>) -> Self {
         Validator(strategies)
     }
 
-    pub fn is_not_invalid(&self, puzzle: Puzzle) -> bool {
+    pub fn with_strategy(mut self, strategy: Box<dyn ValidatorStrategy<R>>) -> Self {
+        self.0.push(strategy);
+        self
+    }
+
+    pub fn is_not_invalid(&self, puzzle: &Puzzle<R>) -> bool {
         self.0
             .iter()
-            .all(|strategy| strategy.is_valid(puzzle.clone()) != Some(false))
+            .all(|strategy| strategy.is_valid(puzzle) != Some(false))
     }
 
-    pub fn is_valid(&self, puzzle: Puzzle) -> bool {
+    pub fn is_valid(&self, puzzle: &Puzzle<R>) -> bool {
         self.0
             .iter()
-            .all(|strategy| strategy.is_valid(puzzle.clone()) == Some(true))
+            .all(|strategy| strategy.is_valid(puzzle) == Some(true))
+    }
+
+    // The verdict of every strategy on the puzzle, with its reason.
+    pub fn report(&self, puzzle: &Puzzle<R>) -> ValidationReport {
+        ValidationReport(
+            self.0
+                .iter()
+                .map(|strategy| {
+                    (
+                        strategy.name(),
+                        strategy.is_valid(puzzle),
+                        strategy.reason(puzzle),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    // The first strategy that finds the puzzle invalid, if any, which
+    // makes `is_not_invalid` fail.
+    pub fn invalidated_by(&self, puzzle: &Puzzle<R>) -> Option<&dyn ValidatorStrategy<R>> {
+        self.0
+            .iter()
+            .find(|strategy| strategy.is_valid(puzzle) == Some(false))
+            .map(|strategy| strategy.as_ref())
+    }
+
+    // The first strategy that does not find the puzzle valid, if any,
+    // which makes `is_valid` fail.
+    pub fn not_validated_by(&self, puzzle: &Puzzle<R>) -> Option<&dyn ValidatorStrategy<R>> {
+        self.0
+            .iter()
+            .find(|strategy| strategy.is_valid(puzzle) != Some(true))
+            .map(|strategy| strategy.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Axis;
+    use crate::grid::Position;
+    use crate::puzzle::board::Board;
+    use crate::puzzle::technique::{self, IntersectionTechnique};
+
+    #[test]
+    fn given_color_diversity() {
+        let mut board = Board::new(2).unwrap();
+        let strategy = GivenColorDiversity(2);
+
+        assert_eq!(
+            Some(true),
+            strategy.is_valid(&Puzzle::with_clues(board.clone()))
+        );
+
+        board.insert(Position::zero(), Cell::Red);
+        board.insert(Position::new((1, -1, 0)).unwrap(), Cell::Red);
+        assert_eq!(
+            Some(false),
+            strategy.is_valid(&Puzzle::with_clues(board.clone()))
+        );
+
+        board.insert(Position::new((1, 0, -1)).unwrap(), Cell::Blue);
+        assert_eq!(Some(true), strategy.is_valid(&Puzzle::with_clues(board)));
+    }
+
+    // Tells whether puzzles are valid, invalid or undecided, whatever
+    // the puzzle.
+    struct Verdict(Option<bool>);
+
+    impl ValidatorStrategy for Verdict {
+        fn name(&self) -> &'static str {
+            "verdict"
+        }
+
+        fn is_valid(&self, _puzzle: &Puzzle) -> Option<bool> {
+            self.0
+        }
+    }
+
+    #[test]
+    fn report() {
+        let mut board = Board::new(2).unwrap();
+        board.insert(Position::zero(), Cell::Red);
+        board.insert(Position::new((1, -1, 0)).unwrap(), Cell::Red);
+        let puzzle = Puzzle::with_clues(board);
+
+        let validator = Validator::new(vec![
+            Box::new(MaximumSolvedPositions(1)),
+            Box::new(GivenColorDiversity(1)),
+            Box::new(Verdict(None)),
+        ]);
+        let report = validator.report(&puzzle);
+
+        assert_eq!(validator.is_valid(&puzzle), report.is_valid());
+        assert_eq!(validator.is_not_invalid(&puzzle), report.is_not_invalid());
+        assert_eq!(
+            vec!["maximum-solved-positions"],
+            report.rejected_by().collect::<Vec<_>>()
+        );
+        assert_eq!("2 givens, at most 1", report.entries()[0].2);
+        assert_eq!(
+            "maximum-solved-positions: invalid (2 givens, at most 1)\n\
+             given-color-diversity: valid (1 colors among the givens, at least 1)\n\
+             verdict: undecided\n",
+            report.to_string()
+        );
+    }
+
+    #[test]
+    fn require_unique_solution() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+        assert_eq!(Some(true), RequireUniqueSolution.is_valid(&puzzle));
+
+        // Without clues, any cells left open can take any color.
+        for (key, _clue) in board.clues() {
+            puzzle.remove_clue_rule(key);
+        }
+        for (position, cell) in board.cells().iter().skip(2) {
+            puzzle.mut_board().insert(*position, *cell);
+        }
+        assert_eq!(None, RequireUniqueSolution.is_valid(&puzzle));
+        assert_eq!(
+            "has several solutions",
+            RequireUniqueSolution.reason(&puzzle)
+        );
+
+        for (position, cell) in board.cells() {
+            puzzle.mut_board().insert(*position, *cell);
+        }
+        assert_eq!(Some(true), RequireUniqueSolution.is_valid(&puzzle));
+    }
+
+    #[test]
+    fn difficulty_range() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board);
+        puzzle.clear();
+        let difficulty = Difficulty::rate(&puzzle).unwrap().value();
+
+        // Too easy puzzles only get easier as cells are revealed, while
+        // too difficult ones may yet fall within the range.
+        let within = DifficultyRange(difficulty, difficulty + 1.0);
+        assert_eq!(Some(true), within.is_valid(&puzzle));
+        let above = DifficultyRange(difficulty + 1.0, difficulty + 2.0);
+        assert_eq!(Some(false), above.is_valid(&puzzle));
+        let below = DifficultyRange(0.0, difficulty);
+        assert_eq!(None, below.is_valid(&puzzle));
+    }
+
+    #[test]
+    fn givens() {
+        let mut board = Board::new(1).unwrap();
+        board.insert(Position::zero(), Cell::Red);
+        let puzzle = Puzzle::with_clues(board.clone());
+
+        assert_eq!(Some(true), MinimumGivens(1).is_valid(&puzzle));
+        assert_eq!(None, MinimumGivens(2).is_valid(&puzzle));
+
+        // One of seven positions is given.
+        assert_eq!(Some(true), GivenShare(0.1, 0.2).is_valid(&puzzle));
+        assert_eq!(None, GivenShare(0.2, 0.5).is_valid(&puzzle));
+        assert_eq!(Some(false), GivenShare(0.0, 0.1).is_valid(&puzzle));
+        assert_eq!(
+            "14% of the positions given, from 10% to 20%",
+            GivenShare(0.1, 0.2).reason(&puzzle)
+        );
+    }
+
+    #[test]
+    fn techniques() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board);
+        puzzle.clear();
+
+        let defaults = technique::default_techniques();
+        let used = techniques_used(&defaults, &puzzle).unwrap();
+        assert!(!used.is_empty());
+
+        for name in used {
+            let required = RequireTechnique::new(defaults.clone(), name);
+            assert_eq!(Some(true), required.is_valid(&puzzle));
+            let forbidden = ForbidTechnique::new(defaults.clone(), name);
+            assert_eq!(None, forbidden.is_valid(&puzzle));
+        }
+
+        let intersection: Vec<Arc<dyn Technique>> = vec![Arc::new(IntersectionTechnique)];
+        assert_eq!(
+            Some(false),
+            RequireTechnique::new(intersection.clone(), "hints").is_valid(&puzzle)
+        );
+        assert_eq!(
+            Some(true),
+            ForbidTechnique::new(intersection.clone(), "hints").is_valid(&puzzle)
+        );
+        assert_eq!(
+            "solved with passes of intersection",
+            ForbidTechnique::new(intersection, "hints").reason(&puzzle)
+        );
+    }
+
+    #[test]
+    fn no_trivially_solved_segments() {
+        // The outer ring is blue, so the six lines along the sides of
+        // the board are of a single color.
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        puzzle.clear();
+
+        assert_eq!(Some(false), NoTriviallySolvedSegments.is_valid(&puzzle));
+        assert_eq!(
+            "6 lines solved by their clue alone",
+            NoTriviallySolvedSegments.reason(&puzzle)
+        );
+
+        // The lines through the center mix all three colors.
+        for ((direction, distance), _clue) in board.clues() {
+            if distance != 0 {
+                puzzle.remove_clue_rule((direction, distance));
+            }
+        }
+        assert_eq!(Some(true), NoTriviallySolvedSegments.is_valid(&puzzle));
+    }
+
+    #[test]
+    fn color_balance() {
+        // One red, six green and twelve blue cells.
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board);
+        puzzle.clear();
+
+        assert_eq!(Some(true), ColorBalance { max_skew: 0.6 }.is_valid(&puzzle));
+        assert_eq!(
+            Some(false),
+            ColorBalance { max_skew: 0.5 }.is_valid(&puzzle)
+        );
+        assert_eq!(
+            "1 red, 6 green, 12 blue cells, a skew of 0.58, at most 0.5",
+            ColorBalance { max_skew: 0.5 }.reason(&puzzle)
+        );
+    }
+
+    #[test]
+    fn symmetric_givens() {
+        let mut board = Board::new(2).unwrap();
+        let rotation = SymmetricGivens(Isometry::rotation(Position::zero(), 3));
+        let reflection = SymmetricGivens(Isometry::reflection(Position::zero(), Axis::X));
+        assert_eq!(
+            Some(true),
+            rotation.is_valid(&Puzzle::with_clues(board.clone()))
+        );
+
+        let position = Position::new((2, -2, 0)).unwrap();
+        board.insert(position, Cell::Red);
+        assert_eq!(None, rotation.is_valid(&Puzzle::with_clues(board.clone())));
+
+        board.insert(-position, Cell::Blue);
+        let puzzle = Puzzle::with_clues(board);
+        assert_eq!(Some(true), rotation.is_valid(&puzzle));
+        assert_eq!(None, reflection.is_valid(&puzzle));
+        assert_eq!(
+            "2 givens without a symmetric counterpart",
+            reflection.reason(&puzzle)
+        );
+    }
+
+    #[test]
+    fn solve_steps_range() {
+        let board = Board::rings_fixture();
+        let mut puzzle = Puzzle::with_clues(board.clone());
+        assert_eq!(Some(0), SolveStepsRange::passes(&puzzle));
+        assert_eq!(Some(false), SolveStepsRange(1, 10).is_valid(&puzzle));
+
+        puzzle.clear();
+        let passes = SolveStepsRange::passes(&puzzle).unwrap();
+        assert!(passes > 0);
+        assert_eq!(
+            Some(true),
+            SolveStepsRange(passes, passes + 1).is_valid(&puzzle)
+        );
+        assert_eq!(None, SolveStepsRange(0, passes).is_valid(&puzzle));
+        assert_eq!(
+            format!("{passes} solver passes, from 0 to below {passes}"),
+            SolveStepsRange(0, passes).reason(&puzzle)
+        );
+    }
+
+    #[test]
+    fn combinators() {
+        let puzzle = Puzzle::with_clues(Board::new(2).unwrap());
+        let verdicts = |verdicts: &[Option<bool>]| -> Vec<Box<dyn ValidatorStrategy>> {
+            verdicts
+                .iter()
+                .map(|verdict| Box::new(Verdict(*verdict)) as Box<dyn ValidatorStrategy>)
+                .collect()
+        };
+
+        assert_eq!(
+            Some(false),
+            Not(Box::new(Verdict(Some(true)))).is_valid(&puzzle)
+        );
+        assert_eq!(None, Not(Box::new(Verdict(None))).is_valid(&puzzle));
+
+        let any = |list: &[Option<bool>]| AnyOf(verdicts(list)).is_valid(&puzzle);
+        assert_eq!(Some(true), any(&[None, Some(false), Some(true)]));
+        assert_eq!(None, any(&[None, Some(false)]));
+        assert_eq!(Some(false), any(&[Some(false), Some(false)]));
+        assert_eq!(Some(false), any(&[]));
+
+        let all = |list: &[Option<bool>]| AllOf(verdicts(list)).is_valid(&puzzle);
+        assert_eq!(Some(false), all(&[None, Some(false), Some(true)]));
+        assert_eq!(None, all(&[None, Some(true)]));
+        assert_eq!(Some(true), all(&[Some(true), Some(true)]));
+        assert_eq!(Some(true), all(&[]));
+
+        // Puzzles of either many givens or none at all.
+        let validator = Validator::new(vec![Box::new(AnyOf(vec![
+            Box::new(MaximumSolvedPositions(0)),
+            Box::new(Not(Box::new(MaximumSolvedPositions(10)))),
+        ]))]);
+        let mut board = Board::new(2).unwrap();
+        assert!(validator.is_valid(&Puzzle::with_clues(board.clone())));
+        board.insert(Position::zero(), Cell::Red);
+        assert!(!validator.is_not_invalid(&Puzzle::with_clues(board)));
     }
 }
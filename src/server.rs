@@ -0,0 +1,190 @@
+use std::fmt::Write;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use tungstenite::{HandshakeError, Message, WebSocket};
+
+use crate::i18n;
+use crate::puzzle::binary;
+use crate::puzzle::puzzle::Puzzle;
+use crate::puzzle::solver::Solver;
+use crate::puzzle::trace::SolveStep;
+
+// Serves the connections of the listener, each on a thread of its own.
+// Connections that cannot be accepted, or that fail while they are
+// served, are logged and skipped.
+//
+// Clients send puzzles in the binary format, as binary messages, and
+// get a JSON text message back for each step of the solver, as it is
+// taken, for web front-ends to animate how the puzzle is solved:
+//
+//     {"technique":"clues","position":[1,-1,0],"cell":"red",
+//      "line":["XY",0],"explanation":"..."}
+//
+// where the line, of the clue that solved the position, is `null` for
+// steps that follow from more than one clue. The steps end with a
+// `{"solved":true}` message, or `false` when the solver got stuck, and
+// puzzles that cannot be decoded get an `{"error":"..."}` message.
+pub fn serve(listener: &TcpListener) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                thread::spawn(move || {
+                    if let Err(error) = handle(stream) {
+                        eprintln!(
+                            "{}",
+                            i18n::message("serve.connection-failed", &[("error", &error)])
+                        );
+                    }
+                });
+            }
+            Err(error) => eprintln!(
+                "{}",
+                i18n::message("serve.accept-failed", &[("error", &error)])
+            ),
+        }
+    }
+}
+
+// Serves a single connection, until the client closes it.
+pub fn handle(stream: TcpStream) -> Result<(), tungstenite::Error> {
+    let mut socket = tungstenite::accept(stream).map_err(|error| match error {
+        HandshakeError::Failure(error) => error,
+        // Blocking streams should not interrupt the handshake, but if
+        // one does the connection is given up like any other failure.
+        HandshakeError::Interrupted(_) => tungstenite::Error::Io(io::ErrorKind::Interrupted.into()),
+    })?;
+
+    loop {
+        let message = match socket.read() {
+            Ok(message) => message,
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                return Ok(());
+            }
+            Err(error) => return Err(error),
+        };
+
+        match message {
+            Message::Binary(bytes) => match binary::decode(&bytes) {
+                Ok(puzzle) => solve(&mut socket, puzzle)?,
+                Err(error) => socket.send(Message::text(error_message(&error.to_string())))?,
+            },
+            Message::Text(_) => socket.send(Message::text(error_message(&i18n::message(
+                "serve.text-message",
+                &[],
+            ))))?,
+            _ => {}
+        }
+    }
+}
+
+// Sends the steps of the solver, one message at a time, followed by
+// whether they solved the puzzle.
+fn solve(socket: &mut WebSocket<TcpStream>, puzzle: Puzzle) -> Result<(), tungstenite::Error> {
+    let mut solver = Solver::new(puzzle);
+    for step in solver.steps() {
+        socket.send(Message::text(step_message(&step)))?;
+    }
+
+    let solved = solver.solution().is_solved();
+    socket.send(Message::text(format!("{{\"solved\":{solved}}}")))
+}
+
+fn step_message(step: &SolveStep) -> String {
+    let (x, y, z) = step.position().coordinates();
+    let line = match step.line() {
+        Some((direction, distance)) => format!("[\"{direction:?}\",{distance}]"),
+        None => "null".to_string(),
+    };
+
+    format!(
+        "{{\"technique\":{},\"position\":[{x},{y},{z}],\"cell\":\"{}\",\"line\":{line},\"explanation\":{}}}",
+        json_string(step.technique()),
+        step.cell().name(),
+        json_string(&step.to_string()),
+    )
+}
+
+fn error_message(error: &str) -> String {
+    format!("{{\"error\":{}}}", json_string(error))
+}
+
+// The text as a JSON string, quoted and escaped.
+fn json_string(text: &str) -> String {
+    let mut string = String::with_capacity(text.len() + 2);
+    string.push('"');
+    for char in text.chars() {
+        match char {
+            '"' => string.push_str("\\\""),
+            '\\' => string.push_str("\\\\"),
+            '\n' => string.push_str("\\n"),
+            char if char.is_control() => write!(string, "\\u{:04x}", char as u32).unwrap(),
+            char => string.push(char),
+        }
+    }
+    string.push('"');
+    string
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::board::Board;
+
+    fn puzzle() -> Puzzle {
+        let mut puzzle = Puzzle::with_clues(Board::rings_fixture());
+        puzzle.clear();
+        puzzle
+    }
+
+    #[test]
+    fn json_string() {
+        assert_eq!(super::json_string("plain"), "\"plain\"");
+        assert_eq!(
+            super::json_string("\"quoted\"\\\n\t"),
+            "\"\\\"quoted\\\"\\\\\\n\\u0009\""
+        );
+    }
+
+    #[test]
+    fn serve() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || super::serve(&listener));
+
+        let stream = TcpStream::connect(address).unwrap();
+        let (mut socket, _response) =
+            tungstenite::client(format!("ws://{address}/"), stream).unwrap();
+
+        let puzzle = puzzle();
+        let expected: Vec<_> = Solver::new(puzzle.clone())
+            .steps()
+            .map(|step| step_message(&step))
+            .collect();
+        assert!(!expected.is_empty());
+
+        socket
+            .send(Message::binary(binary::encode(&puzzle).unwrap()))
+            .unwrap();
+        for step in &expected {
+            assert_eq!(socket.read().unwrap().into_text().unwrap().as_str(), step);
+        }
+        assert_eq!(
+            socket.read().unwrap().into_text().unwrap().as_str(),
+            "{\"solved\":true}"
+        );
+
+        socket.send(Message::binary(vec![0, 1, 2])).unwrap();
+        let error = socket.read().unwrap().into_text().unwrap();
+        assert!(error.starts_with("{\"error\":"));
+
+        socket.send(Message::text("puzzle")).unwrap();
+        assert_eq!(
+            socket.read().unwrap().into_text().unwrap().as_str(),
+            "{\"error\":\"puzzles are sent as binary messages\"}"
+        );
+
+        socket.close(None).unwrap();
+    }
+}